@@ -1,8 +1,7 @@
 use nanors_core::AgentLoop;
-use nanors_tools::StaticToolRegistry;
 use uuid::Uuid;
 
-use super::{build_agent_config, init_common_components};
+use super::{build_agent_config, build_tool_registry, init_common_components};
 
 /// Input parameters for Agent command strategy.
 ///
@@ -49,9 +48,12 @@ impl super::CommandStrategy for AgentStrategy {
         // Register tools (always enabled)
         let working_dir = input.working_dir.unwrap_or_else(|| ".".to_string());
 
-        let registry = StaticToolRegistry::with_default_tools(&working_dir);
+        let registry = build_tool_registry(&common.config, &common.mcp, &working_dir);
 
-        eprintln!("🔧 Tool calling enabled with 6 tools");
+        eprintln!(
+            "🔧 Tool calling enabled with {} tools",
+            registry.definitions().len()
+        );
 
         let agent = agent.with_tools(registry);
 
@@ -60,6 +62,7 @@ impl super::CommandStrategy for AgentStrategy {
                 let session_id = Uuid::now_v7();
                 let response = agent.process_message(&session_id, &msg).await?;
                 println!("{response}");
+                agent.wait_for_memory().await;
             }
             None => {
                 agent.run_interactive().await?;
@@ -0,0 +1,59 @@
+use nanors_config::Config;
+
+/// Schema action for the `db` command.
+#[derive(Debug, Clone, Copy)]
+pub enum DbAction {
+    /// Apply pending migrations
+    Migrate,
+    /// List migrations and whether they are applied
+    Status,
+    /// Revert the last `steps` migrations
+    Rollback { steps: usize },
+}
+
+/// Strategy for managing the database schema.
+///
+/// Memory-backed commands migrate automatically on connect; this command
+/// runs the same migrations by hand, shows their state, or reverts them.
+#[derive(Debug, Clone, Copy)]
+pub struct DbStrategy;
+
+impl super::CommandStrategy for DbStrategy {
+    type Input = DbAction;
+
+    async fn execute(&self, input: Self::Input) -> anyhow::Result<()> {
+        let config = Config::load()?;
+        let db = nanors_migration::connect(&config.database.url).await?;
+
+        match input {
+            DbAction::Migrate => {
+                let applied = nanors_migration::migrate(&db).await?;
+                if applied.is_empty() {
+                    println!("Database is up to date");
+                }
+                for version in applied {
+                    println!("Applied {}", migration_name(version));
+                }
+            }
+            DbAction::Status => {
+                for status in nanors_migration::status(&db).await? {
+                    let state = status.applied_at.as_deref().unwrap_or("pending");
+                    println!("{:<40} {state}", status.name);
+                }
+            }
+            DbAction::Rollback { steps } => {
+                for version in nanors_migration::rollback(&db, steps).await? {
+                    println!("Reverted {}", migration_name(version));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn migration_name(version: u32) -> &'static str {
+    nanors_migration::MIGRATIONS
+        .iter()
+        .find(|m| m.version == version)
+        .map_or("(unknown)", |m| m.name)
+}
@@ -0,0 +1,105 @@
+use nanors_config::{Config, RerankerConfig};
+use nanors_core::LLMProvider;
+use nanors_memory::eval::{CachedEmbedder, EvalOptions, HashEmbedder, evaluate, load_dataset};
+use nanors_providers::ZhipuProvider;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+
+use super::build_memory_manager;
+
+/// Throwaway store each evaluation run ingests its dataset into.
+const EVAL_DATABASE_URL: &str = "sqlite::memory:";
+
+/// Embeddings used for an evaluation run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EvalEmbeddings {
+    /// Deterministic hashed terms, the rules reranker and no LLM card
+    /// extraction; no model calls
+    Hash,
+    /// The provider's embedding model, cached in a file, with the
+    /// configured reranker and card extractors
+    Provider,
+}
+
+/// Evaluation action for the `eval` command.
+#[derive(Debug, Clone)]
+pub enum EvalAction {
+    /// Measure retrieval against a JSONL dataset
+    Retrieval {
+        dataset: PathBuf,
+        embeddings: EvalEmbeddings,
+        /// Embedding cache file; next to the dataset when unset
+        cache: Option<PathBuf>,
+        ks: Vec<usize>,
+        json: bool,
+    },
+}
+
+/// Strategy for evaluating retrieval quality.
+///
+/// Ingests a dataset into an in-memory `SQLite` store configured like the
+/// real one (`memory` section of the config) and reports recall@k, MRR and
+/// nDCG, so scoring changes can be compared run to run.
+#[derive(Debug, Clone, Copy)]
+pub struct EvalStrategy;
+
+impl super::CommandStrategy for EvalStrategy {
+    type Input = EvalAction;
+
+    async fn execute(&self, input: Self::Input) -> anyhow::Result<()> {
+        let EvalAction::Retrieval {
+            dataset,
+            embeddings,
+            cache,
+            ks,
+            json,
+        } = input;
+
+        let mut config = Config::load()?;
+        if embeddings == EvalEmbeddings::Hash {
+            config.memory.reranker = RerankerConfig::Rules;
+            config.memory.llm_card_extraction = false;
+        }
+        info!("Reranker: {}", describe_reranker(&config.memory.reranker));
+        let cases = load_dataset(&dataset)?;
+        let provider = ZhipuProvider::new(config.providers.zhipu.api_key.clone());
+        let manager = build_memory_manager(&config, &provider, EVAL_DATABASE_URL).await?;
+        let options = EvalOptions {
+            ks,
+            retrieval: config.memory.retrieval.clone(),
+        };
+
+        let report = match embeddings {
+            EvalEmbeddings::Hash => {
+                evaluate(&manager, &cases, &HashEmbedder::default(), &options).await?
+            }
+            EvalEmbeddings::Provider => {
+                let cache = cache.unwrap_or_else(|| dataset.with_extension("embeddings.json"));
+                let provider: Arc<dyn LLMProvider> = Arc::new(provider);
+                let embedder = CachedEmbedder::open(provider, &cache)?;
+                let report = evaluate(&manager, &cases, &embedder, &options).await;
+                // Keep what was fetched even if the run failed part way
+                embedder.save()?;
+                report?
+            }
+        };
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{report}");
+        }
+        Ok(())
+    }
+}
+
+/// The reranker a run uses, for the log.
+fn describe_reranker(reranker: &RerankerConfig) -> String {
+    match reranker {
+        RerankerConfig::Rules => "rules".to_string(),
+        RerankerConfig::Llm { model: Some(model) } => format!("llm ({model})"),
+        RerankerConfig::Llm { model: None } => "llm (default agent model)".to_string(),
+        RerankerConfig::CrossEncoder { url, .. } => format!("cross-encoder ({url})"),
+    }
+}
@@ -1,4 +1,4 @@
-use nanors_config::Config;
+use nanors_config::{Config, McpTransportConfig};
 use nanors_core::retrieval::adaptive::CutoffStrategy;
 use nanors_memory::MemoryManager;
 use nanors_memory::rerank::RuleBasedReranker;
@@ -12,6 +12,8 @@ use tracing::info;
 /// - Agent defaults (model, tokens, temperature, system prompt, history limit)
 /// - Memory retrieval configuration
 /// - Telegram configuration
+/// - MCP servers
+/// - HTTP API server
 ///
 /// # Design
 /// - Zero-allocation: No heap allocation beyond what business logic requires
@@ -105,11 +107,42 @@ impl super::CommandStrategy for InfoStrategy {
         } else {
             println!("  Allow From: {}", config.telegram.allow_from.join(", "));
         }
+        println!();
+
+        print_servers(&config);
 
         Ok(())
     }
 }
 
+/// Print MCP servers and the HTTP API settings.
+fn print_servers(config: &Config) {
+    println!("MCP Servers:");
+    if config.mcp.servers.is_empty() {
+        println!("  (none)");
+    }
+    for server in &config.mcp.servers {
+        let target = match &server.transport {
+            McpTransportConfig::Stdio { command, args, .. } => {
+                format!("stdio: {command} {}", args.join(" "))
+            }
+            McpTransportConfig::Http { url, .. } => format!("http: {url}"),
+        };
+        let status = if server.enabled { "" } else { " (disabled)" };
+        println!("  {}: {}{status}", server.name, target.trim_end());
+    }
+    println!();
+
+    println!("HTTP API:");
+    println!("  Listen: {}", config.server.listen);
+    let auth = if config.server.api_key.is_empty() {
+        "(none)"
+    } else {
+        "API key"
+    };
+    println!("  Auth: {auth}");
+}
+
 fn mask_database_url(url: &str) -> String {
     let Some((scheme, rest)) = url.split_once("://") else {
         return url.to_string();
@@ -0,0 +1,58 @@
+use nanors_config::Config;
+use nanors_core::LLMProvider;
+use nanors_core::memory::{MemoryScope, MemoryToolContext};
+use nanors_providers::ZhipuProvider;
+use nanors_tools::StaticToolRegistry;
+use nanors_tools::mcp::server::serve_stdio;
+use std::sync::Arc;
+use tracing::info;
+
+use super::build_memory_manager;
+
+/// Strategy for serving nanors memory as an MCP server.
+///
+/// This strategy exposes long-term memory and session history over stdio:
+/// - `memory_search`: semantic search over stored memories
+/// - `memory_add`: store a new memory
+/// - `memory_list`: list memories, newest first
+/// - `session_get`: fetch a session's message history
+///
+/// Configured MCP servers are not connected: the served tools don't use
+/// them, and a config listing `nanors mcp-serve` itself would otherwise
+/// spawn it recursively.
+///
+/// # Design
+/// - Static dispatch: All method calls are monomorphized
+/// - Stateless: No internal state
+#[derive(Debug, Clone, Copy)]
+pub struct McpServeStrategy;
+
+impl super::CommandStrategy for McpServeStrategy {
+    type Input = ();
+
+    async fn execute(&self, _input: Self::Input) -> anyhow::Result<()> {
+        let config = Config::load()?;
+        let provider = ZhipuProvider::new(config.providers.zhipu.api_key.clone());
+        let memory_manager =
+            Arc::new(build_memory_manager(&config, &provider, &config.database.url).await?);
+
+        let provider: Arc<dyn LLMProvider> = Arc::new(provider);
+        let context = MemoryToolContext {
+            memory: memory_manager.clone(),
+            sessions: memory_manager,
+            provider,
+            scope: MemoryScope::Global,
+        };
+
+        let mut registry = StaticToolRegistry::new();
+        for tool in context.tools() {
+            registry.register_dynamic(tool)?;
+        }
+
+        info!(
+            "Serving {} memory tools over MCP stdio",
+            registry.definitions().len()
+        );
+        serve_stdio(&registry, "nanors").await
+    }
+}
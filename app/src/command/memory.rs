@@ -0,0 +1,46 @@
+use nanors_core::memory::MemoryScope;
+use nanors_memory::enrichment::EnrichmentRunner;
+
+use super::init_common_components;
+
+/// Memory action for the `memory` command.
+#[derive(Debug, Clone)]
+pub enum MemoryAction {
+    /// Run the card enrichers over memories they have not processed yet
+    Enrich {
+        scope: Option<MemoryScope>,
+        batch_size: u64,
+    },
+}
+
+/// Strategy for maintaining stored memories.
+///
+/// `enrich` catches up the configured card extractors: memories stored
+/// before an extractor existed, earlier failures, and everything after an
+/// extractor's version changes.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryStrategy;
+
+impl super::CommandStrategy for MemoryStrategy {
+    type Input = MemoryAction;
+
+    async fn execute(&self, input: Self::Input) -> anyhow::Result<()> {
+        let common = init_common_components().await?;
+
+        match input {
+            MemoryAction::Enrich { scope, batch_size } => {
+                let mut runner =
+                    EnrichmentRunner::new(common.memory_manager).with_batch_size(batch_size);
+                if let Some(scope) = scope {
+                    runner = runner.with_scope(scope);
+                }
+                let report = runner.spawn().await??;
+                println!(
+                    "Enriched {} memories ({} cards), {} failed",
+                    report.enriched, report.cards, report.failed
+                );
+            }
+        }
+        Ok(())
+    }
+}
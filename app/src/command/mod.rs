@@ -4,36 +4,141 @@
 //! inspired by the `MetricAdapter` pattern. Each command is a separate strategy
 //! with its own type, enabling compile-time optimization and zero runtime overhead.
 
-use nanors_config::Config;
-use nanors_core::{AgentConfig, AgentLoop};
-use nanors_memory::MemoryManager;
-use nanors_memory::rerank::RuleBasedReranker;
+use nanors_config::{Config, McpTransportConfig, RerankerConfig};
+use nanors_core::{AgentConfig, AgentFactory, AgentLoop, MemoryQueue};
+use nanors_memory::extraction::LlmExtractor;
+use nanors_memory::query::language::LanguagePack;
+use nanors_memory::rerank::{CrossEncoderReranker, LlmReranker, Reranker, RuleBasedReranker};
+use nanors_memory::{DynMemoryManager, MemoryManager};
 use nanors_providers::ZhipuProvider;
+use nanors_tools::{McpManager, McpServer, McpTransport, StaticToolRegistry};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 
 /// Common components initialized for commands.
 #[derive(Clone)]
 pub struct CommonComponents {
     pub provider: ZhipuProvider,
-    pub memory_manager: Arc<MemoryManager>,
+    pub memory_manager: Arc<DynMemoryManager>,
+    pub mcp: Arc<McpManager>,
     pub config: Config,
 }
 
-/// Initialize common components (provider, `memory_manager`, config).
+/// Initialize common components (provider, `memory_manager`, MCP servers, config).
 pub async fn init_common_components() -> anyhow::Result<CommonComponents> {
     let config = Config::load()?;
     let provider = ZhipuProvider::new(config.providers.zhipu.api_key.clone());
     info!("Connecting to database");
     let memory_manager =
-        Arc::new(MemoryManager::<RuleBasedReranker>::new(&config.database.url).await?);
+        Arc::new(build_memory_manager(&config, &provider, &config.database.url).await?);
+    if !config.mcp.servers.is_empty() {
+        info!("Connecting to {} MCP servers", config.mcp.servers.len());
+    }
+    let mcp = Arc::new(McpManager::connect_all(&mcp_servers(&config)).await);
     Ok(CommonComponents {
         provider,
         memory_manager,
+        mcp,
         config,
     })
 }
 
+/// The enabled servers of the `mcp` section.
+fn mcp_servers(config: &Config) -> Vec<McpServer> {
+    config
+        .mcp
+        .servers
+        .iter()
+        .filter(|server| server.enabled)
+        .map(|server| McpServer {
+            name: server.name.clone(),
+            transport: match &server.transport {
+                McpTransportConfig::Stdio {
+                    command,
+                    args,
+                    env,
+                    cwd,
+                } => McpTransport::Stdio {
+                    command: command.clone(),
+                    args: args.clone(),
+                    env: env.clone(),
+                    cwd: cwd.clone(),
+                },
+                McpTransportConfig::Http { url, headers } => McpTransport::Http {
+                    url: url.clone(),
+                    headers: headers.clone(),
+                },
+            },
+            tool_prefix: server.tool_prefix.clone(),
+            timeout: Duration::from_secs(server.timeout_secs),
+        })
+        .collect()
+}
+
+/// Connect a memory manager to `database_url`, configured by the
+/// `memory` section: reranker, scoring, language packs and card extractors.
+async fn build_memory_manager(
+    config: &Config,
+    provider: &ZhipuProvider,
+    database_url: &str,
+) -> anyhow::Result<DynMemoryManager> {
+    let language = load_language(config)?;
+    let reranker = build_reranker(config, provider, &language);
+    let memory_manager = MemoryManager::<RuleBasedReranker>::with_reranker(database_url, reranker)
+        .await?
+        .with_scoring(config.memory.scoring)
+        .with_language(&language);
+    if config.memory.llm_card_extraction {
+        return Ok(memory_manager.with_card_extractor(LlmExtractor::new(
+            provider.clone(),
+            config.agents.defaults.model.clone(),
+        )));
+    }
+    Ok(memory_manager)
+}
+
+/// Merge the language packs listed in `memory.language_packs`.
+fn load_language(config: &Config) -> anyhow::Result<LanguagePack> {
+    let base_dir = Config::config_dir()?;
+    let packs = config
+        .memory
+        .language_packs
+        .iter()
+        .map(|spec| LanguagePack::resolve(spec, &base_dir))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let language = LanguagePack::merge(packs);
+    info!("Language packs: {}", language.name);
+    Ok(language)
+}
+
+/// Build the reranker selected by `memory.reranker`.
+fn build_reranker(
+    config: &Config,
+    provider: &ZhipuProvider,
+    language: &LanguagePack,
+) -> Box<dyn Reranker> {
+    match &config.memory.reranker {
+        RerankerConfig::Rules => Box::new(RuleBasedReranker::with_config(
+            config.memory.scoring.boosts,
+            language,
+        )),
+        RerankerConfig::Llm { model } => Box::new(LlmReranker::new(
+            provider.clone(),
+            model
+                .clone()
+                .unwrap_or_else(|| config.agents.defaults.model.clone()),
+        )),
+        RerankerConfig::CrossEncoder { url, model } => {
+            let reranker = CrossEncoderReranker::new(url.clone());
+            Box::new(match model {
+                Some(model) => reranker.with_model(model.clone()),
+                None => reranker,
+            })
+        }
+    }
+}
+
 /// Build `AgentConfig` from config with optional model override.
 pub fn build_agent_config(config: &Config, model_override: Option<String>) -> AgentConfig {
     AgentConfig {
@@ -43,9 +148,59 @@ pub fn build_agent_config(config: &Config, model_override: Option<String>) -> Ag
     }
 }
 
+/// Build the default tool registry plus MCP tools, with the configured tool
+/// filter applied.
+pub fn build_tool_registry(
+    config: &Config,
+    mcp: &McpManager,
+    working_dir: &str,
+) -> StaticToolRegistry {
+    let mut registry = StaticToolRegistry::with_default_tools(working_dir);
+    mcp.register_into(&mut registry);
+    let tools = &config.agents.defaults.tools;
+    registry.apply_filter(&tools.enabled, &tools.disabled);
+    registry
+}
+
+/// Build an agent factory for long-running frontends (HTTP API, chat bots).
+///
+/// Each agent gets memory retrieval and a fresh tool registry built from
+/// `working_dir` plus MCP tools. The agents share one memory queue, so each
+/// scope's exchanges are stored in turn order.
+pub fn build_agent_factory(
+    common: &CommonComponents,
+    working_dir: String,
+) -> AgentFactory<ZhipuProvider, Arc<DynMemoryManager>> {
+    let common = common.clone();
+    let memory_queue = Arc::new(MemoryQueue::default());
+    Arc::new(move |model| {
+        let config = &common.config;
+        let agent_config = build_agent_config(config, Some(model.to_string()));
+        let agent = AgentLoop::new(
+            common.provider.clone(),
+            common.memory_manager.clone(),
+            agent_config,
+        )
+        .with_memory(common.memory_manager.clone())
+        .with_memory_queue(Arc::clone(&memory_queue))
+        .with_retrieval_config(config.memory.retrieval.clone())
+        .with_memory_extraction(!config.memory.store_raw_messages)
+        .with_tools(build_tool_registry(config, &common.mcp, &working_dir));
+        match config.agents.defaults.history_limit {
+            Some(limit) => agent.with_history_limit(limit),
+            None => agent,
+        }
+    })
+}
+
 mod agent;
+mod db;
+mod eval;
 mod info;
 mod init;
+mod mcp_serve;
+mod memory;
+mod serve;
 mod telegram;
 mod version;
 
@@ -55,9 +210,9 @@ mod version;
 /// memory retrieval capabilities through Arc<dyn MemoryItemRepo>.
 fn setup_memory_storage(
     config: &Config,
-    agent: AgentLoop<ZhipuProvider, Arc<MemoryManager>>,
-    memory_manager: Arc<MemoryManager>,
-) -> AgentLoop<ZhipuProvider, Arc<MemoryManager>> {
+    agent: AgentLoop<ZhipuProvider, Arc<DynMemoryManager>>,
+    memory_manager: Arc<DynMemoryManager>,
+) -> AgentLoop<ZhipuProvider, Arc<DynMemoryManager>> {
     info!("Memory feature enabled, setting up memory retrieval");
 
     let retrieval_config = config.memory.retrieval.clone();
@@ -73,11 +228,17 @@ fn setup_memory_storage(
     agent
         .with_memory(memory_repo)
         .with_retrieval_config(retrieval_config)
+        .with_memory_extraction(!config.memory.store_raw_messages)
 }
 
 pub use agent::{AgentInput, AgentStrategy};
+pub use db::{DbAction, DbStrategy};
+pub use eval::{EvalAction, EvalEmbeddings, EvalStrategy};
 pub use info::InfoStrategy;
 pub use init::InitStrategy;
+pub use mcp_serve::McpServeStrategy;
+pub use memory::{MemoryAction, MemoryStrategy};
+pub use serve::{ServeInput, ServeStrategy};
 pub use telegram::{TelegramInput, TelegramStrategy};
 pub use version::VersionStrategy;
 
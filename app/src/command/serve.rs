@@ -0,0 +1,47 @@
+use nanors_server::ApiServer;
+use tracing::info;
+
+use super::{build_agent_factory, build_tool_registry, init_common_components};
+
+/// Input for the HTTP API server command.
+#[derive(Debug, Clone)]
+pub struct ServeInput {
+    /// Listen address (overrides config)
+    pub listen: Option<String>,
+    /// Working directory for tools
+    pub working_dir: Option<String>,
+}
+
+/// Strategy for serving the agent over an OpenAI-compatible HTTP API.
+///
+/// Every request runs through `AgentLoop` with memory retrieval and tools;
+/// the `user` field owns the session (named by the `X-Session-Id` header)
+/// and the memory scope.
+#[derive(Debug, Clone, Copy)]
+pub struct ServeStrategy;
+
+impl super::CommandStrategy for ServeStrategy {
+    type Input = ServeInput;
+
+    async fn execute(&self, input: Self::Input) -> anyhow::Result<()> {
+        let common = init_common_components().await?;
+        let config = &common.config;
+        let listen = input.listen.unwrap_or_else(|| config.server.listen.clone());
+        let working_dir = input.working_dir.unwrap_or_else(|| ".".to_string());
+
+        info!(
+            "Serving {} tools to API clients",
+            build_tool_registry(config, &common.mcp, &working_dir)
+                .definitions()
+                .len()
+        );
+
+        ApiServer::new(
+            build_agent_factory(&common, working_dir),
+            config.agents.defaults.model.clone(),
+        )
+        .with_api_key(Some(config.server.api_key.clone()))
+        .serve(&listen)
+        .await
+    }
+}
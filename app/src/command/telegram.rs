@@ -17,8 +17,10 @@
     clippy::missing_errors_doc
 )]
 
-use crate::command::{CommandStrategy, init_common_components};
-use nanors_telegram::TelegramBot;
+use crate::command::{CommandStrategy, build_agent_factory, init_common_components};
+use nanors_config::TelegramWebhookConfig;
+use nanors_telegram::{TelegramBot, WebhookOptions};
+use std::sync::Arc;
 use tracing::info;
 
 /// Input for Telegram bot command.
@@ -55,14 +57,25 @@ impl CommandStrategy for TelegramStrategy {
         info!("Starting Telegram bot...");
 
         // Create and run bot (tools use current directory)
-        let bot = TelegramBot::new(
+        let mut bot = TelegramBot::new(
             token,
-            common.provider,
+            build_agent_factory(&common, ".".to_string()),
+            common.config.agents.defaults.model.clone(),
+            common.provider.clone(),
             common.memory_manager,
-            common.config,
             &allow_from,
-            ".".to_string(),
-        )?;
+            common.config.telegram.max_concurrent_turns,
+        )?
+        .with_working_dir(".")
+        .with_speech_to_text(Arc::new(common.provider.clone()));
+
+        if let Some(webhook) = &common.config.telegram.webhook {
+            info!(
+                "Using webhook {} (listening on {})",
+                webhook.url, webhook.listen
+            );
+            bot = bot.with_webhook(webhook_options(webhook)?);
+        }
 
         info!("Telegram bot is running. Press Ctrl+C to stop.");
         bot.run().await?;
@@ -70,3 +83,21 @@ impl CommandStrategy for TelegramStrategy {
         Ok(())
     }
 }
+
+/// Map the webhook config section onto the bot's options.
+fn webhook_options(config: &TelegramWebhookConfig) -> anyhow::Result<WebhookOptions> {
+    let mut options =
+        WebhookOptions::new(&config.url, &config.listen)?.self_signed(config.self_signed);
+    if !config.path.is_empty() {
+        options = options.with_path(&config.path);
+    }
+    if !config.secret_token.is_empty() {
+        options = options.with_secret_token(&config.secret_token);
+    }
+    match (config.tls_cert.is_empty(), config.tls_key.is_empty()) {
+        (false, false) => options = options.with_tls(&config.tls_cert, &config.tls_key),
+        (true, true) => {}
+        _ => anyhow::bail!("telegram.webhook needs both \"tls_cert\" and \"tls_key\" for HTTPS"),
+    }
+    Ok(options)
+}
@@ -24,8 +24,9 @@ use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
 
 use command::{
-    AgentInput, AgentStrategy, CommandStrategy, InfoStrategy, InitStrategy, TelegramInput,
-    TelegramStrategy, VersionStrategy,
+    AgentInput, AgentStrategy, CommandStrategy, DbAction, DbStrategy, EvalAction, EvalEmbeddings,
+    EvalStrategy, InfoStrategy, InitStrategy, McpServeStrategy, MemoryAction, MemoryStrategy,
+    ServeInput, ServeStrategy, TelegramInput, TelegramStrategy, VersionStrategy,
 };
 
 #[derive(Parser)]
@@ -68,18 +69,101 @@ enum Commands {
         #[arg(short = 'a', long)]
         allow_from: Option<String>,
     },
+    /// Serve long-term memory as MCP tools over stdio
+    McpServe,
+    /// Serve the agent over an OpenAI-compatible HTTP API
+    Serve {
+        /// Listen address, e.g. 127.0.0.1:8080 (overrides config)
+        #[arg(short = 'l', long)]
+        listen: Option<String>,
+
+        /// Working directory for tools
+        #[arg(short = 'd', long)]
+        working_dir: Option<String>,
+    },
+    /// Manage the database schema
+    Db {
+        #[command(subcommand)]
+        action: DbCommand,
+    },
+    /// Maintain stored memories
+    Memory {
+        #[command(subcommand)]
+        action: MemoryCommand,
+    },
+    /// Measure retrieval quality
+    Eval {
+        #[command(subcommand)]
+        action: EvalCommand,
+    },
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Apply pending migrations
+    Migrate,
+    /// Show applied and pending migrations
+    Status,
+    /// Revert the most recent migrations
+    Rollback {
+        /// Number of migrations to revert
+        #[arg(short = 'n', long, default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum MemoryCommand {
+    /// Extract cards from memories not yet processed by the current extractors
+    Enrich {
+        /// Only enrich this scope, e.g. global or user:telegram:42
+        #[arg(short = 's', long)]
+        scope: Option<nanors_core::memory::MemoryScope>,
+
+        /// Memories processed per batch
+        #[arg(short = 'b', long, default_value_t = 100)]
+        batch_size: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum EvalCommand {
+    /// Report recall@k, MRR and nDCG of memory search on a JSONL dataset
+    Retrieval {
+        /// Dataset of (memories, query, expected memory ids) cases
+        dataset: std::path::PathBuf,
+
+        /// Embeddings to use
+        #[arg(short = 'e', long, value_enum, default_value_t = EvalEmbeddings::Hash)]
+        embeddings: EvalEmbeddings,
+
+        /// Embedding cache for `--embeddings provider` (default: next to the dataset)
+        #[arg(short = 'c', long)]
+        cache: Option<std::path::PathBuf>,
 
-    tracing::subscriber::set_global_default(subscriber)?;
+        /// Ranks to report recall and nDCG at (comma-separated)
+        #[arg(short = 'k', long, value_delimiter = ',', default_values_t = [1, 3, 5, 10])]
+        ks: Vec<usize>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
 
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    // stdout carries the protocol in MCP mode and the report in eval mode,
+    // so log to stderr there
+    let subscriber = FmtSubscriber::builder().with_max_level(Level::INFO);
+    if matches!(cli.command, Commands::McpServe | Commands::Eval { .. }) {
+        tracing::subscriber::set_global_default(subscriber.with_writer(std::io::stderr).finish())?;
+    } else {
+        tracing::subscriber::set_global_default(subscriber.finish())?;
+    }
+
     // Static dispatch to command strategies.
     // Each strategy is a zero-sized type (ZST) with no runtime overhead.
     // The compiler will monomorphize each call, enabling full optimization.
@@ -108,10 +192,56 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::Telegram { token, allow_from } => {
             let allow_from = allow_from.map(|s| s.split(',').map(String::from).collect());
-            TelegramStrategy
-                .execute(TelegramInput { token, allow_from })
+            Box::pin(TelegramStrategy.execute(TelegramInput { token, allow_from })).await?;
+        }
+        Commands::McpServe => {
+            McpServeStrategy.execute(()).await?;
+        }
+        Commands::Serve {
+            listen,
+            working_dir,
+        } => {
+            ServeStrategy
+                .execute(ServeInput {
+                    listen,
+                    working_dir,
+                })
                 .await?;
         }
+        Commands::Db { action } => {
+            let action = match action {
+                DbCommand::Migrate => DbAction::Migrate,
+                DbCommand::Status => DbAction::Status,
+                DbCommand::Rollback { steps } => DbAction::Rollback { steps },
+            };
+            DbStrategy.execute(action).await?;
+        }
+        Commands::Memory { action } => {
+            let action = match action {
+                MemoryCommand::Enrich { scope, batch_size } => {
+                    MemoryAction::Enrich { scope, batch_size }
+                }
+            };
+            MemoryStrategy.execute(action).await?;
+        }
+        Commands::Eval { action } => {
+            let action = match action {
+                EvalCommand::Retrieval {
+                    dataset,
+                    embeddings,
+                    cache,
+                    ks,
+                    json,
+                } => EvalAction::Retrieval {
+                    dataset,
+                    embeddings,
+                    cache,
+                    ks,
+                    json,
+                },
+            };
+            EvalStrategy.execute(action).await?;
+        }
     }
 
     Ok(())
@@ -20,5 +20,7 @@
 mod schema;
 
 pub use schema::{
-    AgentDefaults, AgentsConfig, Config, DatabaseConfig, ProviderConfig, ProvidersConfig,
+    AgentDefaults, AgentsConfig, Config, DatabaseConfig, McpConfig, McpServerConfig,
+    McpTransportConfig, ProviderConfig, ProvidersConfig, RerankerConfig, ServerConfig,
+    TelegramConfig, TelegramWebhookConfig, ToolsConfig,
 };
@@ -2,15 +2,18 @@
 
 use serde::{Deserialize, Serialize};
 use std::io::Write;
-use std::sync::{Arc, atomic::AtomicBool};
+use std::sync::{Arc, Mutex, atomic::AtomicBool};
+use tokio::sync::oneshot;
 use tracing::{debug, info};
 use uuid::Uuid;
 
 use crate::{
     ChatMessage, ContentBlock, DEFAULT_SYSTEM_PROMPT, LLMProvider, MemoryItem, MemoryItemRepo,
-    MessageContent, Role, SessionStorage,
+    MemoryScope, MessageContent, Role, SessionStorage,
 };
 
+use super::MemoryQueue;
+use crate::memory::extraction;
 use crate::retrieval::adaptive::{AdaptiveConfig, find_adaptive_cutoff};
 
 /// Format a timestamp as a human-readable "time ago" string
@@ -61,11 +64,19 @@ where
     config: AgentConfig,
     running: Arc<AtomicBool>,
     memory_manager: Option<Arc<dyn MemoryItemRepo>>,
+    /// Scope memories are retrieved from and stored into
+    memory_scope: MemoryScope,
     retrieval_config: RetrievalConfig,
     tools: Option<nanors_tools::StaticToolRegistry>,
     max_tool_iterations: usize,
     /// Maximum number of messages to keep in context history
     history_limit: usize,
+    /// Store facts distilled by the model rather than raw user messages
+    memory_extraction: bool,
+    /// Stores exchanges in turn order, per scope
+    memory_queue: Arc<MemoryQueue>,
+    /// This agent's memory writes not known to be finished
+    pending_memory: Mutex<Vec<oneshot::Receiver<()>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -87,7 +98,7 @@ impl Default for AgentConfig {
 
 impl<P, S> AgentLoop<P, S>
 where
-    P: LLMProvider + Send + Sync,
+    P: LLMProvider + Clone + Send + Sync + 'static,
     S: SessionStorage + Send + Sync,
 {
     pub fn new(provider: P, session_manager: S, config: AgentConfig) -> Self {
@@ -97,10 +108,14 @@ where
             config,
             running: Arc::new(AtomicBool::new(true)),
             memory_manager: None,
+            memory_scope: MemoryScope::Global,
             retrieval_config: RetrievalConfig::default(),
             tools: None,
             max_tool_iterations: 10,
             history_limit: 20,
+            memory_extraction: true,
+            memory_queue: Arc::default(),
+            pending_memory: Mutex::new(Vec::new()),
         }
     }
 
@@ -111,6 +126,13 @@ where
         self
     }
 
+    /// Disable tool calling.
+    #[must_use]
+    pub fn without_tools(mut self) -> Self {
+        self.tools = None;
+        self
+    }
+
     /// Set the maximum number of tool iterations.
     #[must_use]
     pub const fn with_max_tool_iterations(mut self, max: usize) -> Self {
@@ -125,6 +147,22 @@ where
         self
     }
 
+    /// Store memories through `queue`, shared with the other agents over
+    /// the same memory, so the exchanges of a scope are stored in turn
+    /// order even when each turn gets its own agent.
+    #[must_use]
+    pub fn with_memory_queue(mut self, queue: Arc<MemoryQueue>) -> Self {
+        self.memory_queue = queue;
+        self
+    }
+
+    /// Confine memory retrieval and storage to `scope` (default: global).
+    #[must_use]
+    pub fn with_memory_scope(mut self, scope: MemoryScope) -> Self {
+        self.memory_scope = scope;
+        self
+    }
+
     /// Set the retrieval configuration.
     #[must_use]
     pub const fn with_retrieval_config(mut self, retrieval_config: RetrievalConfig) -> Self {
@@ -139,6 +177,14 @@ where
         self
     }
 
+    /// Store facts distilled from each exchange by the model (default), or
+    /// every user message verbatim when `enabled` is false.
+    #[must_use]
+    pub const fn with_memory_extraction(mut self, enabled: bool) -> Self {
+        self.memory_extraction = enabled;
+        self
+    }
+
     pub async fn run_interactive(&self) -> anyhow::Result<()> {
         println!("nanors agent started. Type 'exit' to quit.\n");
 
@@ -165,19 +211,53 @@ where
             }
         }
 
+        self.wait_for_memory().await;
         Ok(())
     }
 
+    /// Wait for the memory writes of earlier turns, e.g. before exiting.
+    ///
+    /// Replies don't wait for memories to be extracted and stored; a
+    /// long-lived process can drop the agent without calling this.
+    pub async fn wait_for_memory(&self) {
+        let pending = self
+            .pending_memory
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or_default();
+        for write in pending {
+            if write.await.is_err() {
+                debug!("Memory write was dropped");
+            }
+        }
+    }
+
     pub async fn process_message(
         &self,
         session_id: &Uuid,
         content: &str,
+    ) -> anyhow::Result<String> {
+        self.process_message_with_images(session_id, content, &[])
+            .await
+    }
+
+    /// Process a message with attached [`ContentBlock::Image`] blocks.
+    ///
+    /// Images are sent only to vision-capable models and are not kept in the
+    /// session history; `content` should mention them for later turns.
+    pub async fn process_message_with_images(
+        &self,
+        session_id: &Uuid,
+        content: &str,
+        images: &[ContentBlock],
     ) -> anyhow::Result<String> {
         info!("Processing message from session: {}", session_id);
 
         // Check if tools are available
         if self.tools.is_some() {
-            return self.process_message_with_tools(session_id, content).await;
+            return self
+                .process_message_with_tools(session_id, content, images)
+                .await;
         }
 
         // Load session history
@@ -196,10 +276,7 @@ where
             content: MessageContent::Text(system_prompt),
         }];
         messages.extend(history_messages);
-        messages.push(ChatMessage {
-            role: Role::User,
-            content: MessageContent::Text(content.to_string()),
-        });
+        messages.push(self.user_message(content, images));
 
         // Log messages being sent to the LLM
         for (i, msg) in messages.iter().enumerate() {
@@ -216,8 +293,7 @@ where
         let response = self.provider.chat(&messages, &self.config.model).await?;
 
         self.save_to_session(session_id, content, &response).await?;
-        self.save_to_memory_with_embeddings(content, &response.content)
-            .await;
+        self.save_to_memory_with_embeddings(session_id, history.len(), content, &response.content);
 
         Ok(response.content)
     }
@@ -227,6 +303,7 @@ where
         &self,
         session_id: &Uuid,
         content: &str,
+        images: &[ContentBlock],
     ) -> anyhow::Result<String> {
         // Load session history
         let session = self.session_manager.get_or_create(session_id).await?;
@@ -248,10 +325,7 @@ where
             content: MessageContent::Text(system_prompt),
         }];
         messages.extend(history_messages);
-        messages.push(ChatMessage {
-            role: Role::User,
-            content: MessageContent::Text(content.to_string()),
-        });
+        messages.push(self.user_message(content, images));
 
         // Tool calling loop
         for iteration in 0..self.max_tool_iterations {
@@ -286,8 +360,12 @@ where
                     // Save to session and memory
                     self.save_to_session_with_blocks(session_id, content, &response.content)
                         .await?;
-                    self.save_to_memory_with_embeddings(content, &final_text)
-                        .await;
+                    self.save_to_memory_with_embeddings(
+                        session_id,
+                        history.len(),
+                        content,
+                        &final_text,
+                    );
 
                     return Ok(final_text);
                 }
@@ -339,6 +417,40 @@ where
         ))
     }
 
+    /// Build the current user message, attaching images if the model can
+    /// see them.
+    fn user_message(&self, content: &str, images: &[ContentBlock]) -> ChatMessage {
+        if images.is_empty() {
+            return ChatMessage {
+                role: Role::User,
+                content: MessageContent::Text(content.to_string()),
+            };
+        }
+
+        if !self.provider.supports_vision(&self.config.model) {
+            info!(
+                "Model {} has no vision support, dropping {} image(s)",
+                self.config.model,
+                images.len()
+            );
+            return ChatMessage {
+                role: Role::User,
+                content: MessageContent::Text(format!(
+                    "{content}\n[图片未能发送：当前模型不支持图像]"
+                )),
+            };
+        }
+
+        let mut blocks = vec![ContentBlock::Text {
+            text: content.to_string(),
+        }];
+        blocks.extend_from_slice(images);
+        ChatMessage {
+            role: Role::User,
+            content: MessageContent::Blocks(blocks),
+        }
+    }
+
     /// Build the system prompt with memory retrieval.
     pub async fn build_system_prompt(&self, query: &str) -> String {
         let Some(memory_manager) = &self.memory_manager else {
@@ -358,7 +470,7 @@ where
 
         // Try to use enhanced search if available, fall back to standard search
         let Ok(mut items) = memory_manager
-            .search_enhanced(&query_embedding, query, fetch_count)
+            .search_enhanced(&self.memory_scope, &query_embedding, query, fetch_count)
             .await
         else {
             return DEFAULT_SYSTEM_PROMPT.to_string();
@@ -404,7 +516,10 @@ where
 
         for item_score in &items {
             let time_ago = time_ago_since(item_score.item.happened_at);
-            let text = format!("- [{}] {}", time_ago, item_score.item.summary);
+            let mut text = format!("- [{}] {}", time_ago, item_score.item.summary);
+            if !item_score.item.is_current() {
+                text.push_str(" (superseded)");
+            }
             let text_len = text.len();
             if total_length + text_len > self.retrieval_config.context_target_length {
                 break;
@@ -428,7 +543,7 @@ where
         info!("=== End Memory Context ===");
 
         format!(
-            "You are a helpful AI assistant with memory of past conversations.\n\n# Relevant Memories\n\nMemories below are sorted by RELEVANCE (similarity), NOT by time. Each memory shows when it was recorded.\n\n{memory_context}\n\n# CRITICAL: Resolve Conflicts by RECENCY\n\n**When memories conflict, ALWAYS pick the one with the SMALLEST time value.**\n\nTime comparison (smaller = more recent):\n- 1小时前 < 1天前 (1 hour ago is MORE recent than 1 day ago)\n- 14小时前 < 1天前 (14 hours ago is MORE recent than 1 day ago)\n- 2天前 < 1周前 (2 days ago is MORE recent than 1 week ago)\n\n**DO NOT** pick based on position in the list. **ALWAYS** compare the timestamps.\n\nExample: If you see \"[14小时前] 我住丰台\" and \"[1天前] 我搬家到了东城\", answer \"丰台\" because 14小时前 < 1天前.\n\nMake a decisive answer. Do NOT ask for confirmation."
+            "You are a helpful AI assistant with memory of past conversations.\n\n# Relevant Memories\n\nMemories below are sorted by RELEVANCE (similarity), NOT by time. Each memory shows when it was recorded; memories marked (superseded) were replaced by a newer version and are only shown for history questions.\n\n{memory_context}\n\n# CRITICAL: Resolve Conflicts by RECENCY\n\n**When memories conflict, ALWAYS pick the one with the SMALLEST time value.**\n\nTime comparison (smaller = more recent):\n- 1小时前 < 1天前 (1 hour ago is MORE recent than 1 day ago)\n- 14小时前 < 1天前 (14 hours ago is MORE recent than 1 day ago)\n- 2天前 < 1周前 (2 days ago is MORE recent than 1 week ago)\n\n**DO NOT** pick based on position in the list. **ALWAYS** compare the timestamps.\n\nExample: If you see \"[14小时前] 我住丰台\" and \"[1天前] 我搬家到了东城\", answer \"丰台\" because 14小时前 < 1天前.\n\nMake a decisive answer. Do NOT ask for confirmation."
         )
     }
 
@@ -474,37 +589,127 @@ where
         Ok(())
     }
 
-    /// Save interaction to memory storage with embeddings.
-    async fn save_to_memory_with_embeddings(&self, content: &str, _response_text: &str) {
-        let Some(memory) = &self.memory_manager else {
+    /// Save what is worth remembering from an exchange to memory storage,
+    /// in the background so the reply is not held up by extraction and
+    /// embedding calls, after the exchanges queued before it in the same
+    /// scope. See [`Self::wait_for_memory`].
+    fn save_to_memory_with_embeddings(
+        &self,
+        session_id: &Uuid,
+        message_index: usize,
+        content: &str,
+        response_text: &str,
+    ) {
+        let Some(memory) = self.memory_manager.clone() else {
             return;
         };
+        let writer = MemoryWriter {
+            provider: self.provider.clone(),
+            model: self.config.model.clone(),
+            memory,
+            scope: self.memory_scope.clone(),
+            extraction: self.memory_extraction,
+        };
+        let exchange = Exchange {
+            session_id: *session_id,
+            message_index,
+            user: content.to_string(),
+            assistant: response_text.to_string(),
+        };
+        let write =
+            self.memory_queue.push(
+                &self.memory_scope,
+                async move { writer.save(exchange).await },
+            );
+        if let Ok(mut pending) = self.pending_memory.lock() {
+            pending.retain_mut(|write| {
+                matches!(write.try_recv(), Err(oneshot::error::TryRecvError::Empty))
+            });
+            pending.push(write);
+        }
+    }
+}
 
-        let now = chrono::Utc::now();
+/// A finished exchange to remember.
+struct Exchange {
+    session_id: Uuid,
+    /// Index of the user message in the session
+    message_index: usize,
+    user: String,
+    assistant: String,
+}
 
-        // Only store user messages as memories - assistant responses are just outputs,
-        // not facts. Storing assistant responses can cause confusion when they contain
-        // incorrect information that gets retrieved later.
-        let user_embedding = match self.provider.embed(content).await {
-            Ok(embedding) => Some(embedding),
-            Err(e) => {
-                debug!("Failed to generate user embedding: {e}");
-                None
+/// What a background memory write needs from the agent.
+struct MemoryWriter<P> {
+    provider: P,
+    model: String,
+    memory: Arc<dyn MemoryItemRepo>,
+    scope: MemoryScope,
+    extraction: bool,
+}
+
+impl<P: LLMProvider> MemoryWriter<P> {
+    /// Store what is worth remembering from `exchange`.
+    ///
+    /// The model distills atomic facts, preferences and procedures from the
+    /// exchange and only those are stored, each linked to the user message
+    /// in the session; greetings and questions leave no memory. Assistant
+    /// replies are never a source: they can be wrong and would be retrieved
+    /// as facts later. With extraction off, the user message is stored
+    /// verbatim.
+    async fn save(&self, exchange: Exchange) {
+        let now = chrono::Utc::now();
+        let items: Vec<(String, MemoryItem)> = if self.extraction {
+            match extraction::extract_memories(
+                &self.provider,
+                &self.model,
+                &exchange.user,
+                &exchange.assistant,
+            )
+            .await
+            {
+                Ok(extracted) => extracted
+                    .into_iter()
+                    .map(|m| {
+                        let item = MemoryItem::new(m.memory_type, &m.text, None, now);
+                        (m.text, item)
+                    })
+                    .collect(),
+                Err(e) => {
+                    debug!("Failed to extract memories: {e}");
+                    return;
+                }
             }
+        } else {
+            let item = MemoryItem::create_episodic(&exchange.user, None, now);
+            vec![(exchange.user, item)]
         };
 
-        let user_memory = MemoryItem::create_episodic(content, user_embedding, now);
-
-        // Use semantic upsert to handle fact updates (e.g., location changes)
-        match memory.semantic_upsert(&user_memory, 0.85).await {
-            Ok(id) => {
-                debug!("Stored user memory: {}", id);
+        let source = serde_json::json!({
+            "source": {
+                "session_id": exchange.session_id,
+                "message_index": exchange.message_index,
             }
-            Err(e) => {
-                debug!("Failed to store user memory: {e}");
+        });
+        for (text, mut item) in items {
+            item.embedding = match self.provider.embed(&text).await {
+                Ok(embedding) => Some(embedding),
+                Err(e) => {
+                    debug!("Failed to generate memory embedding: {e}");
+                    None
+                }
+            };
+            item.extra = Some(source.clone());
+
+            // Use semantic upsert to handle fact updates (e.g., location changes)
+            match self.memory.semantic_upsert(&self.scope, &item).await {
+                Ok(id) => {
+                    debug!("Stored memory {id}: {}", item.summary);
+                }
+                Err(e) => {
+                    debug!("Failed to store memory: {e}");
+                }
             }
         }
-
-        debug!("Stored user message as memory");
     }
 }
@@ -0,0 +1,96 @@
+//! Ordered background memory writes.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::MemoryScope;
+
+/// A memory write waiting its turn.
+type MemoryWrite = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Runs memory writes in the background, one scope at a time in the order
+/// they were queued.
+///
+/// Each scope has one writer task, so an exchange is stored only after the
+/// earlier exchanges of its scope: a fact never races the version it
+/// supersedes. Frontends that build an agent per turn share one queue
+/// between them with [`AgentLoop::with_memory_queue`](super::AgentLoop::with_memory_queue).
+#[derive(Default)]
+pub struct MemoryQueue {
+    writers: Mutex<HashMap<MemoryScope, mpsc::UnboundedSender<MemoryWrite>>>,
+}
+
+impl MemoryQueue {
+    /// Queue `write` behind the earlier writes of `scope`. The receiver
+    /// resolves once it has run; it errors if the write was dropped.
+    pub fn push<F>(&self, scope: &MemoryScope, write: F) -> oneshot::Receiver<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (done, finished) = oneshot::channel();
+        let write: MemoryWrite = Box::pin(async move {
+            write.await;
+            let _ = done.send(());
+        });
+        let Ok(mut writers) = self.writers.lock() else {
+            return finished;
+        };
+        let writer = writers.entry(scope.clone()).or_insert_with(spawn_writer);
+        if let Err(mpsc::error::SendError(write)) = writer.send(write) {
+            // The writer stopped with the runtime it ran on; start another
+            let writer = spawn_writer();
+            let _ = writer.send(write);
+            writers.insert(scope.clone(), writer);
+        }
+        drop(writers);
+        finished
+    }
+}
+
+/// Start a task running the writes sent to it one after another.
+fn spawn_writer() -> mpsc::UnboundedSender<MemoryWrite> {
+    let (writer, mut writes) = mpsc::unbounded_channel::<MemoryWrite>();
+    tokio::spawn(async move {
+        while let Some(write) = writes.recv().await {
+            write.await;
+        }
+    });
+    writer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_writes_of_a_scope_run_in_order() -> anyhow::Result<()> {
+        let queue = MemoryQueue::default();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let write = |id: u8, delay: u64| {
+            let order = Arc::clone(&order);
+            async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                if let Ok(mut order) = order.lock() {
+                    order.push(id);
+                }
+            }
+        };
+
+        let scope = MemoryScope::user("telegram", 42);
+        let first = queue.push(&scope, write(1, 50));
+        let second = queue.push(&scope, write(2, 0));
+        let elsewhere = queue.push(&MemoryScope::Global, write(3, 0));
+        elsewhere.await?;
+        first.await?;
+        second.await?;
+
+        let order = order.lock().map(|o| o.clone()).unwrap_or_default();
+        assert_eq!(order, [3, 1, 2]);
+        Ok(())
+    }
+}
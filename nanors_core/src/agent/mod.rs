@@ -1,3 +1,13 @@
 mod agent_loop;
+mod memory_queue;
 
 pub use agent_loop::{AgentConfig, AgentLoop, RetrievalConfig};
+pub use memory_queue::MemoryQueue;
+
+use std::sync::Arc;
+
+/// Builds a fully configured agent for the given model name.
+///
+/// Frontends call this once per request or message, since tool registries
+/// are owned by the agent.
+pub type AgentFactory<P, S> = Arc<dyn Fn(&str) -> AgentLoop<P, S> + Send + Sync>;
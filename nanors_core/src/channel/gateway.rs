@@ -0,0 +1,585 @@
+//! Channel-agnostic message handling.
+
+use std::sync::{Arc, MutexGuard, PoisonError};
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::{Mutex, Semaphore, mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::{
+    Channel, ChatSessionInfo, ChatSessionStore, ChatSettings, InMemoryChatSessionStore,
+    InboundMessage,
+};
+use crate::agent::AgentFactory;
+use crate::{LLMProvider, MemoryScope, SessionStorage};
+
+/// How often the typing indicator is refreshed while the agent works.
+const TYPING_INTERVAL: Duration = Duration::from_secs(4);
+
+/// Messages sent within this window of each other are answered as one turn.
+const DEFAULT_COALESCE_WINDOW: Duration = Duration::from_millis(800);
+
+/// Agent turns allowed to run at once across all chats.
+const DEFAULT_MAX_CONCURRENT_TURNS: usize = 4;
+
+/// A chat's message queue, drained by its worker task.
+struct ChatQueue {
+    tx: mpsc::UnboundedSender<InboundMessage>,
+    /// Aborts the turn in flight, if any
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+/// Routes inbound channel messages through the agent and sends the reply.
+pub struct ChatGateway<P, S>
+where
+    P: Send + Sync,
+    S: Send + Sync,
+{
+    channel: Arc<dyn Channel>,
+    factory: AgentFactory<P, S>,
+    model: String,
+    /// Allowed chat ids (empty = everyone)
+    allowed_chats: Vec<String>,
+    /// Persistent chat → session mapping
+    session_store: Arc<dyn ChatSessionStore>,
+    /// Cache of active sessions per chat
+    sessions: Mutex<HashMap<String, Uuid>>,
+    /// Per-chat model and tool overrides
+    settings: std::sync::Mutex<HashMap<String, ChatSettings>>,
+    /// Chats with a running worker
+    queues: std::sync::Mutex<HashMap<String, ChatQueue>>,
+    /// Bounds concurrent agent turns across chats
+    turns: Semaphore,
+    coalesce_window: Duration,
+}
+
+impl<P, S> ChatGateway<P, S>
+where
+    P: LLMProvider + Clone + Send + Sync + 'static,
+    S: SessionStorage + Send + Sync + 'static,
+{
+    pub fn new(
+        channel: Arc<dyn Channel>,
+        factory: AgentFactory<P, S>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            channel,
+            factory,
+            model: model.into(),
+            allowed_chats: Vec::new(),
+            session_store: Arc::new(InMemoryChatSessionStore::new()),
+            sessions: Mutex::new(HashMap::new()),
+            settings: std::sync::Mutex::new(HashMap::new()),
+            queues: std::sync::Mutex::new(HashMap::new()),
+            turns: Semaphore::new(DEFAULT_MAX_CONCURRENT_TURNS),
+            coalesce_window: DEFAULT_COALESCE_WINDOW,
+        }
+    }
+
+    /// Run at most `max` agent turns at once across all chats.
+    #[must_use]
+    pub fn with_max_concurrent_turns(mut self, max: usize) -> Self {
+        self.turns = Semaphore::new(max.max(1));
+        self
+    }
+
+    /// Answer messages sent within `window` of each other as one turn.
+    #[must_use]
+    pub const fn with_coalesce_window(mut self, window: Duration) -> Self {
+        self.coalesce_window = window;
+        self
+    }
+
+    /// Only accept messages from these chats (empty = everyone).
+    #[must_use]
+    pub fn with_allowed_chats(mut self, allowed_chats: Vec<String>) -> Self {
+        self.allowed_chats = allowed_chats;
+        self
+    }
+
+    /// Persist the chat → session mapping in `store`.
+    #[must_use]
+    pub fn with_session_store(mut self, store: Arc<dyn ChatSessionStore>) -> Self {
+        self.session_store = store;
+        self
+    }
+
+    /// The channel replies are sent through.
+    #[must_use]
+    pub fn channel(&self) -> &Arc<dyn Channel> {
+        &self.channel
+    }
+
+    /// Check if a chat is allowed.
+    #[must_use]
+    pub fn is_allowed(&self, chat_id: &str) -> bool {
+        self.allowed_chats.is_empty() || self.allowed_chats.iter().any(|c| c == chat_id)
+    }
+
+    /// Default model for chats without an override.
+    #[must_use]
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Current overrides for a chat.
+    #[must_use]
+    pub fn settings(&self, chat_id: &str) -> ChatSettings {
+        self.settings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(chat_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Change a chat's overrides; they last until the process exits.
+    pub fn update_settings(&self, chat_id: &str, update: impl FnOnce(&mut ChatSettings)) {
+        let mut settings = self.settings.lock().unwrap_or_else(PoisonError::into_inner);
+        update(settings.entry(chat_id.to_string()).or_default());
+    }
+
+    /// All sessions of a chat, newest first.
+    pub async fn sessions(&self, chat_id: &str) -> anyhow::Result<Vec<ChatSessionInfo>> {
+        self.session_store
+            .list_sessions(self.channel.name(), chat_id)
+            .await
+    }
+
+    /// Active session for a chat, creating one on first contact.
+    pub async fn session_id(&self, chat_id: &str) -> anyhow::Result<Uuid> {
+        // Held across the store lookup so concurrent first messages agree
+        let mut sessions = self.sessions.lock().await;
+        if let Some(id) = sessions.get(chat_id) {
+            return Ok(*id);
+        }
+
+        let channel = self.channel.name();
+        let id = if let Some(id) = self.session_store.active_session(channel, chat_id).await? {
+            debug!("Resuming session {id} for {channel} chat {chat_id}");
+            id
+        } else {
+            let id = Uuid::now_v7();
+            self.session_store
+                .create_session(channel, chat_id, &id)
+                .await?;
+            info!("Created session {id} for {channel} chat {chat_id}");
+            id
+        };
+
+        sessions.insert(chat_id.to_string(), id);
+        drop(sessions);
+        Ok(id)
+    }
+
+    /// Start a fresh session for a chat.
+    ///
+    /// The previous session is archived, not deleted; its id is returned.
+    pub async fn reset(&self, chat_id: &str) -> anyhow::Result<Option<Uuid>> {
+        // Held until archived so the old session cannot be re-cached
+        let mut sessions = self.sessions.lock().await;
+        sessions.remove(chat_id);
+        let archived = self
+            .session_store
+            .archive_session(self.channel.name(), chat_id)
+            .await;
+        drop(sessions);
+        archived
+    }
+
+    /// Run a message through the agent and return the reply text.
+    ///
+    /// The typing indicator is refreshed until the agent finishes.
+    pub async fn process(&self, msg: &InboundMessage) -> anyhow::Result<String> {
+        if !self.is_allowed(&msg.chat_id) {
+            anyhow::bail!(
+                "Unauthorized access from {} chat {}",
+                self.channel.name(),
+                msg.chat_id
+            );
+        }
+
+        let session_id = self.session_id(&msg.chat_id).await?;
+        let settings = self.settings(&msg.chat_id);
+        let mut agent = (self.factory)(settings.model.as_deref().unwrap_or(&self.model))
+            .with_memory_scope(self.memory_scope(msg));
+        if !settings.tools {
+            agent = agent.without_tools();
+        }
+        let work = agent.process_message_with_images(&session_id, &msg.text, &msg.images);
+        tokio::pin!(work);
+
+        self.send_typing(&msg.chat_id).await;
+        let mut typing =
+            tokio::time::interval_at(Instant::now() + TYPING_INTERVAL, TYPING_INTERVAL);
+        loop {
+            tokio::select! {
+                reply = &mut work => return reply,
+                _ = typing.tick() => self.send_typing(&msg.chat_id).await,
+            }
+        }
+    }
+
+    /// Memory scope for a message: the sender's own when known, so users
+    /// sharing a chat never see each other's memories; otherwise the chat's.
+    #[must_use]
+    pub fn memory_scope(&self, msg: &InboundMessage) -> MemoryScope {
+        let channel = self.channel.name();
+        msg.sender_id.as_ref().map_or_else(
+            || MemoryScope::chat(channel, &msg.chat_id),
+            |id| MemoryScope::user(channel, id),
+        )
+    }
+
+    /// Typing indicators are best effort.
+    async fn send_typing(&self, chat_id: &str) {
+        if let Err(e) = self.channel.send_typing(chat_id).await {
+            debug!("Typing indicator failed: {e}");
+        }
+    }
+
+    /// Send a reply to a chat, formatted and split by the channel.
+    pub async fn send(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        self.channel.send_reply(chat_id, text).await
+    }
+
+    /// Handle an inbound message end to end: agent run, then reply.
+    pub async fn handle(&self, msg: &InboundMessage) -> anyhow::Result<()> {
+        let sender = msg.sender.as_deref().unwrap_or("unknown");
+        info!(
+            "[{}:{}] Message from {sender}: {}",
+            self.channel.name(),
+            msg.chat_id,
+            msg.text
+        );
+
+        let reply = self.process(msg).await?;
+
+        info!(
+            "[{}:{}] Response: {reply}",
+            self.channel.name(),
+            msg.chat_id
+        );
+        self.send(&msg.chat_id, &reply).await
+    }
+
+    fn queues(&self) -> MutexGuard<'_, HashMap<String, ChatQueue>> {
+        self.queues.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queue a message for its chat and return immediately.
+    ///
+    /// Each chat has one worker, so turns on a session never overlap.
+    /// Messages sent in quick succession, or while a turn is running, are
+    /// answered together in the next turn.
+    pub fn submit(self: &Arc<Self>, msg: InboundMessage) {
+        let chat_id = msg.chat_id.clone();
+        let mut queues = self.queues();
+        let queue = queues.entry(chat_id.clone()).or_insert_with(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(Arc::clone(self).run_chat(chat_id.clone(), rx));
+            ChatQueue { tx, cancel: None }
+        });
+        if queue.tx.send(msg).is_err() {
+            warn!("Worker for chat {chat_id} is gone, message dropped");
+        }
+        drop(queues);
+    }
+
+    /// Abort the chat's running turn and drop its queued messages.
+    ///
+    /// Returns whether there was anything to cancel.
+    pub fn cancel(&self, chat_id: &str) -> bool {
+        self.queues()
+            .get_mut(chat_id)
+            .and_then(|queue| queue.cancel.take())
+            .is_some_and(|cancel| cancel.send(()).is_ok())
+    }
+
+    /// Worker loop for one chat; exits once the queue is empty.
+    async fn run_chat(
+        self: Arc<Self>,
+        chat_id: String,
+        mut rx: mpsc::UnboundedReceiver<InboundMessage>,
+    ) {
+        // A message from another sender that ended the previous turn
+        let mut pending = None;
+        loop {
+            let (cancel_tx, cancel_rx) = oneshot::channel();
+            let first = {
+                // Checked under the lock so `submit` cannot race the exit
+                let mut queues = self.queues();
+                let Some(first) = pending.take().or_else(|| rx.try_recv().ok()) else {
+                    queues.remove(&chat_id);
+                    return;
+                };
+                if let Some(queue) = queues.get_mut(&chat_id) {
+                    queue.cancel = Some(cancel_tx);
+                }
+                drop(queues);
+                first
+            };
+
+            tokio::select! {
+                () = self.run_turn(first, &mut rx, &mut pending) => {}
+                _ = cancel_rx => {
+                    info!("[{}:{chat_id}] Turn cancelled", self.channel.name());
+                    pending = None;
+                    while rx.try_recv().is_ok() {}
+                }
+            }
+
+            if let Some(queue) = self.queues().get_mut(&chat_id) {
+                queue.cancel = None;
+            }
+        }
+    }
+
+    /// Coalesce follow-up messages into `first`, then run one turn.
+    ///
+    /// Only messages from the same sender are merged, so a turn runs in a
+    /// single memory scope; a message from someone else is left in
+    /// `pending` for the next turn.
+    async fn run_turn(
+        &self,
+        first: InboundMessage,
+        rx: &mut mpsc::UnboundedReceiver<InboundMessage>,
+        pending: &mut Option<InboundMessage>,
+    ) {
+        let mut msg = first;
+        while let Ok(Some(next)) = tokio::time::timeout(self.coalesce_window, rx.recv()).await {
+            if next.sender_id != msg.sender_id {
+                *pending = Some(next);
+                break;
+            }
+            msg.text.push_str("\n\n");
+            msg.text.push_str(&next.text);
+            msg.images.extend(next.images);
+        }
+
+        let Ok(_permit) = self.turns.acquire().await else {
+            return;
+        };
+        if let Err(e) = self.handle(&msg).await {
+            warn!("Failed to handle message from chat {}: {e}", msg.chat_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{LoopbackChannel, OutboundMessage};
+    use crate::testing::{InMemorySessions, RepeatProvider, repeat_factory};
+
+    fn gateway(
+        channel: Arc<LoopbackChannel>,
+    ) -> ChatGateway<RepeatProvider, Arc<InMemorySessions>> {
+        slow_gateway(channel, Duration::ZERO)
+    }
+
+    fn slow_gateway(
+        channel: Arc<LoopbackChannel>,
+        delay: Duration,
+    ) -> ChatGateway<RepeatProvider, Arc<InMemorySessions>> {
+        let factory = repeat_factory(Arc::new(InMemorySessions::default()), delay);
+        ChatGateway::new(channel, factory, "test-model")
+    }
+
+    #[tokio::test]
+    async fn test_gateway_replies_through_channel_in_chunks() -> anyhow::Result<()> {
+        let channel = Arc::new(LoopbackChannel::new().with_max_message_len(12));
+        let gateway = gateway(Arc::clone(&channel));
+
+        gateway
+            .handle(&InboundMessage::new("chat-1", "hello world").with_sender("alice"))
+            .await?;
+
+        assert_eq!(channel.typing(), vec!["chat-1"]);
+        let sent = channel.take_sent();
+        let texts: Vec<&str> = sent.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, vec!["hello world", "hello world"]);
+        assert!(sent.iter().all(|m| m.chat_id == "chat-1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gateway_keeps_session_until_reset() -> anyhow::Result<()> {
+        let gateway = gateway(Arc::new(LoopbackChannel::new()));
+
+        let first = gateway.session_id("chat-1").await?;
+        assert_eq!(gateway.session_id("chat-1").await?, first);
+        assert_ne!(gateway.session_id("chat-2").await?, first);
+
+        assert_eq!(gateway.reset("chat-1").await?, Some(first));
+        assert_ne!(gateway.session_id("chat-1").await?, first);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gateway_resumes_sessions_from_store() -> anyhow::Result<()> {
+        let store: Arc<dyn ChatSessionStore> = Arc::new(InMemoryChatSessionStore::new());
+
+        // A restart is a new gateway over the same store
+        let before =
+            gateway(Arc::new(LoopbackChannel::new())).with_session_store(Arc::clone(&store));
+        let id = before.session_id("chat-1").await?;
+        let after =
+            gateway(Arc::new(LoopbackChannel::new())).with_session_store(Arc::clone(&store));
+        assert_eq!(after.session_id("chat-1").await?, id);
+
+        assert_eq!(after.reset("chat-1").await?, Some(id));
+        assert_eq!(store.active_session("loopback", "chat-1").await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gateway_lists_sessions_and_chat_settings() -> anyhow::Result<()> {
+        let gateway = gateway(Arc::new(LoopbackChannel::new()));
+
+        let first = gateway.session_id("chat-1").await?;
+        gateway.reset("chat-1").await?;
+        let second = gateway.session_id("chat-1").await?;
+
+        let sessions = gateway.sessions("chat-1").await?;
+        let listed: Vec<(Uuid, bool)> = sessions
+            .iter()
+            .map(|s| (s.session_id, s.archived))
+            .collect();
+        assert_eq!(listed, vec![(second, false), (first, true)]);
+        assert!(gateway.sessions("chat-2").await?.is_empty());
+
+        assert_eq!(gateway.settings("chat-1"), ChatSettings::default());
+        gateway.update_settings("chat-1", |s| {
+            s.model = Some("glm-4v-plus".to_string());
+            s.tools = false;
+        });
+        assert_eq!(
+            gateway.settings("chat-1").model.as_deref(),
+            Some("glm-4v-plus")
+        );
+        assert!(!gateway.settings("chat-1").tools);
+        assert_eq!(gateway.settings("chat-2"), ChatSettings::default());
+
+        // Replies still work with tools off and a different model
+        gateway.handle(&InboundMessage::new("chat-1", "hi")).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gateway_rejects_chats_outside_allowlist() -> anyhow::Result<()> {
+        let channel = Arc::new(LoopbackChannel::new());
+        let gateway = gateway(Arc::clone(&channel)).with_allowed_chats(vec!["42".to_string()]);
+
+        assert!(
+            gateway
+                .handle(&InboundMessage::new("7", "hi"))
+                .await
+                .is_err()
+        );
+        assert!(channel.take_sent().is_empty());
+
+        gateway.handle(&InboundMessage::new("42", "hi")).await?;
+        assert_eq!(
+            channel.take_sent(),
+            vec![OutboundMessage {
+                chat_id: "42".to_string(),
+                text: "hi hi".to_string(),
+            }]
+        );
+        Ok(())
+    }
+
+    /// Wait until `count` replies have been sent.
+    async fn wait_for_replies(channel: &LoopbackChannel, count: usize) -> Vec<String> {
+        let mut sent = Vec::new();
+        for _ in 0..500 {
+            sent.extend(channel.take_sent().into_iter().map(|m| m.text));
+            if sent.len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        sent
+    }
+
+    #[tokio::test]
+    async fn test_gateway_coalesces_rapid_messages() {
+        let channel = Arc::new(LoopbackChannel::new());
+        let gateway =
+            Arc::new(gateway(Arc::clone(&channel)).with_coalesce_window(Duration::from_millis(50)));
+
+        gateway.submit(InboundMessage::new("chat-1", "a"));
+        gateway.submit(InboundMessage::new("chat-1", "b"));
+
+        assert_eq!(wait_for_replies(&channel, 1).await, vec!["a\n\nb a\n\nb"]);
+    }
+
+    #[tokio::test]
+    async fn test_gateway_keeps_senders_apart() {
+        let channel = Arc::new(LoopbackChannel::new());
+        let gateway =
+            Arc::new(gateway(Arc::clone(&channel)).with_coalesce_window(Duration::from_millis(50)));
+
+        let alice = InboundMessage::new("group", "a").with_sender_id("1");
+        let bob = InboundMessage::new("group", "b").with_sender_id("2");
+        assert_eq!(gateway.memory_scope(&alice).to_string(), "user:loopback:1");
+        assert_ne!(gateway.memory_scope(&alice), gateway.memory_scope(&bob));
+        assert_eq!(
+            gateway
+                .memory_scope(&InboundMessage::new("group", "c"))
+                .to_string(),
+            "chat:loopback:group"
+        );
+
+        // Different senders are never merged into one turn
+        gateway.submit(alice);
+        gateway.submit(bob);
+        assert_eq!(wait_for_replies(&channel, 2).await, vec!["a a", "b b"]);
+    }
+
+    #[tokio::test]
+    async fn test_gateway_serializes_turns_per_chat() {
+        let channel = Arc::new(LoopbackChannel::new());
+        let gateway = Arc::new(
+            slow_gateway(Arc::clone(&channel), Duration::from_millis(200))
+                .with_coalesce_window(Duration::from_millis(10)),
+        );
+
+        gateway.submit(InboundMessage::new("chat-1", "one"));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // Both arrive while the first turn runs and are answered together
+        gateway.submit(InboundMessage::new("chat-1", "two"));
+        gateway.submit(InboundMessage::new("chat-1", "three"));
+
+        assert_eq!(
+            wait_for_replies(&channel, 2).await,
+            vec!["one one", "two\n\nthree two\n\nthree"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gateway_cancel_aborts_running_turn() {
+        let channel = Arc::new(LoopbackChannel::new());
+        let gateway = Arc::new(
+            slow_gateway(Arc::clone(&channel), Duration::from_secs(30))
+                .with_coalesce_window(Duration::from_millis(10)),
+        );
+
+        assert!(!gateway.cancel("chat-1"));
+        gateway.submit(InboundMessage::new("chat-1", "hi"));
+        for _ in 0..100 {
+            if !channel.typing().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert!(gateway.cancel("chat-1"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(channel.take_sent().is_empty());
+        assert!(!gateway.cancel("chat-1"));
+    }
+}
@@ -0,0 +1,81 @@
+//! In-memory channel that records everything sent to it.
+
+use async_trait::async_trait;
+use std::sync::Mutex;
+
+use super::Channel;
+
+/// A message delivered through [`LoopbackChannel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboundMessage {
+    pub chat_id: String,
+    pub text: String,
+}
+
+/// Channel that keeps sent messages in memory, for tests and local tooling.
+#[derive(Debug, Default)]
+pub struct LoopbackChannel {
+    max_message_len: Option<usize>,
+    sent: Mutex<Vec<OutboundMessage>>,
+    typing: Mutex<Vec<String>>,
+}
+
+impl LoopbackChannel {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the message size limit to exercise chunking.
+    #[must_use]
+    pub const fn with_max_message_len(mut self, max: usize) -> Self {
+        self.max_message_len = Some(max);
+        self
+    }
+
+    /// Drain the messages sent so far.
+    pub fn take_sent(&self) -> Vec<OutboundMessage> {
+        self.sent
+            .lock()
+            .map(|mut sent| std::mem::take(&mut *sent))
+            .unwrap_or_default()
+    }
+
+    /// Chats that received a typing indicator, in order.
+    pub fn typing(&self) -> Vec<String> {
+        self.typing
+            .lock()
+            .map(|typing| typing.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Channel for LoopbackChannel {
+    fn name(&self) -> &'static str {
+        "loopback"
+    }
+
+    fn max_message_len(&self) -> usize {
+        self.max_message_len.unwrap_or(4096)
+    }
+
+    async fn send_text(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        self.sent
+            .lock()
+            .map_err(|_| anyhow::anyhow!("loopback channel lock poisoned"))?
+            .push(OutboundMessage {
+                chat_id: chat_id.to_string(),
+                text: text.to_string(),
+            });
+        Ok(())
+    }
+
+    async fn send_typing(&self, chat_id: &str) -> anyhow::Result<()> {
+        self.typing
+            .lock()
+            .map_err(|_| anyhow::anyhow!("loopback channel lock poisoned"))?
+            .push(chat_id.to_string());
+        Ok(())
+    }
+}
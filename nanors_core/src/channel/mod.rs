@@ -0,0 +1,180 @@
+//! Chat channel abstraction.
+//!
+//! A [`Channel`] is a frontend (Telegram, a loopback for tests, ...) that can
+//! deliver text to a chat. [`ChatGateway`] holds everything channel-agnostic:
+//! allowlisting, chat → session mapping (persisted through a
+//! [`ChatSessionStore`]) and running the agent. Channels deliver the reply via
+//! [`Channel::send_reply`], which by default chunks it to the message size
+//! limit.
+
+use async_trait::async_trait;
+
+use crate::ContentBlock;
+
+mod gateway;
+mod loopback;
+mod store;
+
+pub use gateway::ChatGateway;
+pub use loopback::{LoopbackChannel, OutboundMessage};
+pub use store::{ChatSessionInfo, ChatSessionStore, InMemoryChatSessionStore};
+
+/// A message received from a channel.
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    /// Channel-specific chat identifier
+    pub chat_id: String,
+    /// Display name of the sender, if known
+    pub sender: Option<String>,
+    /// Stable channel-specific sender id; selects the user's memory scope
+    pub sender_id: Option<String>,
+    pub text: String,
+    /// Attached [`ContentBlock::Image`] blocks
+    pub images: Vec<ContentBlock>,
+}
+
+impl InboundMessage {
+    pub fn new(chat_id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            sender: None,
+            sender_id: None,
+            text: text.into(),
+            images: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_sender(mut self, sender: impl Into<String>) -> Self {
+        self.sender = Some(sender.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_sender_id(mut self, sender_id: impl Into<String>) -> Self {
+        self.sender_id = Some(sender_id.into());
+        self
+    }
+
+    /// Attach a base64-encoded image.
+    #[must_use]
+    pub fn with_image(mut self, media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        self.images.push(ContentBlock::Image {
+            media_type: media_type.into(),
+            data: data.into(),
+        });
+        self
+    }
+}
+
+/// Per-chat overrides set through chat commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatSettings {
+    /// Model to use instead of the gateway default
+    pub model: Option<String>,
+    /// Whether the agent may call tools
+    pub tools: bool,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            model: None,
+            tools: true,
+        }
+    }
+}
+
+/// Outbound side of a chat frontend.
+#[async_trait]
+pub trait Channel: Send + Sync {
+    /// Short channel name used in logs (e.g. `"telegram"`).
+    fn name(&self) -> &str;
+
+    /// Maximum characters per message; longer replies are split.
+    fn max_message_len(&self) -> usize {
+        4096
+    }
+
+    /// Send a text message to a chat.
+    async fn send_text(&self, chat_id: &str, text: &str) -> anyhow::Result<()>;
+
+    /// Show a typing indicator. Channels without one can ignore this.
+    async fn send_typing(&self, _chat_id: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Deliver an agent reply, split to the message size limit.
+    ///
+    /// Channels that render Markdown override this to format and split the
+    /// reply themselves.
+    async fn send_reply(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        for chunk in chunk_message(text, self.max_message_len()) {
+            self.send_text(chat_id, &chunk).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Split `text` into chunks of at most `max_chars` characters.
+///
+/// Splits prefer paragraph breaks, then line breaks, then spaces, and only
+/// cut inside a word when nothing else fits.
+#[must_use]
+pub fn chunk_message(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+
+    while rest.chars().count() > max_chars {
+        // Byte offset just past the first `max_chars` characters
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..limit];
+
+        let split = ["\n\n", "\n", " "]
+            .iter()
+            .find_map(|sep| window.rfind(sep).filter(|&i| i > 0))
+            .unwrap_or(limit);
+
+        chunks.push(rest[..split].trim_end().to_string());
+        rest = rest[split..].trim_start();
+    }
+
+    if !rest.is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_message_short_text_is_single_chunk() {
+        assert_eq!(chunk_message("hello", 10), vec!["hello"]);
+        assert!(chunk_message("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_message_prefers_paragraphs_then_words() {
+        let text = "first paragraph\n\nsecond one here";
+        assert_eq!(
+            chunk_message(text, 20),
+            vec!["first paragraph", "second one here"]
+        );
+        assert_eq!(
+            chunk_message("alpha beta gamma", 11),
+            vec!["alpha beta", "gamma"]
+        );
+    }
+
+    #[test]
+    fn test_chunk_message_hard_splits_multibyte() {
+        let chunks = chunk_message("你好世界你好世界", 3);
+        assert_eq!(chunks, vec!["你好世", "界你好", "世界"]);
+    }
+}
@@ -0,0 +1,122 @@
+//! Persistence of the chat → session mapping.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Maps channel chats to their active session.
+///
+/// Each chat has at most one active session. Resetting a chat archives the
+/// mapping; the session and its history are kept.
+#[async_trait]
+pub trait ChatSessionStore: Send + Sync {
+    /// Active session of a chat, if any.
+    async fn active_session(&self, channel: &str, chat_id: &str) -> anyhow::Result<Option<Uuid>>;
+
+    /// Record `session_id` as the active session of a chat.
+    async fn create_session(
+        &self,
+        channel: &str,
+        chat_id: &str,
+        session_id: &Uuid,
+    ) -> anyhow::Result<()>;
+
+    /// Archive the active session of a chat and return its id.
+    async fn archive_session(&self, channel: &str, chat_id: &str) -> anyhow::Result<Option<Uuid>>;
+
+    /// All sessions of a chat, active and archived, newest first.
+    async fn list_sessions(
+        &self,
+        channel: &str,
+        chat_id: &str,
+    ) -> anyhow::Result<Vec<ChatSessionInfo>>;
+}
+
+/// A session that belongs to a chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatSessionInfo {
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub archived: bool,
+}
+
+/// Process-local store; mappings are lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryChatSessionStore {
+    /// Sessions per chat, oldest first
+    sessions: Mutex<HashMap<(String, String), Vec<ChatSessionInfo>>>,
+}
+
+impl InMemoryChatSessionStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn key(channel: &str, chat_id: &str) -> (String, String) {
+    (channel.to_string(), chat_id.to_string())
+}
+
+#[async_trait]
+impl ChatSessionStore for InMemoryChatSessionStore {
+    async fn active_session(&self, channel: &str, chat_id: &str) -> anyhow::Result<Option<Uuid>> {
+        Ok(self
+            .sessions
+            .lock()
+            .await
+            .get(&key(channel, chat_id))
+            .and_then(|sessions| sessions.iter().find(|s| !s.archived))
+            .map(|s| s.session_id))
+    }
+
+    async fn create_session(
+        &self,
+        channel: &str,
+        chat_id: &str,
+        session_id: &Uuid,
+    ) -> anyhow::Result<()> {
+        self.sessions
+            .lock()
+            .await
+            .entry(key(channel, chat_id))
+            .or_default()
+            .push(ChatSessionInfo {
+                session_id: *session_id,
+                created_at: Utc::now(),
+                archived: false,
+            });
+        Ok(())
+    }
+
+    async fn archive_session(&self, channel: &str, chat_id: &str) -> anyhow::Result<Option<Uuid>> {
+        Ok(self
+            .sessions
+            .lock()
+            .await
+            .get_mut(&key(channel, chat_id))
+            .and_then(|sessions| sessions.iter_mut().find(|s| !s.archived))
+            .map(|s| {
+                s.archived = true;
+                s.session_id
+            }))
+    }
+
+    async fn list_sessions(
+        &self,
+        channel: &str,
+        chat_id: &str,
+    ) -> anyhow::Result<Vec<ChatSessionInfo>> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .await
+            .get(&key(channel, chat_id))
+            .cloned()
+            .unwrap_or_default();
+        sessions.reverse();
+        Ok(sessions)
+    }
+}
@@ -26,13 +26,20 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 pub mod agent;
+pub mod channel;
 pub mod memory;
 pub mod retrieval;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 mod util;
 
-pub use agent::{AgentConfig, AgentLoop};
-pub use memory::{MemoryItem, MemoryItemRepo, MemoryType, SalienceScore};
-pub use util::{DEFAULT_SYSTEM_PROMPT, DEFAULT_SYSTEM_PROMPT_WITH_MEMORY, content_hash};
+pub use agent::{AgentConfig, AgentFactory, AgentLoop, MemoryQueue};
+pub use memory::{
+    MemoryItem, MemoryItemRepo, MemoryScope, MemoryType, SalienceScore, VersionRelation,
+};
+pub use util::{
+    DEFAULT_SYSTEM_PROMPT, DEFAULT_SYSTEM_PROMPT_WITH_MEMORY, content_hash, json_array_in_reply,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -74,6 +81,9 @@ pub enum ContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
     },
+    /// Base64-encoded image for vision-capable models
+    #[serde(rename = "image")]
+    Image { media_type: String, data: String },
 }
 
 #[derive(Debug, Clone)]
@@ -95,6 +105,11 @@ pub trait LLMProvider: Send + Sync {
     async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>>;
     fn get_default_model(&self) -> &str;
 
+    /// Whether `model` accepts [`ContentBlock::Image`] input.
+    fn supports_vision(&self, _model: &str) -> bool {
+        false
+    }
+
     /// Chat with tool support
     async fn chat_with_tools(
         &self,
@@ -111,8 +126,17 @@ pub struct LLMToolResponse {
     pub usage: Option<Usage>,
 }
 
+/// Transcribes audio (e.g. voice notes) to text.
+#[async_trait]
+pub trait SpeechToText: Send + Sync {
+    /// Transcribe `audio` encoded as `mime_type` (e.g. `"audio/ogg"`).
+    async fn transcribe(&self, audio: Vec<u8>, mime_type: &str) -> anyhow::Result<String>;
+}
+
 #[async_trait]
 pub trait SessionStorage: Send + Sync {
+    /// The stored session, or `None` if it has no messages yet.
+    async fn get(&self, id: &Uuid) -> anyhow::Result<Option<Session>>;
     async fn get_or_create(&self, id: &Uuid) -> anyhow::Result<Session>;
     async fn add_message(&self, id: &Uuid, role: Role, content: &str) -> anyhow::Result<()>;
 }
@@ -120,6 +144,10 @@ pub trait SessionStorage: Send + Sync {
 // Blanket implementation for Arc<T> where T implements SessionStorage
 #[async_trait]
 impl<T: SessionStorage + ?Sized> SessionStorage for Arc<T> {
+    async fn get(&self, id: &Uuid) -> anyhow::Result<Option<Session>> {
+        self.as_ref().get(id).await
+    }
+
     async fn get_or_create(&self, id: &Uuid) -> anyhow::Result<Session> {
         self.as_ref().get_or_create(id).await
     }
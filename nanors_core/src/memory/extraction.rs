@@ -0,0 +1,107 @@
+//! Distilling long-term memories from a conversation turn.
+//!
+//! Instead of storing every user message verbatim, the chat model is asked
+//! for the atomic facts, preferences and procedures an exchange reveals.
+//! Greetings, questions and requests yield nothing.
+
+use serde::Deserialize;
+
+use super::types::MemoryType;
+use crate::{ChatMessage, LLMProvider, MessageContent, Role, json_array_in_reply};
+
+/// A memory distilled from an exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ExtractedMemory {
+    /// `Semantic` for facts and preferences, `Procedural` for how-tos
+    #[serde(rename = "type")]
+    pub memory_type: MemoryType,
+    /// A short first-person statement, e.g. "我住在东城"
+    pub text: String,
+}
+
+fn prompt(user: &str, assistant: &str) -> String {
+    format!(
+        "Extract what is worth remembering long-term about the user from the exchange below.\n\
+         Reply with a JSON array only. Each element is an object with:\n\
+         - \"type\": \"semantic\" for a fact about the user or a preference, \
+           \"procedural\" for how the user wants something done\n\
+         - \"text\": one atomic statement in the first person, in the user's language \
+           (e.g. \"我住在北京\", \"I prefer tabs over spaces\")\n\
+         Only use what the user states; the assistant reply is context, not a source of facts.\n\
+         Reply with [] for greetings, questions, requests and small talk.\n\n\
+         User: {user}\n\
+         Assistant: {assistant}"
+    )
+}
+
+/// Ask `provider` which memories the exchange of `user` and `assistant`
+/// reveals; empty when there is nothing to remember.
+pub async fn extract_memories<P: LLMProvider + ?Sized>(
+    provider: &P,
+    model: &str,
+    user: &str,
+    assistant: &str,
+) -> anyhow::Result<Vec<ExtractedMemory>> {
+    let messages = [ChatMessage {
+        role: Role::User,
+        content: MessageContent::Text(prompt(user, assistant)),
+    }];
+    let response = provider.chat(&messages, model).await?;
+    Ok(parse_memories(&response.content))
+}
+
+/// Memories from a model reply: the outermost JSON array, semantic and
+/// procedural entries with text only. Malformed entries are skipped.
+fn parse_memories(reply: &str) -> Vec<ExtractedMemory> {
+    json_array_in_reply(reply)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value::<ExtractedMemory>(entry).ok())
+        .filter(|memory| memory.memory_type != MemoryType::Episodic)
+        .map(|memory| ExtractedMemory {
+            text: memory.text.trim().to_string(),
+            ..memory
+        })
+        .filter(|memory| !memory.text.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_memories_from_fenced_reply() {
+        let reply = "```json\n[{\"type\": \"semantic\", \"text\": \" 我住在东城 \"},\
+                     {\"type\": \"procedural\", \"text\": \"Use cargo nextest to run tests\"},\
+                     {\"type\": \"episodic\", \"text\": \"said hello\"},\
+                     {\"type\": \"semantic\", \"text\": \"\"}]\n```";
+        let memories = parse_memories(reply);
+        assert_eq!(
+            memories,
+            [
+                ExtractedMemory {
+                    memory_type: MemoryType::Semantic,
+                    text: "我住在东城".to_string(),
+                },
+                ExtractedMemory {
+                    memory_type: MemoryType::Procedural,
+                    text: "Use cargo nextest to run tests".to_string(),
+                },
+            ]
+        );
+        assert!(parse_memories("[]").is_empty());
+        assert!(parse_memories("Nothing to remember.").is_empty());
+    }
+
+    #[test]
+    fn test_parse_memories_skips_malformed_entries() {
+        let reply = "[{\"type\": \"semantic\", \"text\": \"我养了一只猫\"},\
+                     {\"type\": \"belief\", \"text\": \"unknown type\"},\
+                     {\"text\": \"no type\"},\
+                     \"just a string\",\
+                     {\"type\": \"procedural\", \"text\": \"先写测试\"}]";
+        let texts: Vec<String> = parse_memories(reply).into_iter().map(|m| m.text).collect();
+        assert_eq!(texts, ["我养了一只猫", "先写测试"]);
+    }
+}
@@ -1,5 +1,12 @@
+pub mod extraction;
 mod repository;
+mod scope;
+mod tools;
 mod types;
 
 pub use repository::MemoryItemRepo;
-pub use types::{MemoryItem, MemoryType, SalienceScore};
+pub use scope::MemoryScope;
+pub use tools::{
+    MemoryAddTool, MemoryListTool, MemorySearchTool, MemoryToolContext, SessionGetTool,
+};
+pub use types::{MemoryItem, MemoryType, SalienceScore, VersionRelation};
@@ -1,24 +1,43 @@
 use async_trait::async_trait;
 use uuid::Uuid;
 
+use super::scope::MemoryScope;
 use super::types::{MemoryItem, SalienceScore};
 
+/// Long-term memory storage.
+///
+/// Reads and writes take the [`MemoryScope`] they are confined to; an item
+/// is never visible outside the scope it was inserted into.
 #[async_trait]
 pub trait MemoryItemRepo: Send + Sync {
-    async fn insert(&self, item: &MemoryItem) -> anyhow::Result<()>;
+    async fn insert(&self, scope: &MemoryScope, item: &MemoryItem) -> anyhow::Result<()>;
 
-    async fn find_by_id(&self, id: &Uuid) -> anyhow::Result<Option<MemoryItem>>;
+    async fn find_by_id(
+        &self,
+        scope: &MemoryScope,
+        id: &Uuid,
+    ) -> anyhow::Result<Option<MemoryItem>>;
 
-    async fn find_by_content_hash(&self, hash: &str) -> anyhow::Result<Option<MemoryItem>>;
+    async fn find_by_content_hash(
+        &self,
+        scope: &MemoryScope,
+        hash: &str,
+    ) -> anyhow::Result<Option<MemoryItem>>;
 
-    async fn update(&self, item: &MemoryItem) -> anyhow::Result<()>;
+    /// Update an item of `scope` in place.
+    async fn update(&self, scope: &MemoryScope, item: &MemoryItem) -> anyhow::Result<()>;
 
-    async fn delete(&self, id: &Uuid) -> anyhow::Result<()>;
+    /// Delete an item of `scope`. Deleting a current version also deletes
+    /// the versions it superseded; deleting an older version keeps the rest
+    /// of its chain.
+    async fn delete(&self, scope: &MemoryScope, id: &Uuid) -> anyhow::Result<()>;
 
-    async fn list_all(&self) -> anyhow::Result<Vec<MemoryItem>>;
+    /// Current versions of the memories in `scope`.
+    async fn list(&self, scope: &MemoryScope) -> anyhow::Result<Vec<MemoryItem>>;
 
     async fn search_by_embedding(
         &self,
+        scope: &MemoryScope,
         query_embedding: &[f32],
         query_text: &str,
         top_k: usize,
@@ -34,16 +53,17 @@ pub trait MemoryItemRepo: Send + Sync {
     /// Default implementation falls back to `search_by_embedding`.
     async fn search_enhanced(
         &self,
+        scope: &MemoryScope,
         query_embedding: &[f32],
         query_text: &str,
         top_k: usize,
     ) -> anyhow::Result<Vec<SalienceScore<MemoryItem>>> {
         // Default implementation: just use standard vector search
-        self.search_by_embedding(query_embedding, query_text, top_k)
+        self.search_by_embedding(scope, query_embedding, query_text, top_k)
             .await
     }
 
-    /// Backfill embeddings for items that don't have them.
+    /// Backfill embeddings for items that don't have them, in every scope.
     /// Returns the number of items updated.
     async fn backfill_embeddings(
         &self,
@@ -52,21 +72,20 @@ pub trait MemoryItemRepo: Send + Sync {
 
     /// Insert or update a memory item based on semantic similarity.
     ///
-    /// Searches for semantically similar memories using embedding similarity.
-    /// If a memory with similarity above the threshold exists, creates a new
-    /// version of that memory (update). Otherwise, inserts as a new memory.
+    /// Searches for semantically similar memories in `scope` using embedding
+    /// similarity.
+    /// If a memory with similarity above the store's supersede threshold
+    /// (`memory.scoring.supersede_threshold`, default 0.85) exists, stores a
+    /// new version that supersedes it; the old version is kept for history
+    /// but no longer returned by default. Otherwise, inserts as a new memory.
     ///
     /// # Arguments
+    /// * `scope` - Scope the item belongs to
     /// * `item` - The memory item to insert or use for update
-    /// * `similarity_threshold` - Minimum similarity (0.0-1.0) to consider
-    ///   memories as semantically equivalent. Default: 0.85
     ///
     /// # Returns
-    /// * `Ok(uuid)` - ID of the inserted or updated memory
+    /// * `Ok(uuid)` - ID of the inserted memory or new version
     /// * `Err(e)` - Error if operation fails
-    async fn semantic_upsert(
-        &self,
-        item: &MemoryItem,
-        similarity_threshold: f64,
-    ) -> anyhow::Result<Uuid>;
+    async fn semantic_upsert(&self, scope: &MemoryScope, item: &MemoryItem)
+    -> anyhow::Result<Uuid>;
 }
@@ -0,0 +1,89 @@
+use std::fmt;
+
+/// Whose memory an item belongs to.
+///
+/// Every read and write on a [`MemoryItemRepo`](super::MemoryItemRepo) is
+/// confined to one scope, so one user's memories never reach another user's
+/// prompt. Stored in the `user_scope` column as `user:<id>`, `chat:<id>`,
+/// `agent:<id>` or `global`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum MemoryScope {
+    /// One person across all chats, e.g. `telegram:12345`
+    User(String),
+    /// One conversation, shared by its members
+    Chat(String),
+    /// One agent's own notes
+    Agent(String),
+    /// Shared by everyone (CLI and single-user setups)
+    #[default]
+    Global,
+}
+
+impl MemoryScope {
+    /// A channel user's scope, e.g. `MemoryScope::user("telegram", 42)`.
+    #[must_use]
+    pub fn user(channel: &str, user_id: impl fmt::Display) -> Self {
+        Self::User(format!("{channel}:{user_id}"))
+    }
+
+    /// A channel chat's scope.
+    #[must_use]
+    pub fn chat(channel: &str, chat_id: impl fmt::Display) -> Self {
+        Self::Chat(format!("{channel}:{chat_id}"))
+    }
+}
+
+impl fmt::Display for MemoryScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(id) => write!(f, "user:{id}"),
+            Self::Chat(id) => write!(f, "chat:{id}"),
+            Self::Agent(id) => write!(f, "agent:{id}"),
+            Self::Global => write!(f, "global"),
+        }
+    }
+}
+
+impl std::str::FromStr for MemoryScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "global" {
+            return Ok(Self::Global);
+        }
+        let (kind, id) = s
+            .split_once(':')
+            .filter(|(_, id)| !id.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("invalid memory scope: {s}"))?;
+        match kind {
+            "user" => Ok(Self::User(id.to_string())),
+            "chat" => Ok(Self::Chat(id.to_string())),
+            "agent" => Ok(Self::Agent(id.to_string())),
+            _ => Err(anyhow::anyhow!("unknown memory scope kind: {kind}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_round_trips_through_string() -> anyhow::Result<()> {
+        for scope in [
+            MemoryScope::user("telegram", 42),
+            MemoryScope::chat("telegram", -100),
+            MemoryScope::Agent("researcher".into()),
+            MemoryScope::Global,
+        ] {
+            assert_eq!(scope.to_string().parse::<MemoryScope>()?, scope);
+        }
+        assert_eq!(
+            MemoryScope::user("telegram", 42).to_string(),
+            "user:telegram:42"
+        );
+        assert!("user:".parse::<MemoryScope>().is_err());
+        assert!("team:x".parse::<MemoryScope>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+//! Tools exposing long-term memory and session history.
+//!
+//! These are registered as dynamic tools so they can be served over MCP
+//! (`nanors mcp-serve`) or offered to an agent like any other tool.
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use nanors_tools::{Tool, ToolDefinition, ToolResult, schema_object};
+
+use super::{MemoryItem, MemoryItemRepo, MemoryScope, MemoryType};
+use crate::{LLMProvider, MessageContent, SessionStorage};
+
+/// Shared handles used by all memory tools.
+#[derive(Clone)]
+pub struct MemoryToolContext {
+    pub memory: Arc<dyn MemoryItemRepo>,
+    pub sessions: Arc<dyn SessionStorage>,
+    pub provider: Arc<dyn LLMProvider>,
+    /// Scope the tools read and write
+    pub scope: MemoryScope,
+}
+
+impl MemoryToolContext {
+    /// Build all memory tools for this context.
+    #[must_use]
+    pub fn tools(&self) -> Vec<Box<dyn Tool>> {
+        vec![
+            Box::new(MemorySearchTool(self.clone())),
+            Box::new(MemoryAddTool(self.clone())),
+            Box::new(MemoryListTool(self.clone())),
+            Box::new(SessionGetTool(self.clone())),
+        ]
+    }
+
+    async fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        match self.provider.embed(text).await {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
+                warn!("Failed to generate embedding: {e}");
+                None
+            }
+        }
+    }
+}
+
+fn memory_to_json(item: &MemoryItem) -> Value {
+    json!({
+        "id": item.id,
+        "memory_type": item.memory_type.to_string(),
+        "summary": item.summary,
+        "happened_at": item.happened_at.to_rfc3339(),
+        "reinforcement_count": item.reinforcement_count,
+    })
+}
+
+fn json_result(value: &Value) -> ToolResult {
+    match serde_json::to_string_pretty(value) {
+        Ok(text) => ToolResult::success(text),
+        Err(e) => ToolResult::error(format!("Failed to serialize result: {e}")),
+    }
+}
+
+fn usize_arg(input: &Value, key: &str, default: usize) -> usize {
+    input
+        .get(key)
+        .and_then(Value::as_u64)
+        .and_then(|v| usize::try_from(v).ok())
+        .unwrap_or(default)
+}
+
+/// Semantic search over long-term memory.
+pub struct MemorySearchTool(MemoryToolContext);
+
+#[async_trait]
+impl Tool for MemorySearchTool {
+    fn name(&self) -> &'static str {
+        "memory_search"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().into(),
+            description: "Search long-term memory for facts relevant to a query.".into(),
+            input_schema: schema_object(
+                json!({
+                    "query": {"type": "string", "description": "Search query"},
+                    "limit": {"type": "integer", "description": "Maximum results (default: 5)"}
+                }),
+                &["query"],
+            ),
+        }
+    }
+
+    async fn execute(&self, input: Value) -> ToolResult {
+        let Some(query) = input.get("query").and_then(Value::as_str) else {
+            return ToolResult::error("Missing 'query' parameter");
+        };
+        let limit = usize_arg(&input, "limit", 5);
+
+        // Without an embedding the search degrades to keyword overlap
+        let embedding = self.0.embed(query).await.unwrap_or_default();
+        match self
+            .0
+            .memory
+            .search_enhanced(&self.0.scope, &embedding, query, limit)
+            .await
+        {
+            Ok(results) => {
+                let results: Vec<Value> = results
+                    .iter()
+                    .map(|s| {
+                        let mut item = memory_to_json(&s.item);
+                        item["score"] = json!(s.score);
+                        item["similarity"] = json!(s.similarity);
+                        item
+                    })
+                    .collect();
+                json_result(&json!({"results": results}))
+            }
+            Err(e) => ToolResult::error(format!("Memory search failed: {e}")),
+        }
+    }
+}
+
+/// Store a new fact in long-term memory.
+pub struct MemoryAddTool(MemoryToolContext);
+
+#[async_trait]
+impl Tool for MemoryAddTool {
+    fn name(&self) -> &'static str {
+        "memory_add"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().into(),
+            description: "Store a fact, preference or procedure in long-term memory.".into(),
+            input_schema: schema_object(
+                json!({
+                    "text": {"type": "string", "description": "The memory to store"},
+                    "memory_type": {
+                        "type": "string",
+                        "enum": ["episodic", "semantic", "procedural"],
+                        "description": "Memory type (default: semantic)"
+                    }
+                }),
+                &["text"],
+            ),
+        }
+    }
+
+    async fn execute(&self, input: Value) -> ToolResult {
+        let Some(text) = input.get("text").and_then(Value::as_str) else {
+            return ToolResult::error("Missing 'text' parameter");
+        };
+        let memory_type = match input.get("memory_type").and_then(Value::as_str) {
+            Some(t) => match t.parse::<MemoryType>() {
+                Ok(t) => t,
+                Err(e) => return ToolResult::error(e.to_string()),
+            },
+            None => MemoryType::Semantic,
+        };
+
+        let embedding = self.0.embed(text).await;
+        let item = MemoryItem::new(memory_type, text, embedding, chrono::Utc::now());
+        match self.0.memory.semantic_upsert(&self.0.scope, &item).await {
+            Ok(id) => json_result(&json!({"id": id})),
+            Err(e) => ToolResult::error(format!("Failed to store memory: {e}")),
+        }
+    }
+}
+
+/// List stored memories, newest first.
+pub struct MemoryListTool(MemoryToolContext);
+
+#[async_trait]
+impl Tool for MemoryListTool {
+    fn name(&self) -> &'static str {
+        "memory_list"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().into(),
+            description: "List stored memories, newest first.".into(),
+            input_schema: schema_object(
+                json!({
+                    "limit": {"type": "integer", "description": "Maximum results (default: 20)"},
+                    "offset": {"type": "integer", "description": "Results to skip (default: 0)"}
+                }),
+                &[],
+            ),
+        }
+    }
+
+    async fn execute(&self, input: Value) -> ToolResult {
+        let limit = usize_arg(&input, "limit", 20);
+        let offset = usize_arg(&input, "offset", 0);
+
+        match self.0.memory.list(&self.0.scope).await {
+            Ok(mut items) => {
+                items.sort_by_key(|item| std::cmp::Reverse(item.happened_at));
+                let total = items.len();
+                let page: Vec<Value> = items
+                    .iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(memory_to_json)
+                    .collect();
+                json_result(&json!({"total": total, "memories": page}))
+            }
+            Err(e) => ToolResult::error(format!("Failed to list memories: {e}")),
+        }
+    }
+}
+
+/// Fetch the message history of a session.
+pub struct SessionGetTool(MemoryToolContext);
+
+#[async_trait]
+impl Tool for SessionGetTool {
+    fn name(&self) -> &'static str {
+        "session_get"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().into(),
+            description: "Get the message history of a conversation session.".into(),
+            input_schema: schema_object(
+                json!({
+                    "session_id": {"type": "string", "description": "Session UUID"},
+                    "limit": {"type": "integer", "description": "Return only the last N messages"}
+                }),
+                &["session_id"],
+            ),
+        }
+    }
+
+    async fn execute(&self, input: Value) -> ToolResult {
+        let Some(raw_id) = input.get("session_id").and_then(Value::as_str) else {
+            return ToolResult::error("Missing 'session_id' parameter");
+        };
+        let Ok(session_id) = raw_id.parse::<Uuid>() else {
+            return ToolResult::error(format!("Invalid session id: {raw_id}"));
+        };
+
+        match self.0.sessions.get(&session_id).await {
+            Ok(None) => ToolResult::error(format!("Session not found: {session_id}")),
+            Ok(Some(session)) => {
+                let limit = usize_arg(&input, "limit", session.messages.len());
+                let start = session.messages.len().saturating_sub(limit);
+                let messages: Vec<Value> = session.messages[start..]
+                    .iter()
+                    .map(|m| {
+                        let content = match &m.content {
+                            MessageContent::Text(text) => json!(text),
+                            MessageContent::Blocks(blocks) => json!(blocks),
+                        };
+                        json!({"role": m.role, "content": content})
+                    })
+                    .collect();
+                json_result(&json!({
+                    "id": session.id,
+                    "created_at": session.created_at.to_rfc3339(),
+                    "updated_at": session.updated_at.to_rfc3339(),
+                    "messages": messages,
+                }))
+            }
+            Err(e) => ToolResult::error(format!("Failed to load session: {e}")),
+        }
+    }
+}
@@ -33,6 +33,45 @@ impl std::str::FromStr for MemoryType {
     }
 }
 
+/// How a memory version relates to the version it supersedes.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum VersionRelation {
+    /// First version of a fact
+    #[default]
+    Sets,
+    /// Replaces the previous value ("I moved to 东城")
+    Updates,
+    /// Adds detail to the previous version
+    Extends,
+    /// Withdraws the previous version ("I no longer live in 东城")
+    Retracts,
+}
+
+impl std::fmt::Display for VersionRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sets => write!(f, "Sets"),
+            Self::Updates => write!(f, "Updates"),
+            Self::Extends => write!(f, "Extends"),
+            Self::Retracts => write!(f, "Retracts"),
+        }
+    }
+}
+
+impl std::str::FromStr for VersionRelation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Sets" => Ok(Self::Sets),
+            "Updates" => Ok(Self::Updates),
+            "Extends" => Ok(Self::Extends),
+            "Retracts" => Ok(Self::Retracts),
+            _ => Err(anyhow::anyhow!("unknown version relation: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MemoryItem {
     pub id: Uuid,
@@ -43,11 +82,41 @@ pub struct MemoryItem {
     pub extra: Option<serde_json::Value>,
     pub content_hash: String,
     pub reinforcement_count: i32,
+    /// The version this one supersedes
+    pub previous_version_id: Option<Uuid>,
+    /// When a newer version replaced this one; `None` while current
+    pub superseded_at: Option<DateTime<Utc>>,
+    pub version_relation: VersionRelation,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl MemoryItem {
+    /// Create a new memory item with the given type and summary.
+    #[must_use]
+    pub fn new(
+        memory_type: MemoryType,
+        summary: &str,
+        embedding: Option<Vec<f32>>,
+        happened_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            content_hash: crate::content_hash(&memory_type.to_string(), summary),
+            memory_type,
+            summary: summary.to_string(),
+            embedding,
+            happened_at,
+            extra: None,
+            reinforcement_count: 0,
+            previous_version_id: None,
+            superseded_at: None,
+            version_relation: VersionRelation::Sets,
+            created_at: happened_at,
+            updated_at: happened_at,
+        }
+    }
+
     /// Create a new episodic memory item for user input.
     #[must_use]
     pub fn create_episodic(
@@ -64,10 +133,19 @@ impl MemoryItem {
             extra: None,
             content_hash: crate::content_hash("episodic", content),
             reinforcement_count: 0,
+            previous_version_id: None,
+            superseded_at: None,
+            version_relation: VersionRelation::Sets,
             created_at: happened_at,
             updated_at: happened_at,
         }
     }
+
+    /// Whether this is the current version of its fact.
+    #[must_use]
+    pub const fn is_current(&self) -> bool {
+        self.superseded_at.is_none()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,144 @@
+//! Fusing vector and lexical (BM25) retrieval.
+//!
+//! Each retriever ranks memories on its own scale, so the candidate set is
+//! chosen by weighted reciprocal rank fusion (RRF), which only looks at
+//! ranks:
+//!
+//! `fused(d) = Σ weight_i / (rrf_k + rank_i(d))`
+//!
+//! The same weights then blend cosine similarity and normalized BM25 into
+//! each candidate's hybrid similarity.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Weights for fusing vector and lexical retrieval.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FusionConfig {
+    /// Weight of the vector (embedding) ranking
+    #[serde(default = "default_vector_weight")]
+    pub vector_weight: f64,
+
+    /// Weight of the lexical (BM25) ranking
+    #[serde(default = "default_lexical_weight")]
+    pub lexical_weight: f64,
+
+    /// RRF rank constant; larger values flatten the difference between
+    /// top and lower ranks
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f64,
+}
+
+const fn default_vector_weight() -> f64 {
+    0.7
+}
+const fn default_lexical_weight() -> f64 {
+    0.3
+}
+const fn default_rrf_k() -> f64 {
+    60.0
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            vector_weight: default_vector_weight(),
+            lexical_weight: default_lexical_weight(),
+            rrf_k: default_rrf_k(),
+        }
+    }
+}
+
+impl FusionConfig {
+    /// Hybrid similarity: the weighted mean of a cosine similarity and a
+    /// lexical score normalized to 0.0-1.0.
+    #[must_use]
+    pub fn hybrid_similarity(&self, vector_sim: f64, lexical_sim: f64) -> f64 {
+        let total = self.vector_weight + self.lexical_weight;
+        if total <= 0.0 {
+            return vector_sim;
+        }
+        self.vector_weight
+            .mul_add(vector_sim, self.lexical_weight * lexical_sim)
+            / total
+    }
+
+    /// Fuse a vector and a lexical ranking (best first) into one, best
+    /// first. Items in only one ranking get that ranking's share.
+    #[must_use]
+    pub fn fuse<T: Copy + Eq + Hash>(&self, vector: &[T], lexical: &[T]) -> Vec<(T, f64)> {
+        reciprocal_rank_fusion(
+            &[(self.vector_weight, vector), (self.lexical_weight, lexical)],
+            self.rrf_k,
+        )
+    }
+}
+
+/// Weighted reciprocal rank fusion of `rankings`, each a weight and a list
+/// of items best first. Returns every item with its fused score, best first.
+#[must_use]
+pub fn reciprocal_rank_fusion<T: Copy + Eq + Hash>(
+    rankings: &[(f64, &[T])],
+    rrf_k: f64,
+) -> Vec<(T, f64)> {
+    let mut fused: HashMap<T, f64> = HashMap::new();
+    let mut order: Vec<T> = Vec::new();
+    for (weight, ranking) in rankings {
+        for (rank, item) in ranking.iter().enumerate() {
+            let score = weight / (rrf_k + rank as f64 + 1.0);
+            fused
+                .entry(*item)
+                .and_modify(|total| *total += score)
+                .or_insert_with(|| {
+                    order.push(*item);
+                    score
+                });
+        }
+    }
+    let mut fused: Vec<(T, f64)> = order
+        .into_iter()
+        .map(|item| (item, fused.get(&item).copied().unwrap_or_default()))
+        .collect();
+    // Stable, so ties keep the order of the first ranking
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_items_in_both_rankings_win() {
+        let config = FusionConfig::default();
+        let fused = config.fuse(&[1, 2, 3], &[3, 4]);
+        let order: Vec<i32> = fused.iter().map(|(item, _)| *item).collect();
+        assert_eq!(order, [3, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_weights_decide_single_ranking_items() {
+        let lexical_first = FusionConfig {
+            vector_weight: 0.2,
+            lexical_weight: 0.8,
+            ..FusionConfig::default()
+        };
+        let fused = lexical_first.fuse(&[1], &[2]);
+        assert_eq!(fused[0].0, 2);
+    }
+
+    #[test]
+    fn test_hybrid_similarity_is_weighted_mean() {
+        let config = FusionConfig::default();
+        assert!((config.hybrid_similarity(1.0, 0.0) - 0.7).abs() < 1e-9);
+        assert!((config.hybrid_similarity(0.5, 0.5) - 0.5).abs() < 1e-9);
+
+        let vector_only = FusionConfig {
+            vector_weight: 0.0,
+            lexical_weight: 0.0,
+            ..FusionConfig::default()
+        };
+        assert!((vector_only.hybrid_similarity(0.8, 1.0) - 0.8).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod adaptive;
+pub mod fusion;
+pub mod scoring;
 
 pub use adaptive::AdaptiveConfig;
+pub use fusion::FusionConfig;
+pub use scoring::{BoostWeights, ScoringConfig};
@@ -0,0 +1,87 @@
+//! Tunable constants of memory scoring.
+//!
+//! Every threshold and weight used to rank, filter and version memories
+//! lives here so it can be set from `memory.scoring` in the config file.
+//! The defaults are the values the scoring was tuned with.
+
+use serde::{Deserialize, Serialize};
+
+use super::FusionConfig;
+
+/// Thresholds and weights of memory search and storage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    /// Weights for fusing vector and lexical retrieval
+    pub fusion: FusionConfig,
+
+    /// Candidates at least this similar to the query are the query itself
+    /// and dropped from search results
+    pub self_match_threshold: f64,
+
+    /// Facts whose similarities differ by less than this are ordered
+    /// newest first
+    pub recency_tie_epsilon: f64,
+
+    /// Cosine similarity above which a stored memory only reinforces the
+    /// existing one
+    pub reinforce_threshold: f64,
+
+    /// Cosine similarity above which a stored memory becomes a new version
+    /// of the existing one
+    pub supersede_threshold: f64,
+
+    /// Similarity lost per pair of question words shared by the query and
+    /// a memory, so answers outrank other questions
+    pub question_penalty: f64,
+
+    /// Boosts of the rule-based reranker
+    pub boosts: BoostWeights,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            fusion: FusionConfig::default(),
+            self_match_threshold: 0.95,
+            recency_tie_epsilon: 0.05,
+            reinforce_threshold: 0.97,
+            supersede_threshold: 0.85,
+            question_penalty: 0.5,
+            boosts: BoostWeights::default(),
+        }
+    }
+}
+
+/// Boosts the rule-based reranker multiplies scores by, as `1 + boost`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoostWeights {
+    /// Per keyword of the question type found in a memory; on other
+    /// questions, scaled by the memory's BM25 similarity to the query
+    pub keyword: f64,
+
+    /// Full recency boost, for a memory from the last hour or so
+    pub recency: f64,
+
+    /// Per profile keyword, for identity questions
+    pub profile: f64,
+
+    /// Factor on `keyword` for profession keywords in identity questions
+    pub profession_factor: f64,
+
+    /// Factor on `keyword` for preference keywords
+    pub preference_factor: f64,
+}
+
+impl Default for BoostWeights {
+    fn default() -> Self {
+        Self {
+            keyword: 0.2,
+            recency: 0.15,
+            profile: 0.25,
+            profession_factor: 1.2,
+            preference_factor: 1.5,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Test doubles for driving `AgentLoop` without a model or database.
+//!
+//! Compiled for this crate's tests and, through the `test-support`
+//! feature, for other crates' tests.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    AgentConfig, AgentFactory, AgentLoop, ChatMessage, ContentBlock, LLMProvider, LLMResponse,
+    LLMToolResponse, MessageContent, Role, Session, SessionStorage,
+};
+
+/// Replies with the last message repeated twice, so chunking is
+/// exercised, after a delay.
+#[derive(Clone)]
+pub struct RepeatProvider(pub Duration);
+
+#[async_trait]
+impl LLMProvider for RepeatProvider {
+    async fn chat(&self, messages: &[ChatMessage], _model: &str) -> anyhow::Result<LLMResponse> {
+        tokio::time::sleep(self.0).await;
+        let last = match messages.last().map(|m| &m.content) {
+            Some(MessageContent::Text(text)) => text.clone(),
+            _ => String::new(),
+        };
+        Ok(LLMResponse {
+            content: format!("{last} {last}"),
+            usage: None,
+        })
+    }
+
+    async fn embed(&self, _text: &str) -> anyhow::Result<Vec<f32>> {
+        Ok(Vec::new())
+    }
+
+    fn get_default_model(&self) -> &'static str {
+        "repeat"
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        _tools: Option<Vec<nanors_tools::ToolDefinition>>,
+    ) -> anyhow::Result<LLMToolResponse> {
+        let response = self.chat(messages, model).await?;
+        Ok(LLMToolResponse {
+            content: vec![ContentBlock::Text {
+                text: response.content,
+            }],
+            stop_reason: Some("stop".to_string()),
+            usage: None,
+        })
+    }
+}
+
+/// Replies with a fixed text, or fails when it has none, and keeps the
+/// prompts it is sent.
+#[derive(Clone, Default)]
+pub struct ScriptedProvider {
+    reply: Option<String>,
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptedProvider {
+    /// A provider answering every chat with `reply`.
+    #[must_use]
+    pub fn replying(reply: impl Into<String>) -> Self {
+        Self {
+            reply: Some(reply.into()),
+            ..Self::default()
+        }
+    }
+
+    /// A provider whose chats all fail.
+    #[must_use]
+    pub fn failing() -> Self {
+        Self::default()
+    }
+
+    /// The last message of each chat so far, oldest first.
+    pub async fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl LLMProvider for ScriptedProvider {
+    async fn chat(&self, messages: &[ChatMessage], _model: &str) -> anyhow::Result<LLMResponse> {
+        if let Some(MessageContent::Text(prompt)) = messages.last().map(|m| &m.content) {
+            self.prompts.lock().await.push(prompt.clone());
+        }
+        let content = self
+            .reply
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("model unavailable"))?;
+        Ok(LLMResponse {
+            content,
+            usage: None,
+        })
+    }
+
+    async fn embed(&self, _text: &str) -> anyhow::Result<Vec<f32>> {
+        Ok(Vec::new())
+    }
+
+    fn get_default_model(&self) -> &'static str {
+        "scripted"
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        _tools: Option<Vec<nanors_tools::ToolDefinition>>,
+    ) -> anyhow::Result<LLMToolResponse> {
+        let response = self.chat(messages, model).await?;
+        Ok(LLMToolResponse {
+            content: vec![ContentBlock::Text {
+                text: response.content,
+            }],
+            stop_reason: Some("stop".to_string()),
+            usage: None,
+        })
+    }
+}
+
+/// Sessions kept in a map.
+#[derive(Default)]
+pub struct InMemorySessions(Mutex<HashMap<Uuid, Session>>);
+
+#[async_trait]
+impl SessionStorage for InMemorySessions {
+    async fn get(&self, id: &Uuid) -> anyhow::Result<Option<Session>> {
+        Ok(self.0.lock().await.get(id).cloned())
+    }
+
+    async fn get_or_create(&self, id: &Uuid) -> anyhow::Result<Session> {
+        let now = chrono::Utc::now();
+        Ok(self
+            .0
+            .lock()
+            .await
+            .entry(*id)
+            .or_insert_with(|| Session {
+                id: *id,
+                messages: Vec::new(),
+                created_at: now,
+                updated_at: now,
+            })
+            .clone())
+    }
+
+    async fn add_message(&self, id: &Uuid, role: Role, content: &str) -> anyhow::Result<()> {
+        self.get_or_create(id).await?;
+        if let Some(session) = self.0.lock().await.get_mut(id) {
+            session.messages.push(ChatMessage {
+                role,
+                content: MessageContent::Text(content.to_string()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Agent factory whose agents reply through `RepeatProvider(delay)` and
+/// keep history in `sessions`.
+#[must_use]
+pub fn repeat_factory(
+    sessions: Arc<InMemorySessions>,
+    delay: Duration,
+) -> AgentFactory<RepeatProvider, Arc<InMemorySessions>> {
+    Arc::new(move |model| {
+        let config = AgentConfig {
+            model: model.to_string(),
+            ..AgentConfig::default()
+        };
+        AgentLoop::new(RepeatProvider(delay), Arc::clone(&sessions), config)
+    })
+}
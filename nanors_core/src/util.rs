@@ -1,4 +1,5 @@
-//! Utility functions for content hashing and deduplication.
+//! Utility functions for content hashing, deduplication and reading model
+//! replies.
 
 use sha2::{Digest, Sha256};
 
@@ -21,6 +22,20 @@ pub fn content_hash(memory_type: &str, summary: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// Elements of the outermost JSON array in a model reply, which may wrap
+/// it in a code fence or prose. `None` when there is no such array.
+///
+/// Callers deserialize the elements one by one, so a malformed entry only
+/// loses itself.
+#[must_use]
+pub fn json_array_in_reply(reply: &str) -> Option<Vec<serde_json::Value>> {
+    let (start, end) = (reply.find('[')?, reply.rfind(']')?);
+    if end < start {
+        return None;
+    }
+    serde_json::from_str(&reply[start..=end]).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,4 +54,17 @@ mod tests {
         let h2 = content_hash("semantic", "had coffee");
         assert_ne!(h1, h2);
     }
+
+    #[test]
+    fn json_array_in_fenced_reply() {
+        let reply = "Here you go:\n```json\n[1, {\"a\": [2]}]\n```";
+        assert_eq!(
+            json_array_in_reply(reply),
+            Some(vec![serde_json::json!(1), serde_json::json!({"a": [2]})])
+        );
+        assert_eq!(json_array_in_reply("[]"), Some(Vec::new()));
+        assert_eq!(json_array_in_reply("nothing here"), None);
+        assert_eq!(json_array_in_reply("] backwards ["), None);
+        assert_eq!(json_array_in_reply("[1, 2"), None);
+    }
 }
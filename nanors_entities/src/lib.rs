@@ -19,5 +19,9 @@
 
 pub mod prelude;
 
+pub mod chat_sessions;
+pub mod enrichment_records;
+pub mod listening_chats;
+pub mod memory_cards;
 pub mod memory_items;
 pub mod sessions;
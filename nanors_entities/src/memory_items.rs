@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
+    #[sea_orm(column_type = "String(StringLen::N(255))")]
+    pub user_scope: String,
     #[sea_orm(column_type = "String(StringLen::N(64))")]
     pub memory_type: String,
     #[sea_orm(column_type = "Text")]
@@ -20,6 +22,10 @@ pub struct Model {
     #[sea_orm(column_type = "String(StringLen::N(64))")]
     pub content_hash: String,
     pub reinforcement_count: i32,
+    pub previous_version_id: Option<Uuid>,
+    pub superseded_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "String(StringLen::N(16))")]
+    pub version_relation: String,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
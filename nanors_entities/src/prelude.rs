@@ -1,4 +1,8 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0
 
+pub use super::chat_sessions::Entity as ChatSessions;
+pub use super::enrichment_records::Entity as EnrichmentRecords;
+pub use super::listening_chats::Entity as ListeningChats;
+pub use super::memory_cards::Entity as MemoryCards;
 pub use super::memory_items::Entity as MemoryItems;
 pub use super::sessions::Entity as Sessions;
@@ -0,0 +1,213 @@
+//! Memory card storage and lookup for `MemoryManager`.
+
+use nanors_core::memory::{MemoryItem, MemoryScope, SalienceScore};
+use nanors_entities::{memory_cards, memory_items};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::convert;
+use crate::enrichment::ExtractorEnricher;
+use crate::extraction::{CardExtractor, MemoryCard, slots};
+use crate::manager::MemoryManager;
+use crate::query::detector::QuestionType;
+use crate::rerank::Reranker;
+
+/// Slots that answer a question type.
+const fn slots_for(question_type: QuestionType) -> &'static [&'static str] {
+    match question_type {
+        QuestionType::WhatKind => &[slots::USER_TYPE, slots::PROFESSION, slots::NAME],
+        QuestionType::Where => &[slots::LOCATION],
+        QuestionType::Preference => &[slots::PREFERENCE],
+        QuestionType::HowMany | QuestionType::Have => &[slots::POSSESSION],
+        QuestionType::Can => &[slots::SKILL],
+        QuestionType::Recency
+        | QuestionType::Update
+        | QuestionType::When
+        | QuestionType::Generic => &[],
+    }
+}
+
+impl<R: Reranker> MemoryManager<R> {
+    /// Run `extractor` on every stored memory, in addition to the rule
+    /// engine (e.g. an [`LlmExtractor`](crate::extraction::LlmExtractor)).
+    #[must_use]
+    pub fn with_card_extractor(mut self, extractor: impl CardExtractor + 'static) -> Self {
+        self.extractors.push(Arc::new(extractor));
+        self
+    }
+
+    /// Extract cards from a stored memory, replacing any cards previously
+    /// extracted from it, and record each extractor's run so the enrichment
+    /// runner skips it. Failures are logged, never returned: cards are an
+    /// index, the memory itself is already stored.
+    pub(crate) async fn extract_cards(&self, scope: &str, item: &MemoryItem) {
+        if let Err(e) = self.delete_cards_for(&item.id).await {
+            warn!("Failed to clear cards of memory {}: {e}", item.id);
+            return;
+        }
+        for extractor in &self.extractors {
+            let enricher = ExtractorEnricher(Arc::clone(extractor));
+            if let Err(e) = self.enrich_item(&enricher, scope, item).await {
+                warn!("Failed to store cards for memory {}: {e}", item.id);
+            }
+        }
+    }
+
+    /// Store a card.
+    pub async fn insert_card(&self, card: &MemoryCard) -> anyhow::Result<()> {
+        memory_cards::Entity::insert(convert::card_to_model(card))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// All cards in a scope, newest first.
+    pub async fn list_cards(&self, scope: &MemoryScope) -> anyhow::Result<Vec<MemoryCard>> {
+        let models = memory_cards::Entity::find()
+            .filter(memory_cards::Column::UserScope.eq(scope.to_string()))
+            .order_by_desc(memory_cards::Column::CreatedAt)
+            .order_by_desc(memory_cards::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(models.into_iter().map(convert::card_from_model).collect())
+    }
+
+    /// Current cards about the user in the given slots: only the newest card
+    /// of each version key, newest first.
+    pub async fn current_cards(
+        &self,
+        scope: &MemoryScope,
+        slots: &[&str],
+    ) -> anyhow::Result<Vec<MemoryCard>> {
+        if slots.is_empty() {
+            return Ok(Vec::new());
+        }
+        let models = memory_cards::Entity::find()
+            .filter(memory_cards::Column::UserScope.eq(scope.to_string()))
+            .filter(memory_cards::Column::Entity.eq("user"))
+            .filter(memory_cards::Column::Slot.is_in(slots.iter().copied()))
+            .order_by_desc(memory_cards::Column::CreatedAt)
+            .order_by_desc(memory_cards::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let mut seen = HashSet::new();
+        Ok(models
+            .into_iter()
+            .map(convert::card_from_model)
+            .filter(|card| {
+                seen.insert(
+                    card.version_key
+                        .clone()
+                        .unwrap_or_else(|| card.id.to_string()),
+                )
+            })
+            .collect())
+    }
+
+    pub(crate) async fn delete_cards_for(&self, memory_id: &Uuid) -> anyhow::Result<()> {
+        memory_cards::Entity::delete_many()
+            .filter(memory_cards::Column::SourceMemoryId.eq(*memory_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Source memories of the cards answering `query_text`, each with the
+    /// confidence of its card.
+    async fn card_hits(
+        &self,
+        scope: &MemoryScope,
+        query_text: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(MemoryItem, f64)>> {
+        let mut wanted: Vec<&str> = Vec::new();
+        for question_type in self.question_detector.detect_all(query_text) {
+            for slot in slots_for(question_type) {
+                if !wanted.contains(slot) {
+                    wanted.push(slot);
+                }
+            }
+        }
+        let cards = self.current_cards(scope, &wanted).await?;
+
+        let mut order: Vec<(Uuid, f64)> = Vec::new();
+        for card in cards {
+            if let Some(id) = card.source_memory_id
+                && !order.iter().any(|(seen, _)| *seen == id)
+            {
+                order.push((id, card.confidence.map_or(1.0, f64::from)));
+            }
+        }
+        order.truncate(limit);
+        if order.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let items: Vec<MemoryItem> = memory_items::Entity::find()
+            .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+            .filter(memory_items::Column::Id.is_in(order.iter().map(|(id, _)| *id)))
+            .filter(memory_items::Column::SupersededAt.is_null())
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(convert::memory_item_from_model)
+            .collect();
+        Ok(order
+            .into_iter()
+            .filter_map(|(id, confidence)| {
+                items
+                    .iter()
+                    .find(|item| item.id == id)
+                    .map(|item| (item.clone(), confidence))
+            })
+            .collect())
+    }
+
+    /// Card hits for the query, ahead of the vector results.
+    pub(crate) async fn search_with_cards(
+        &self,
+        scope: &MemoryScope,
+        vector_results: Vec<SalienceScore<MemoryItem>>,
+        query_text: &str,
+        top_k: usize,
+    ) -> Vec<SalienceScore<MemoryItem>> {
+        let hits = match self.card_hits(scope, query_text, top_k).await {
+            Ok(hits) => hits,
+            Err(e) => {
+                warn!("Card lookup failed, using vector results only: {e}");
+                Vec::new()
+            }
+        };
+        fuse(hits, vector_results, top_k)
+    }
+}
+
+/// Put card hits first, scored like the best vector result so adaptive
+/// cutoffs keep them, followed by the remaining vector results.
+fn fuse(
+    hits: Vec<(MemoryItem, f64)>,
+    vector_results: Vec<SalienceScore<MemoryItem>>,
+    top_k: usize,
+) -> Vec<SalienceScore<MemoryItem>> {
+    let top_score = vector_results.first().map_or(1.0, |s| s.score);
+    let hit_ids: HashSet<Uuid> = hits.iter().map(|(item, _)| item.id).collect();
+    let mut fused: Vec<SalienceScore<MemoryItem>> = hits
+        .into_iter()
+        .map(|(item, confidence)| SalienceScore {
+            item,
+            score: top_score,
+            similarity: confidence,
+        })
+        .collect();
+    fused.extend(
+        vector_results
+            .into_iter()
+            .filter(|s| !hit_ids.contains(&s.item.id)),
+    );
+    fused.truncate(top_k);
+    fused
+}
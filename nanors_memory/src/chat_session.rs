@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use nanors_core::channel::{ChatSessionInfo, ChatSessionStore};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::manager::MemoryManager;
+use nanors_entities::{chat_sessions, listening_chats};
+
+#[async_trait]
+impl<R: crate::rerank::Reranker> ChatSessionStore for MemoryManager<R> {
+    async fn active_session(&self, channel: &str, chat_id: &str) -> anyhow::Result<Option<Uuid>> {
+        let model = chat_sessions::Entity::find()
+            .filter(chat_sessions::Column::Channel.eq(channel))
+            .filter(chat_sessions::Column::ChatId.eq(chat_id))
+            .filter(chat_sessions::Column::Archived.eq(false))
+            .one(&self.db)
+            .await?;
+        Ok(model.map(|m| m.session_id))
+    }
+
+    async fn create_session(
+        &self,
+        channel: &str,
+        chat_id: &str,
+        session_id: &Uuid,
+    ) -> anyhow::Result<()> {
+        chat_sessions::ActiveModel {
+            session_id: Set(*session_id),
+            channel: Set(channel.to_string()),
+            chat_id: Set(chat_id.to_string()),
+            created_at: Set(chrono::Utc::now().naive_utc()),
+            archived: Set(false),
+        }
+        .insert(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn archive_session(&self, channel: &str, chat_id: &str) -> anyhow::Result<Option<Uuid>> {
+        let Some(session_id) = self.active_session(channel, chat_id).await? else {
+            return Ok(None);
+        };
+
+        chat_sessions::Entity::update_many()
+            .col_expr(chat_sessions::Column::Archived, Expr::value(true))
+            .filter(chat_sessions::Column::SessionId.eq(session_id))
+            .exec(&self.db)
+            .await?;
+
+        info!("Archived session {session_id} for {channel} chat {chat_id}");
+        Ok(Some(session_id))
+    }
+
+    async fn list_sessions(
+        &self,
+        channel: &str,
+        chat_id: &str,
+    ) -> anyhow::Result<Vec<ChatSessionInfo>> {
+        let models = chat_sessions::Entity::find()
+            .filter(chat_sessions::Column::Channel.eq(channel))
+            .filter(chat_sessions::Column::ChatId.eq(chat_id))
+            .order_by_desc(chat_sessions::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        Ok(models
+            .into_iter()
+            .map(|m| ChatSessionInfo {
+                session_id: m.session_id,
+                created_at: m.created_at.and_utc(),
+                archived: m.archived,
+            })
+            .collect())
+    }
+}
+
+impl<R: crate::rerank::Reranker> MemoryManager<R> {
+    /// Chats of `channel` with listen mode on.
+    pub async fn listening_chats(&self, channel: &str) -> anyhow::Result<Vec<String>> {
+        let models = listening_chats::Entity::find()
+            .filter(listening_chats::Column::Channel.eq(channel))
+            .all(&self.db)
+            .await?;
+        Ok(models.into_iter().map(|m| m.chat_id).collect())
+    }
+
+    /// Turn listen mode on or off for a chat.
+    pub async fn set_listening(
+        &self,
+        channel: &str,
+        chat_id: &str,
+        enabled: bool,
+    ) -> anyhow::Result<()> {
+        let key = (channel.to_string(), chat_id.to_string());
+        let existing = listening_chats::Entity::find_by_id(key.clone())
+            .one(&self.db)
+            .await?;
+        match (enabled, existing) {
+            (true, None) => {
+                listening_chats::ActiveModel {
+                    channel: Set(key.0),
+                    chat_id: Set(key.1),
+                    created_at: Set(chrono::Utc::now().naive_utc()),
+                }
+                .insert(&self.db)
+                .await?;
+            }
+            (false, Some(_)) => {
+                listening_chats::Entity::delete_by_id(key)
+                    .exec(&self.db)
+                    .await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use nanors_core::memory::MemoryItem;
-use nanors_entities::memory_items;
-use sea_orm::JsonValue;
+use nanors_entities::{memory_cards, memory_items};
+use sea_orm::{JsonValue, Set};
+
+use crate::extraction::{CardKind, MemoryCard, Polarity};
 
 #[allow(clippy::cast_possible_truncation)]
 fn json_to_embedding(val: &JsonValue) -> Option<Vec<f32>> {
@@ -32,7 +34,75 @@ pub fn memory_item_from_model(m: memory_items::Model) -> MemoryItem {
         extra: m.extra,
         content_hash: m.content_hash,
         reinforcement_count: m.reinforcement_count,
+        previous_version_id: m.previous_version_id,
+        superseded_at: m.superseded_at.map(Into::into),
+        version_relation: m.version_relation.parse().unwrap_or_default(),
         created_at: m.created_at.into(),
         updated_at: m.updated_at.into(),
     }
 }
+
+pub fn card_from_model(m: memory_cards::Model) -> MemoryCard {
+    MemoryCard {
+        id: m.id,
+        user_scope: m.user_scope,
+        kind: CardKind::parse(&m.kind),
+        entity: m.entity,
+        slot: m.slot,
+        value: m.value,
+        polarity: m.polarity.as_deref().and_then(Polarity::parse),
+        version_key: m.version_key,
+        source_memory_id: m.source_memory_id,
+        engine: m.engine,
+        engine_version: m.engine_version,
+        confidence: m.confidence.map(|c| c as f32),
+        created_at: m.created_at.into(),
+    }
+}
+
+/// Row for `item` in `scope`, to insert.
+pub fn memory_item_to_model(scope: &str, item: &MemoryItem) -> memory_items::ActiveModel {
+    memory_items::ActiveModel {
+        id: Set(item.id),
+        user_scope: Set(scope.to_string()),
+        memory_type: Set(item.memory_type.to_string()),
+        summary: Set(item.summary.clone()),
+        embedding: Set(item
+            .embedding
+            .as_ref()
+            .map(|v| embedding_to_json(v.as_slice()))),
+        happened_at: Set(item.happened_at.into()),
+        extra: Set(item.extra.clone()),
+        content_hash: Set(item.content_hash.clone()),
+        reinforcement_count: Set(item.reinforcement_count),
+        previous_version_id: Set(item.previous_version_id),
+        superseded_at: Set(item.superseded_at.map(Into::into)),
+        version_relation: Set(item.version_relation.to_string()),
+        created_at: Set(item.created_at.into()),
+        updated_at: Set(item.updated_at.into()),
+    }
+}
+
+pub fn card_to_model(card: &MemoryCard) -> memory_cards::ActiveModel {
+    let now = chrono::Utc::now();
+    memory_cards::ActiveModel {
+        id: Set(card.id),
+        user_scope: Set(card.user_scope.clone()),
+        kind: Set(card.kind.as_str().to_string()),
+        entity: Set(card.entity.clone()),
+        slot: Set(card.slot.clone()),
+        value: Set(card.value.clone()),
+        polarity: Set(card.polarity.map(|p| p.as_str().to_string())),
+        event_date: Set(None),
+        document_date: Set(Some(now.into())),
+        version_key: Set(card.version_key.clone()),
+        version_relation: Set("Sets".to_string()),
+        source_memory_id: Set(card.source_memory_id),
+        engine: Set(card.engine.clone()),
+        engine_version: Set(card.engine_version.clone()),
+        confidence: Set(card.confidence.map(f64::from)),
+        extra: Set(None),
+        created_at: Set(card.created_at.into()),
+        updated_at: Set(now.into()),
+    }
+}
@@ -0,0 +1,331 @@
+//! Incremental enrichment of stored memories.
+//!
+//! An [`Enricher`] derives cards from a memory. Every run is recorded in
+//! `enrichment_records` under the enricher's kind and version, so
+//! [`EnrichmentRunner`] only processes memories without a successful record
+//! for the current version: new memories, earlier failures, and everything
+//! again once an enricher's version is bumped.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use nanors_core::memory::{MemoryItem, MemoryScope};
+use nanors_entities::{enrichment_records, memory_cards, memory_items};
+use sea_orm::sea_query::Query;
+use sea_orm::{ColumnTrait, EntityTrait, JsonValue, QueryFilter, QueryOrder, QuerySelect, Set};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::convert;
+use crate::extraction::{CardExtractor, MemoryCard};
+use crate::manager::MemoryManager;
+use crate::rerank::{Reranker, RuleBasedReranker};
+
+/// Memories fetched per batch by default.
+const DEFAULT_BATCH_SIZE: u64 = 100;
+
+/// Derives cards from a stored memory.
+#[async_trait]
+pub trait Enricher: Send + Sync {
+    /// Engine identifier stored in `enrichment_records.engine_kind`, e.g.
+    /// `rules`.
+    fn kind(&self) -> &str;
+
+    /// Engine version; bumping it makes every memory pending again.
+    fn version(&self) -> &str;
+
+    /// Cards for `item`, a memory in `scope`. They replace the cards this
+    /// engine produced for the memory before.
+    async fn enrich(&self, scope: &str, item: &MemoryItem) -> anyhow::Result<Vec<MemoryCard>>;
+}
+
+/// A card extractor run as an enricher; assistant replies get no cards.
+pub(crate) struct ExtractorEnricher(pub(crate) Arc<dyn CardExtractor>);
+
+#[async_trait]
+impl Enricher for ExtractorEnricher {
+    fn kind(&self) -> &str {
+        self.0.engine()
+    }
+
+    fn version(&self) -> &str {
+        self.0.version()
+    }
+
+    async fn enrich(&self, scope: &str, item: &MemoryItem) -> anyhow::Result<Vec<MemoryCard>> {
+        if item.summary.starts_with("Assistant:") {
+            return Ok(Vec::new());
+        }
+        self.0.extract(&item.summary, scope, item.id).await
+    }
+}
+
+/// Result of enriching one memory.
+pub(crate) enum Outcome {
+    /// Enriched, storing this many cards
+    Enriched(usize),
+    /// The enricher failed; the failure is recorded
+    Failed,
+}
+
+/// Totals of an enrichment run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnrichmentReport {
+    /// Memories enriched successfully
+    pub enriched: usize,
+    /// Memories the enricher failed on
+    pub failed: usize,
+    /// Cards stored
+    pub cards: usize,
+}
+
+impl<R: Reranker> MemoryManager<R> {
+    /// Current memories after `after` (by id) without a successful record for
+    /// `kind`/`version`, with their scope, oldest id first.
+    async fn pending_enrichment(
+        &self,
+        kind: &str,
+        version: &str,
+        scope: Option<&MemoryScope>,
+        after: Option<Uuid>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(String, MemoryItem)>> {
+        let done = Query::select()
+            .column(enrichment_records::Column::MemoryId)
+            .from(enrichment_records::Entity)
+            .and_where(enrichment_records::Column::EngineKind.eq(kind))
+            .and_where(enrichment_records::Column::EngineVersion.eq(version))
+            .and_where(enrichment_records::Column::Success.eq(true))
+            .to_owned();
+        let mut query = memory_items::Entity::find()
+            .filter(memory_items::Column::Id.not_in_subquery(done))
+            .filter(memory_items::Column::SupersededAt.is_null());
+        if let Some(scope) = scope {
+            query = query.filter(memory_items::Column::UserScope.eq(scope.to_string()));
+        }
+        if let Some(after) = after {
+            query = query.filter(memory_items::Column::Id.gt(after));
+        }
+        let models = query
+            .order_by_asc(memory_items::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await?;
+        Ok(models
+            .into_iter()
+            .map(|m| (m.user_scope.clone(), convert::memory_item_from_model(m)))
+            .collect())
+    }
+
+    /// Run `enricher` on one memory: replace its cards from this engine and
+    /// record the run. Enricher failures are recorded, not returned; only
+    /// database errors are.
+    pub(crate) async fn enrich_item(
+        &self,
+        enricher: &dyn Enricher,
+        scope: &str,
+        item: &MemoryItem,
+    ) -> anyhow::Result<Outcome> {
+        let (card_ids, error) = match enricher.enrich(scope, item).await {
+            Ok(cards) => {
+                memory_cards::Entity::delete_many()
+                    .filter(memory_cards::Column::SourceMemoryId.eq(item.id))
+                    .filter(memory_cards::Column::Engine.eq(enricher.kind()))
+                    .exec(&self.db)
+                    .await?;
+                for card in &cards {
+                    self.insert_card(card).await?;
+                }
+                if !cards.is_empty() {
+                    info!(
+                        "Extracted {} cards from memory {} ({})",
+                        cards.len(),
+                        item.id,
+                        enricher.kind()
+                    );
+                }
+                (Some(cards.iter().map(|c| c.id).collect::<Vec<_>>()), None)
+            }
+            Err(e) => {
+                warn!(
+                    "Enricher {} failed on memory {}: {e}",
+                    enricher.kind(),
+                    item.id
+                );
+                (None, Some(e.to_string()))
+            }
+        };
+
+        let outcome = card_ids
+            .as_ref()
+            .map_or(Outcome::Failed, |ids| Outcome::Enriched(ids.len()));
+        self.record_enrichment(enricher, scope, item.id, card_ids, error)
+            .await?;
+        Ok(outcome)
+    }
+
+    /// Store the record of a run, replacing an earlier run of the same
+    /// engine version on the memory.
+    async fn record_enrichment(
+        &self,
+        enricher: &dyn Enricher,
+        scope: &str,
+        memory_id: Uuid,
+        card_ids: Option<Vec<Uuid>>,
+        error: Option<String>,
+    ) -> anyhow::Result<()> {
+        enrichment_records::Entity::delete_many()
+            .filter(enrichment_records::Column::UserScope.eq(scope))
+            .filter(enrichment_records::Column::MemoryId.eq(memory_id))
+            .filter(enrichment_records::Column::EngineKind.eq(enricher.kind()))
+            .filter(enrichment_records::Column::EngineVersion.eq(enricher.version()))
+            .exec(&self.db)
+            .await?;
+
+        let now = Utc::now();
+        let record = enrichment_records::ActiveModel {
+            id: Set(Uuid::now_v7()),
+            user_scope: Set(scope.to_string()),
+            memory_id: Set(memory_id),
+            engine_kind: Set(enricher.kind().to_string()),
+            engine_version: Set(enricher.version().to_string()),
+            enriched_at: Set(now.into()),
+            success: Set(error.is_none()),
+            card_ids: Set(card_ids.map(|ids| {
+                JsonValue::Array(
+                    ids.iter()
+                        .map(|id| JsonValue::from(id.to_string()))
+                        .collect(),
+                )
+            })),
+            error_message: Set(error),
+            extra: Set(None),
+            created_at: Set(now.into()),
+        };
+        enrichment_records::Entity::insert(record)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Runs enrichers over the memories they have not processed yet, in
+/// batches.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use nanors_memory::MemoryManager;
+/// use nanors_memory::enrichment::EnrichmentRunner;
+/// use nanors_memory::rerank::RuleBasedReranker;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let manager = MemoryManager::<RuleBasedReranker>::new("sqlite::memory:").await?;
+/// let handle = EnrichmentRunner::new(Arc::new(manager)).spawn();
+/// let report = handle.await??;
+/// println!("{} memories enriched", report.enriched);
+/// # Ok(())
+/// # }
+/// ```
+pub struct EnrichmentRunner<R: Reranker = RuleBasedReranker> {
+    manager: Arc<MemoryManager<R>>,
+    enrichers: Vec<Arc<dyn Enricher>>,
+    scope: Option<MemoryScope>,
+    batch_size: u64,
+}
+
+impl<R: Reranker + 'static> EnrichmentRunner<R> {
+    /// A runner for the card extractors configured on `manager`.
+    #[must_use]
+    pub fn new(manager: Arc<MemoryManager<R>>) -> Self {
+        let enrichers = manager
+            .extractors
+            .iter()
+            .map(|extractor| {
+                Arc::new(ExtractorEnricher(Arc::clone(extractor))) as Arc<dyn Enricher>
+            })
+            .collect();
+        Self {
+            manager,
+            enrichers,
+            scope: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Also run `enricher`.
+    #[must_use]
+    pub fn with_enricher(mut self, enricher: impl Enricher + 'static) -> Self {
+        self.enrichers.push(Arc::new(enricher));
+        self
+    }
+
+    /// Only enrich memories in `scope` (all scopes by default).
+    #[must_use]
+    pub fn with_scope(mut self, scope: MemoryScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Memories fetched per batch.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Run every enricher until no memory is pending for it.
+    ///
+    /// A memory that fails is skipped for the rest of the run and retried
+    /// by the next one.
+    pub async fn run(&self) -> anyhow::Result<EnrichmentReport> {
+        let mut report = EnrichmentReport::default();
+        for enricher in &self.enrichers {
+            let mut after = None;
+            loop {
+                let batch = self
+                    .manager
+                    .pending_enrichment(
+                        enricher.kind(),
+                        enricher.version(),
+                        self.scope.as_ref(),
+                        after,
+                        self.batch_size,
+                    )
+                    .await?;
+                let Some((_, last)) = batch.last() else {
+                    break;
+                };
+                after = Some(last.id);
+
+                for (scope, item) in &batch {
+                    match self
+                        .manager
+                        .enrich_item(enricher.as_ref(), scope, item)
+                        .await?
+                    {
+                        Outcome::Enriched(cards) => {
+                            report.enriched += 1;
+                            report.cards += cards;
+                        }
+                        Outcome::Failed => report.failed += 1,
+                    }
+                }
+                info!(
+                    "Enriched a batch of {} memories with {} {}",
+                    batch.len(),
+                    enricher.kind(),
+                    enricher.version()
+                );
+            }
+        }
+        Ok(report)
+    }
+
+    /// Run in the background.
+    #[must_use]
+    pub fn spawn(self) -> JoinHandle<anyhow::Result<EnrichmentReport>> {
+        tokio::spawn(async move { self.run().await })
+    }
+}
@@ -0,0 +1,120 @@
+//! Retrieval evaluation datasets.
+//!
+//! A dataset is a JSONL file with one case per line: the memories to store,
+//! a query, and the ids of the memories the query should find.
+//!
+//! ```json
+//! {"id": "moved", "memories": [{"id": "old", "summary": "我住在丰台", "hours_ago": 72}, {"id": "new", "summary": "我搬到了东城", "hours_ago": 1}], "query": "我现在住在哪", "expected": ["new"]}
+//! ```
+
+use anyhow::Context;
+use nanors_core::memory::MemoryType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// One query and the memories it is asked against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCase {
+    /// Name in the report; the line number when empty
+    #[serde(default)]
+    pub id: String,
+    /// Memories stored before the query, in their own scope
+    pub memories: Vec<EvalMemory>,
+    /// Query text
+    pub query: String,
+    /// Ids of the memories the query should retrieve
+    pub expected: Vec<String>,
+}
+
+/// A memory of an [`EvalCase`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalMemory {
+    /// Id referenced by [`EvalCase::expected`], unique within the case
+    pub id: String,
+    /// Memory text, stored as is
+    pub summary: String,
+    /// Age of the memory
+    #[serde(default)]
+    pub hours_ago: i64,
+    #[serde(default = "EvalMemory::default_memory_type")]
+    pub memory_type: MemoryType,
+}
+
+impl EvalMemory {
+    const fn default_memory_type() -> MemoryType {
+        MemoryType::Semantic
+    }
+}
+
+impl EvalCase {
+    /// Check that memory ids are unique and every expected id exists.
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut ids = HashSet::new();
+        for memory in &self.memories {
+            anyhow::ensure!(
+                ids.insert(memory.id.as_str()),
+                "duplicate memory id {}",
+                memory.id
+            );
+        }
+        anyhow::ensure!(!self.expected.is_empty(), "no expected memories");
+        for id in &self.expected {
+            anyhow::ensure!(ids.contains(id.as_str()), "unknown expected memory {id}");
+        }
+        Ok(())
+    }
+}
+
+/// Parse a JSONL dataset; blank lines are skipped.
+pub fn parse_dataset(jsonl: &str) -> anyhow::Result<Vec<EvalCase>> {
+    let mut cases = Vec::new();
+    for (index, line) in jsonl.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut case: EvalCase = serde_json::from_str(line)
+            .with_context(|| format!("Invalid case on line {line_number}"))?;
+        if case.id.is_empty() {
+            case.id = format!("line {line_number}");
+        }
+        case.validate()
+            .with_context(|| format!("Invalid case {} on line {line_number}", case.id))?;
+        cases.push(case);
+    }
+    Ok(cases)
+}
+
+/// Read a JSONL dataset file.
+pub fn load_dataset(path: &Path) -> anyhow::Result<Vec<EvalCase>> {
+    let jsonl = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read dataset {}", path.display()))?;
+    parse_dataset(&jsonl).with_context(|| format!("Invalid dataset {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dataset() -> anyhow::Result<()> {
+        let jsonl = r#"
+{"memories": [{"id": "a", "summary": "我住在丰台", "hours_ago": 72}, {"id": "b", "summary": "I use tabs", "memory_type": "procedural"}], "query": "我住在哪", "expected": ["a"]}
+"#;
+        let cases = parse_dataset(jsonl)?;
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].id, "line 2");
+        assert_eq!(cases[0].memories[0].hours_ago, 72);
+        assert_eq!(cases[0].memories[0].memory_type, MemoryType::Semantic);
+        assert_eq!(cases[0].memories[1].memory_type, MemoryType::Procedural);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_dataset_rejects_unknown_expected_ids() {
+        let jsonl = r#"{"id": "x", "memories": [{"id": "a", "summary": "s"}], "query": "q", "expected": ["b"]}"#;
+        let error = parse_dataset(jsonl).err().map(|e| format!("{e:#}"));
+        assert!(error.is_some_and(|e| e.contains("unknown expected memory b")));
+    }
+}
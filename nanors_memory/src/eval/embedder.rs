@@ -0,0 +1,161 @@
+//! Embeddings for evaluation runs.
+//!
+//! [`HashEmbedder`] needs no model and always gives the same vectors, so
+//! runs before and after a scoring change are directly comparable; it only
+//! captures lexical similarity. [`CachedEmbedder`] uses the real embedding
+//! model and keeps its vectors in a file, so repeated runs cost no calls.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use nanors_core::LLMProvider;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::lexical_index::tokenize;
+
+/// Dimensions of [`HashEmbedder`] vectors by default.
+const DEFAULT_DIMENSIONS: usize = 256;
+
+/// Turns memory and query text into embeddings.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>>;
+}
+
+/// Deterministic embedder: the index terms of the text (words, CJK
+/// characters and character pairs) hashed into a fixed number of signed
+/// buckets, normalized to unit length.
+#[derive(Debug, Clone, Copy)]
+pub struct HashEmbedder {
+    dimensions: usize,
+}
+
+impl HashEmbedder {
+    /// Embedder with `dimensions` buckets.
+    #[must_use]
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+
+    /// The embedding of `text`; all zeros when it has no index terms.
+    #[must_use]
+    pub fn vector(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0_f32; self.dimensions];
+        for token in tokenize(text) {
+            let hash = fnv1a(token.as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            vector[bucket] += if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for x in &mut vector {
+                *x /= norm;
+            }
+        }
+        vector
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(DEFAULT_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        Ok(self.vector(text))
+    }
+}
+
+/// 64-bit FNV-1a: stable across platforms and Rust versions, unlike the
+/// standard library's hasher.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The provider's embeddings, cached in a JSON file keyed by text.
+///
+/// The cache does not record the embedding model; delete the file after
+/// switching models.
+pub struct CachedEmbedder {
+    provider: Arc<dyn LLMProvider>,
+    path: PathBuf,
+    cache: Mutex<HashMap<String, Vec<f32>>>,
+}
+
+impl CachedEmbedder {
+    /// Embedder backed by `provider`, starting from the cache at `path` if
+    /// the file exists.
+    pub fn open(provider: Arc<dyn LLMProvider>, path: &Path) -> anyhow::Result<Self> {
+        let cache = if path.exists() {
+            let json = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read embedding cache {}", path.display()))?;
+            serde_json::from_str(&json)
+                .with_context(|| format!("Invalid embedding cache {}", path.display()))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            provider,
+            path: path.to_path_buf(),
+            cache: Mutex::new(cache),
+        })
+    }
+
+    /// Write the cache, including embeddings fetched since it was opened.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let json = {
+            let cache = self
+                .cache
+                .lock()
+                .map_err(|_| anyhow::anyhow!("embedding cache lock poisoned"))?;
+            serde_json::to_string(&*cache)?
+        };
+        std::fs::write(&self.path, json)
+            .with_context(|| format!("Failed to write embedding cache {}", self.path.display()))
+    }
+
+    fn cached(&self, text: &str) -> Option<Vec<f32>> {
+        self.cache.lock().ok()?.get(text).cloned()
+    }
+}
+
+#[async_trait]
+impl Embedder for CachedEmbedder {
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        if let Some(embedding) = self.cached(text) {
+            return Ok(embedding);
+        }
+        let embedding = self.provider.embed(text).await?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(text.to_string(), embedding.clone());
+        }
+        Ok(embedding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::cosine_similarity;
+
+    #[test]
+    fn test_hash_embedder_is_deterministic_and_lexical() {
+        let embedder = HashEmbedder::default();
+        let home = embedder.vector("我住在东城");
+        assert_eq!(home, embedder.vector("我住在东城"));
+        assert_eq!(home.len(), 256);
+
+        let query = embedder.vector("我住在哪");
+        let unrelated = embedder.vector("I prefer tabs over spaces");
+        assert!(cosine_similarity(&query, &home) > cosine_similarity(&query, &unrelated));
+        assert!(embedder.vector("!!").iter().all(|x| *x == 0.0));
+    }
+}
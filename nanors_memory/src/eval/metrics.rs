@@ -0,0 +1,175 @@
+//! Ranking metrics with binary relevance.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Fraction of `expected` found in the first `k` of `ranked`.
+fn recall_at(ranked: &[String], expected: &HashSet<&str>, k: usize) -> f64 {
+    if expected.is_empty() {
+        return 0.0;
+    }
+    let found = ranked
+        .iter()
+        .take(k)
+        .filter(|id| expected.contains(id.as_str()))
+        .count();
+    found as f64 / expected.len() as f64
+}
+
+/// `1 / rank` of the first expected item, 0.0 when none is ranked.
+fn reciprocal_rank(ranked: &[String], expected: &HashSet<&str>) -> f64 {
+    ranked
+        .iter()
+        .position(|id| expected.contains(id.as_str()))
+        .map_or(0.0, |index| 1.0 / (index + 1) as f64)
+}
+
+/// Normalized discounted cumulative gain of the first `k` of `ranked`.
+fn ndcg_at(ranked: &[String], expected: &HashSet<&str>, k: usize) -> f64 {
+    let discount = |index: usize| 1.0 / ((index + 2) as f64).log2();
+    let dcg: f64 = ranked
+        .iter()
+        .take(k)
+        .enumerate()
+        .filter(|(_, id)| expected.contains(id.as_str()))
+        .map(|(index, _)| discount(index))
+        .sum();
+    let ideal: f64 = (0..k.min(expected.len())).map(discount).sum();
+    if ideal > 0.0 { dcg / ideal } else { 0.0 }
+}
+
+/// Metrics of one case.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaseScores {
+    /// Recall at each of the report's `ks`
+    pub recall: Vec<f64>,
+    /// nDCG at each of the report's `ks`
+    pub ndcg: Vec<f64>,
+    pub reciprocal_rank: f64,
+    /// Results kept by the adaptive cutoff
+    pub cutoff: usize,
+    /// Recall of the results kept by the adaptive cutoff
+    pub cutoff_recall: f64,
+    /// Precision of the results kept by the adaptive cutoff
+    pub cutoff_precision: f64,
+}
+
+impl CaseScores {
+    /// Score `ranked` against the `expected` ids, with `cutoff` results
+    /// kept.
+    #[must_use]
+    pub fn new(ranked: &[String], expected: &[String], ks: &[usize], cutoff: usize) -> Self {
+        let expected: HashSet<&str> = expected.iter().map(String::as_str).collect();
+        let expected = &expected;
+        let kept = ranked
+            .iter()
+            .take(cutoff)
+            .filter(|id| expected.contains(id.as_str()))
+            .count();
+        Self {
+            recall: ks.iter().map(|k| recall_at(ranked, expected, *k)).collect(),
+            ndcg: ks.iter().map(|k| ndcg_at(ranked, expected, *k)).collect(),
+            reciprocal_rank: reciprocal_rank(ranked, expected),
+            cutoff,
+            cutoff_recall: recall_at(ranked, expected, cutoff),
+            cutoff_precision: if cutoff == 0 {
+                0.0
+            } else {
+                kept as f64 / cutoff as f64
+            },
+        }
+    }
+}
+
+/// Metrics averaged over cases.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    pub cases: usize,
+    /// Mean recall at each of the report's `ks`
+    pub recall: Vec<f64>,
+    /// Mean nDCG at each of the report's `ks`
+    pub ndcg: Vec<f64>,
+    /// Mean reciprocal rank
+    pub mrr: f64,
+    /// Mean number of results kept by the adaptive cutoff
+    pub mean_cutoff: f64,
+    pub cutoff_recall: f64,
+    pub cutoff_precision: f64,
+}
+
+impl Metrics {
+    /// Mean of `scores`, each with one value per k in `ks`.
+    #[must_use]
+    pub fn mean<'a>(scores: impl IntoIterator<Item = &'a CaseScores>, ks: &[usize]) -> Self {
+        let mut metrics = Self {
+            recall: vec![0.0; ks.len()],
+            ndcg: vec![0.0; ks.len()],
+            ..Self::default()
+        };
+        for case in scores {
+            metrics.cases += 1;
+            for (total, value) in metrics.recall.iter_mut().zip(&case.recall) {
+                *total += value;
+            }
+            for (total, value) in metrics.ndcg.iter_mut().zip(&case.ndcg) {
+                *total += value;
+            }
+            metrics.mrr += case.reciprocal_rank;
+            metrics.mean_cutoff += case.cutoff as f64;
+            metrics.cutoff_recall += case.cutoff_recall;
+            metrics.cutoff_precision += case.cutoff_precision;
+        }
+        if metrics.cases > 0 {
+            let n = metrics.cases as f64;
+            for value in metrics.recall.iter_mut().chain(metrics.ndcg.iter_mut()) {
+                *value /= n;
+            }
+            metrics.mrr /= n;
+            metrics.mean_cutoff /= n;
+            metrics.cutoff_recall /= n;
+            metrics.cutoff_precision /= n;
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_case_scores() {
+        let expected = ranked(&["b", "d"]);
+        let ranked = ranked(&["a", "b", "c", "d"]);
+        let scores = CaseScores::new(&ranked, &expected, &[1, 2, 4], 2);
+
+        assert_eq!(scores.recall, [0.0, 0.5, 1.0]);
+        assert!((scores.reciprocal_rank - 0.5).abs() < 1e-9);
+        assert!((scores.cutoff_recall - 0.5).abs() < 1e-9);
+        assert!((scores.cutoff_precision - 0.5).abs() < 1e-9);
+
+        // DCG = 1/log2(3) + 1/log2(5), ideal = 1 + 1/log2(3)
+        let dcg = 1.0 / 3.0_f64.log2() + 1.0 / 5.0_f64.log2();
+        let ideal = 1.0 + 1.0 / 3.0_f64.log2();
+        assert!((scores.ndcg[2] - dcg / ideal).abs() < 1e-9);
+        assert!(scores.ndcg[0].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_metrics_mean() {
+        let expected = ranked(&["a"]);
+        let hit = CaseScores::new(&ranked(&["a", "b"]), &expected, &[1], 1);
+        let miss = CaseScores::new(&ranked(&["b"]), &expected, &[1], 0);
+
+        let metrics = Metrics::mean([&hit, &miss], &[1]);
+        assert_eq!(metrics.cases, 2);
+        assert_eq!(metrics.recall, [0.5]);
+        assert!((metrics.mrr - 0.5).abs() < 1e-9);
+        assert!((metrics.mean_cutoff - 0.5).abs() < 1e-9);
+        assert_eq!(Metrics::mean([], &[1]).recall, [0.0]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Retrieval evaluation.
+//!
+//! Runs a dataset of (memories, query, expected memory ids) cases through
+//! the same retrieval the agent uses, `search_enhanced` followed by
+//! `find_adaptive_cutoff`, and reports recall@k, MRR and nDCG@k overall and
+//! per [`QuestionType`], so scoring changes can be compared on numbers
+//! rather than anecdotes.
+//!
+//! Each case is stored in its own scope of the given manager, which should
+//! be backed by a throwaway store such as `sqlite::memory:`.
+
+mod dataset;
+mod embedder;
+mod metrics;
+
+pub use dataset::{EvalCase, EvalMemory, load_dataset, parse_dataset};
+pub use embedder::{CachedEmbedder, Embedder, HashEmbedder};
+pub use metrics::{CaseScores, Metrics};
+
+use chrono::{Duration, Utc};
+use nanors_core::agent::RetrievalConfig;
+use nanors_core::memory::{MemoryItem, MemoryItemRepo, MemoryScope};
+use nanors_core::retrieval::adaptive::find_adaptive_cutoff;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+use crate::manager::MemoryManager;
+use crate::query::detector::QuestionType;
+use crate::rerank::Reranker;
+
+/// How an evaluation run retrieves and what it measures.
+#[derive(Debug, Clone)]
+pub struct EvalOptions {
+    /// Ranks recall and nDCG are reported at
+    pub ks: Vec<usize>,
+    /// Result count and adaptive cutoff, as configured for the agent
+    pub retrieval: RetrievalConfig,
+}
+
+impl Default for EvalOptions {
+    fn default() -> Self {
+        Self {
+            ks: vec![1, 3, 5, 10],
+            retrieval: RetrievalConfig::default(),
+        }
+    }
+}
+
+/// Outcome of one case.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub id: String,
+    pub question_type: QuestionType,
+    /// Dataset ids of the retrieved memories, best first
+    pub ranked: Vec<String>,
+    pub scores: CaseScores,
+}
+
+/// Metrics of an evaluation run.
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    /// Ranks recall and nDCG are reported at
+    pub ks: Vec<usize>,
+    pub overall: Metrics,
+    /// Metrics per detected question type, in order of first appearance
+    pub by_question_type: Vec<(QuestionType, Metrics)>,
+    pub cases: Vec<CaseResult>,
+}
+
+/// Run every case in `cases` against `manager`.
+pub async fn evaluate<R: Reranker>(
+    manager: &MemoryManager<R>,
+    cases: &[EvalCase],
+    embedder: &dyn Embedder,
+    options: &EvalOptions,
+) -> anyhow::Result<EvalReport> {
+    let fetch_count = options
+        .ks
+        .iter()
+        .copied()
+        .chain([options.retrieval.adaptive.max_results])
+        .max()
+        .unwrap_or_default();
+
+    let mut results = Vec::with_capacity(cases.len());
+    for (index, case) in cases.iter().enumerate() {
+        let scope = MemoryScope::user("eval", index);
+        let ids = ingest(manager, &scope, case, embedder).await?;
+
+        let query_embedding = embedder.embed(&case.query).await?;
+        let retrieved = MemoryItemRepo::search_enhanced(
+            manager,
+            &scope,
+            &query_embedding,
+            &case.query,
+            fetch_count,
+        )
+        .await?;
+        let scores: Vec<f64> = retrieved.iter().map(|s| s.score).collect();
+        let cutoff = find_adaptive_cutoff(&scores, &options.retrieval.adaptive)
+            .min(options.retrieval.items_top_k);
+
+        let ranked: Vec<String> = retrieved
+            .iter()
+            .filter_map(|s| ids.get(&s.item.id).cloned())
+            .collect();
+        results.push(CaseResult {
+            id: case.id.clone(),
+            question_type: manager.question_detector.detect(&case.query),
+            scores: CaseScores::new(&ranked, &case.expected, &options.ks, cutoff),
+            ranked,
+        });
+    }
+
+    Ok(EvalReport::new(options.ks.clone(), results))
+}
+
+/// Store the memories of `case` in `scope`, returning their dataset ids.
+async fn ingest<R: Reranker>(
+    manager: &MemoryManager<R>,
+    scope: &MemoryScope,
+    case: &EvalCase,
+    embedder: &dyn Embedder,
+) -> anyhow::Result<HashMap<Uuid, String>> {
+    let now = Utc::now();
+    let mut ids = HashMap::with_capacity(case.memories.len());
+    for memory in &case.memories {
+        let embedding = embedder.embed(&memory.summary).await?;
+        let item = MemoryItem::new(
+            memory.memory_type.clone(),
+            &memory.summary,
+            Some(embedding),
+            now - Duration::hours(memory.hours_ago),
+        );
+        MemoryItemRepo::insert(manager, scope, &item).await?;
+        ids.insert(item.id, memory.id.clone());
+    }
+    Ok(ids)
+}
+
+impl EvalReport {
+    /// Aggregate case results, grouping by question type in order of first
+    /// appearance.
+    #[must_use]
+    pub fn new(ks: Vec<usize>, cases: Vec<CaseResult>) -> Self {
+        let overall = Metrics::mean(cases.iter().map(|c| &c.scores), &ks);
+        let mut types: Vec<QuestionType> = Vec::new();
+        for case in &cases {
+            if !types.contains(&case.question_type) {
+                types.push(case.question_type);
+            }
+        }
+        let by_question_type = types
+            .into_iter()
+            .map(|question_type| {
+                let scores = cases
+                    .iter()
+                    .filter(|c| c.question_type == question_type)
+                    .map(|c| &c.scores);
+                (question_type, Metrics::mean(scores, &ks))
+            })
+            .collect();
+        Self {
+            ks,
+            overall,
+            by_question_type,
+            cases,
+        }
+    }
+}
+
+/// One metrics row of the report table.
+fn write_row(f: &mut fmt::Formatter<'_>, name: &str, m: &Metrics) -> fmt::Result {
+    write!(f, "{name:<12} {:>5}", m.cases)?;
+    for recall in &m.recall {
+        write!(f, " {recall:>6.3}")?;
+    }
+    write!(f, " {:>6.3}", m.mrr)?;
+    for ndcg in &m.ndcg {
+        write!(f, " {ndcg:>7.3}")?;
+    }
+    writeln!(
+        f,
+        " {:>7.2} {:>7.3} {:>7.3}",
+        m.mean_cutoff, m.cutoff_recall, m.cutoff_precision
+    )
+}
+
+impl fmt::Display for EvalReport {
+    /// A table of the overall and per-question-type metrics, followed by
+    /// the cases whose expected memories were not all in the cutoff.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<12} {:>5}", "type", "cases")?;
+        for k in &self.ks {
+            write!(f, " {:>6}", format!("R@{k}"))?;
+        }
+        write!(f, " {:>6}", "MRR")?;
+        for k in &self.ks {
+            write!(f, " {:>7}", format!("nDCG@{k}"))?;
+        }
+        writeln!(f, " {:>7} {:>7} {:>7}", "kept", "R@kept", "P@kept")?;
+
+        write_row(f, "all", &self.overall)?;
+        for (question_type, metrics) in &self.by_question_type {
+            write_row(f, question_type.as_str(), metrics)?;
+        }
+
+        let misses: Vec<&CaseResult> = self
+            .cases
+            .iter()
+            .filter(|c| c.scores.cutoff_recall < 1.0)
+            .collect();
+        if !misses.is_empty() {
+            writeln!(f, "\nMissed after cutoff:")?;
+            for case in misses {
+                writeln!(
+                    f,
+                    "  {} ({}): kept {}, ranked [{}]",
+                    case.id,
+                    case.question_type.as_str(),
+                    case.scores.cutoff,
+                    case.ranked.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! Memory card types.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Well-known slot names.
+pub mod slots {
+    pub const NAME: &str = "name";
+    pub const USER_TYPE: &str = "user_type";
+    pub const PROFESSION: &str = "profession";
+    pub const EMPLOYER: &str = "employer";
+    pub const LOCATION: &str = "location";
+    pub const PREFERENCE: &str = "preference";
+    pub const POSSESSION: &str = "possession";
+    pub const SKILL: &str = "skill";
+    pub const RELATIONSHIP: &str = "relationship";
+    pub const GOAL: &str = "goal";
+
+    /// Every known slot.
+    pub const ALL: &[&str] = &[
+        NAME,
+        USER_TYPE,
+        PROFESSION,
+        EMPLOYER,
+        LOCATION,
+        PREFERENCE,
+        POSSESSION,
+        SKILL,
+        RELATIONSHIP,
+        GOAL,
+    ];
+
+    /// Slots holding many values at once (one version chain per value);
+    /// the others hold a single current value.
+    pub const MULTI_VALUED: &[&str] = &[PREFERENCE, POSSESSION, SKILL, RELATIONSHIP, GOAL];
+}
+
+/// What a card describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardKind {
+    Fact,
+    Preference,
+    Event,
+    Profile,
+    Relationship,
+    Goal,
+}
+
+impl CardKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fact => "fact",
+            Self::Preference => "preference",
+            Self::Event => "event",
+            Self::Profile => "profile",
+            Self::Relationship => "relationship",
+            Self::Goal => "goal",
+        }
+    }
+
+    /// Parse a stored kind; unknown kinds read as [`CardKind::Fact`].
+    #[must_use]
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "preference" => Self::Preference,
+            "event" => Self::Event,
+            "profile" => Self::Profile,
+            "relationship" => Self::Relationship,
+            "goal" => Self::Goal,
+            _ => Self::Fact,
+        }
+    }
+}
+
+/// Whether a preference is a like or a dislike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Polarity {
+    Positive,
+    Negative,
+    Neutral,
+}
+
+impl Polarity {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Positive => "positive",
+            Self::Negative => "negative",
+            Self::Neutral => "neutral",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "positive" => Some(Self::Positive),
+            "negative" => Some(Self::Negative),
+            "neutral" => Some(Self::Neutral),
+            _ => None,
+        }
+    }
+}
+
+/// One entity/slot/value fact extracted from a memory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryCard {
+    pub id: Uuid,
+    pub user_scope: String,
+    pub kind: CardKind,
+    /// Who the fact is about, e.g. `user`
+    pub entity: String,
+    pub slot: String,
+    pub value: String,
+    pub polarity: Option<Polarity>,
+    /// Cards sharing a key are versions of the same fact; the newest wins
+    pub version_key: Option<String>,
+    pub source_memory_id: Option<Uuid>,
+    pub engine: String,
+    pub engine_version: String,
+    pub confidence: Option<f32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MemoryCard {
+    /// A card for `entity.slot = value` with its version key filled in.
+    #[must_use]
+    pub fn new(kind: CardKind, entity: &str, slot: &str, value: &str) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_scope: String::new(),
+            kind,
+            entity: entity.to_string(),
+            slot: slot.to_string(),
+            value: value.to_string(),
+            polarity: None,
+            version_key: Some(version_key(entity, slot, value)),
+            source_memory_id: None,
+            engine: String::new(),
+            engine_version: String::new(),
+            confidence: None,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// `entity:slot` for single-valued slots, `entity:slot:value` otherwise, so
+/// a new location replaces the old one while a new preference does not.
+#[must_use]
+pub fn version_key(entity: &str, slot: &str, value: &str) -> String {
+    if slots::MULTI_VALUED.contains(&slot) {
+        format!("{entity}:{slot}:{}", value.to_lowercase())
+    } else {
+        format!("{entity}:{slot}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_key_groups_single_valued_slots() {
+        assert_eq!(
+            version_key("user", slots::LOCATION, "北京"),
+            version_key("user", slots::LOCATION, "杭州")
+        );
+        assert_ne!(
+            version_key("user", slots::PREFERENCE, "茶"),
+            version_key("user", slots::PREFERENCE, "咖啡")
+        );
+    }
+}
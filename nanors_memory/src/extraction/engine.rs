@@ -0,0 +1,207 @@
+//! Rule-based card extraction.
+
+use async_trait::async_trait;
+use regex::Regex;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::CardExtractor;
+use super::cards::MemoryCard;
+use super::patterns::{CardPattern, default_patterns};
+use crate::query::language::ZH_QUESTION_WORDS;
+use crate::scoring;
+
+const ENGINE: &str = "rules";
+const ENGINE_VERSION: &str = "1.0.0";
+
+/// Clause separators; each clause is matched on its own so a question in
+/// one clause does not hide a fact in the next.
+const CLAUSE_ENDS: &[char] = &['。', '！', '？', '!', '?', '；', ';', '\n'];
+
+/// English conjunctions that end a captured value.
+const VALUE_STOPS: &[&str] = &[" and ", " but ", " because ", " so "];
+
+/// Extracts cards with regex patterns; cheap enough to run on every memory.
+pub struct ExtractionEngine {
+    patterns: Vec<(CardPattern, Regex)>,
+}
+
+impl ExtractionEngine {
+    /// Create an engine from patterns; patterns that fail to compile are
+    /// skipped with a warning.
+    #[must_use]
+    pub fn new(patterns: Vec<CardPattern>) -> Self {
+        let patterns = patterns
+            .into_iter()
+            .filter_map(|pattern| match Regex::new(&pattern.pattern) {
+                Ok(re) => Some((pattern, re)),
+                Err(e) => {
+                    warn!("Skipping card pattern {}: {e}", pattern.pattern);
+                    None
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Create an engine with the default Chinese/English patterns.
+    #[must_use]
+    pub fn with_defaults() -> Self {
+        Self::new(default_patterns())
+    }
+
+    /// Extract cards from a memory summary (a leading `User:` is ignored).
+    #[must_use]
+    pub fn extract_from_summary(
+        &self,
+        text: &str,
+        scope: &str,
+        source_memory_id: Uuid,
+    ) -> Vec<MemoryCard> {
+        let text = text.strip_prefix("User:").unwrap_or(text);
+        let mut cards: Vec<MemoryCard> = Vec::new();
+
+        for clause in clauses(text) {
+            for (pattern, re) in &self.patterns {
+                for caps in re.captures_iter(clause) {
+                    let Some(value) = caps.name("value").map(|m| clean_value(m.as_str())) else {
+                        continue;
+                    };
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let mut card =
+                        MemoryCard::new(pattern.kind, &pattern.entity, &pattern.slot, &value);
+                    if cards
+                        .iter()
+                        .any(|c| c.version_key == card.version_key && c.value == card.value)
+                    {
+                        continue;
+                    }
+                    card.user_scope = scope.to_string();
+                    card.polarity = pattern.polarity;
+                    card.source_memory_id = Some(source_memory_id);
+                    card.engine = ENGINE.to_string();
+                    card.engine_version = ENGINE_VERSION.to_string();
+                    card.confidence = Some(pattern.confidence);
+                    cards.push(card);
+                }
+            }
+        }
+        cards
+    }
+}
+
+impl Default for ExtractionEngine {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[async_trait]
+impl CardExtractor for ExtractionEngine {
+    fn engine(&self) -> &str {
+        ENGINE
+    }
+
+    fn version(&self) -> &str {
+        ENGINE_VERSION
+    }
+
+    async fn extract(
+        &self,
+        text: &str,
+        scope: &str,
+        source_memory_id: Uuid,
+    ) -> anyhow::Result<Vec<MemoryCard>> {
+        Ok(self.extract_from_summary(text, scope, source_memory_id))
+    }
+}
+
+/// Statement clauses of `text`; questions are dropped.
+fn clauses(text: &str) -> impl Iterator<Item = &str> {
+    text.split_inclusive(CLAUSE_ENDS)
+        .flat_map(|s| s.split(". "))
+        .filter(|clause| {
+            let clause = clause.trim();
+            !clause.is_empty()
+                && !clause.ends_with(['?', '？'])
+                && scoring::count_question_keywords(clause, ZH_QUESTION_WORDS) == 0
+        })
+}
+
+fn clean_value(value: &str) -> String {
+    let value = VALUE_STOPS
+        .iter()
+        .filter_map(|stop| value.find(stop))
+        .min()
+        .map_or(value, |end| &value[..end]);
+    value
+        .trim()
+        .trim_end_matches(['了', '的', '啦', '呀', '啊'])
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extraction::{Polarity, slots};
+
+    fn extract(text: &str) -> Vec<(String, String)> {
+        ExtractionEngine::with_defaults()
+            .extract_from_summary(text, "global", Uuid::now_v7())
+            .into_iter()
+            .map(|c| (c.slot, c.value))
+            .collect()
+    }
+
+    fn pair(slot: &str, value: &str) -> (String, String) {
+        (slot.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_default_patterns_compile() {
+        let engine = ExtractionEngine::with_defaults();
+        assert_eq!(engine.patterns.len(), default_patterns().len());
+    }
+
+    #[test]
+    fn test_extract_chinese_profile() {
+        let cards = extract("User: 我是安卓玩机用户，住在北京");
+        assert!(cards.contains(&pair(slots::USER_TYPE, "安卓玩机")));
+        assert!(cards.contains(&pair(slots::LOCATION, "北京")));
+    }
+
+    #[test]
+    fn test_extract_english() {
+        let cards = extract("I live in Berlin and I work at Acme Corp. My name is Ada");
+        assert!(cards.contains(&pair(slots::LOCATION, "Berlin")));
+        assert!(cards.contains(&pair(slots::EMPLOYER, "Acme Corp")));
+        assert!(cards.contains(&pair(slots::NAME, "Ada")));
+    }
+
+    #[test]
+    fn test_extract_preference_polarity() {
+        let engine = ExtractionEngine::with_defaults();
+        let cards =
+            engine.extract_from_summary("我不喜欢香菜，我喜欢喝茶", "global", Uuid::now_v7());
+        let polarity = |value: &str| {
+            cards
+                .iter()
+                .find(|c| c.value == value)
+                .and_then(|c| c.polarity)
+        };
+        assert_eq!(polarity("香菜"), Some(Polarity::Negative));
+        assert_eq!(polarity("喝茶"), Some(Polarity::Positive));
+        assert_eq!(cards.len(), 2);
+    }
+
+    #[test]
+    fn test_questions_and_chatter_extract_nothing() {
+        assert!(extract("User: 我住在哪里？").is_empty());
+        assert!(extract("我是什么用户").is_empty());
+        assert!(extract("我有点累").is_empty());
+        assert!(extract("你好").is_empty());
+    }
+}
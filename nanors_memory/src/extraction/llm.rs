@@ -0,0 +1,142 @@
+//! LLM-based card extraction.
+
+use async_trait::async_trait;
+use nanors_core::{ChatMessage, LLMProvider, MessageContent, Role, json_array_in_reply};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::CardExtractor;
+use super::cards::{CardKind, MemoryCard, Polarity, slots};
+
+const ENGINE_VERSION: &str = "1";
+
+/// Confidence for cards the model returns without one.
+const DEFAULT_CONFIDENCE: f32 = 0.7;
+
+/// Asks the provider for cards; catches phrasings the rules miss at the
+/// cost of one model call per memory.
+pub struct LlmExtractor<P: LLMProvider> {
+    provider: P,
+    model: String,
+    engine: String,
+}
+
+#[derive(Deserialize)]
+struct LlmCard {
+    #[serde(default)]
+    kind: Option<CardKind>,
+    #[serde(default = "default_entity")]
+    entity: String,
+    slot: String,
+    value: String,
+    #[serde(default)]
+    polarity: Option<Polarity>,
+    #[serde(default)]
+    confidence: Option<f32>,
+}
+
+fn default_entity() -> String {
+    "user".to_string()
+}
+
+impl<P: LLMProvider> LlmExtractor<P> {
+    #[must_use]
+    pub fn new(provider: P, model: impl Into<String>) -> Self {
+        let model = model.into();
+        Self {
+            provider,
+            engine: format!("llm:{model}"),
+            model,
+        }
+    }
+
+    fn prompt(text: &str) -> String {
+        format!(
+            "Extract durable facts about the user from the message below.\n\
+             Reply with a JSON array only. Each element is an object with:\n\
+             - \"kind\": one of fact, preference, event, profile, relationship, goal\n\
+             - \"slot\": one of {}\n\
+             - \"value\": a short value, in the language of the message\n\
+             - \"polarity\": \"positive\" or \"negative\" for likes and dislikes, otherwise null\n\
+             - \"confidence\": 0.0 to 1.0\n\
+             Reply with [] when the message states no such fact (greetings, questions, requests).\n\n\
+             Message: {text}",
+            slots::ALL.join(", ")
+        )
+    }
+}
+
+#[async_trait]
+impl<P: LLMProvider> CardExtractor for LlmExtractor<P> {
+    fn engine(&self) -> &str {
+        &self.engine
+    }
+
+    fn version(&self) -> &str {
+        ENGINE_VERSION
+    }
+
+    async fn extract(
+        &self,
+        text: &str,
+        scope: &str,
+        source_memory_id: Uuid,
+    ) -> anyhow::Result<Vec<MemoryCard>> {
+        let text = text.strip_prefix("User:").unwrap_or(text).trim();
+        let messages = [ChatMessage {
+            role: Role::User,
+            content: MessageContent::Text(Self::prompt(text)),
+        }];
+        let response = self.provider.chat(&messages, &self.model).await?;
+        Ok(parse_cards(&response.content)
+            .into_iter()
+            .map(|card| {
+                let mut memory_card = MemoryCard::new(
+                    card.kind.unwrap_or(CardKind::Fact),
+                    &card.entity,
+                    &card.slot,
+                    card.value.trim(),
+                );
+                memory_card.user_scope = scope.to_string();
+                memory_card.polarity = card.polarity;
+                memory_card.source_memory_id = Some(source_memory_id);
+                memory_card.engine.clone_from(&self.engine);
+                memory_card.engine_version = ENGINE_VERSION.to_string();
+                memory_card.confidence = Some(
+                    card.confidence
+                        .unwrap_or(DEFAULT_CONFIDENCE)
+                        .clamp(0.0, 1.0),
+                );
+                memory_card
+            })
+            .collect())
+    }
+}
+
+/// Cards from a model reply: the outermost JSON array, known slots only.
+/// Malformed entries are skipped.
+fn parse_cards(reply: &str) -> Vec<LlmCard> {
+    json_array_in_reply(reply)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value::<LlmCard>(entry).ok())
+        .filter(|card| slots::ALL.contains(&card.slot.as_str()) && !card.value.trim().is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cards_from_fenced_reply() {
+        let reply = "```json\n[{\"kind\": \"fact\", \"slot\": \"location\", \"value\": \"东城\"},\
+                     {\"value\": \"no slot\"},\
+                     {\"slot\": \"mood\", \"value\": \"tired\"}]\n```";
+        let cards = parse_cards(reply);
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].value, "东城");
+        assert_eq!(cards[0].entity, "user");
+        assert!(parse_cards("nothing here").is_empty());
+    }
+}
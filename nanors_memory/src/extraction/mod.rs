@@ -0,0 +1,38 @@
+//! Structured memory extraction.
+//!
+//! Extractors turn a stored memory into [`MemoryCard`]s: entity/slot/value
+//! triples such as `user / location / 北京`. Cards are looked up by slot in
+//! `search_enhanced`, so "我住在哪" finds the location fact directly instead
+//! of relying on embedding similarity between question and answer.
+
+mod cards;
+mod engine;
+mod llm;
+mod patterns;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+pub use cards::{CardKind, MemoryCard, Polarity, slots};
+pub use engine::ExtractionEngine;
+pub use llm::LlmExtractor;
+pub use patterns::{CardPattern, default_patterns};
+
+/// Turns memory text into structured cards.
+#[async_trait]
+pub trait CardExtractor: Send + Sync {
+    /// Engine identifier recorded on each card, e.g. `rules`.
+    fn engine(&self) -> &str;
+
+    /// Engine version recorded on each card.
+    fn version(&self) -> &str;
+
+    /// Extract cards from `text`, a memory in `scope` with id
+    /// `source_memory_id`. Returns no cards when nothing is recognised.
+    async fn extract(
+        &self,
+        text: &str,
+        scope: &str,
+        source_memory_id: Uuid,
+    ) -> anyhow::Result<Vec<MemoryCard>>;
+}
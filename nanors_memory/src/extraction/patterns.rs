@@ -0,0 +1,203 @@
+//! Regex patterns for rule-based card extraction (Chinese and English).
+
+use serde::{Deserialize, Serialize};
+
+use super::cards::{CardKind, Polarity, slots};
+
+/// Characters that end a Chinese value.
+const ZH_VALUE: &str = r"[^，。,.!！?？;；、\s]";
+
+/// Characters that end an English value.
+const EN_VALUE: &str = r"[^,.!?;\n]";
+
+/// A pattern producing one card per match of its `value` capture group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardPattern {
+    pub kind: CardKind,
+    #[serde(default = "default_entity")]
+    pub entity: String,
+    pub slot: String,
+    /// Regex with a named `value` group
+    pub pattern: String,
+    #[serde(default)]
+    pub polarity: Option<Polarity>,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+fn default_entity() -> String {
+    "user".to_string()
+}
+
+const fn default_confidence() -> f32 {
+    0.8
+}
+
+impl CardPattern {
+    fn new(kind: CardKind, slot: &str, pattern: String, confidence: f32) -> Self {
+        Self {
+            kind,
+            entity: default_entity(),
+            slot: slot.to_string(),
+            pattern,
+            polarity: None,
+            confidence,
+        }
+    }
+
+    const fn with_polarity(mut self, polarity: Polarity) -> Self {
+        self.polarity = Some(polarity);
+        self
+    }
+}
+
+/// Default first-person patterns for common facts.
+#[must_use]
+pub fn default_patterns() -> Vec<CardPattern> {
+    let mut patterns = chinese_patterns();
+    patterns.extend(english_patterns());
+    patterns
+}
+
+/// "我住在北京", "我是安卓玩机用户", ...
+fn chinese_patterns() -> Vec<CardPattern> {
+    vec![
+        CardPattern::new(
+            CardKind::Profile,
+            slots::NAME,
+            format!(r"(?:我叫|我的名字是|我的名字叫)(?P<value>{ZH_VALUE}{{1,20}})"),
+            0.9,
+        ),
+        CardPattern::new(
+            CardKind::Profile,
+            slots::USER_TYPE,
+            format!(r"我是(?:一[个名位])?(?P<value>{ZH_VALUE}{{1,20}}?)用户"),
+            0.9,
+        ),
+        CardPattern::new(
+            CardKind::Profile,
+            slots::PROFESSION,
+            format!(
+                r"我(?:是|当)(?:一[个名位])?(?P<value>{ZH_VALUE}{{0,10}}?(?:工程师|程序员|开发者|设计师|医生|护士|老师|教师|学生|律师|产品经理|会计|记者|研究员))"
+            ),
+            0.85,
+        ),
+        CardPattern::new(
+            CardKind::Fact,
+            slots::EMPLOYER,
+            format!(r"我在(?P<value>{ZH_VALUE}{{1,30}}?)(?:工作|上班|任职|实习)"),
+            0.85,
+        ),
+        // Also "住在北京" without the subject, and moves: "我搬到了东城"
+        CardPattern::new(
+            CardKind::Fact,
+            slots::LOCATION,
+            format!(r"(?:住在|居住在|定居在|定居|搬到了?|搬去了?)(?P<value>{ZH_VALUE}{{1,30}})"),
+            0.9,
+        ),
+        // Dislikes before likes, so "不喜欢" never reads as a like
+        CardPattern::new(
+            CardKind::Preference,
+            slots::PREFERENCE,
+            format!(r"我(?:很|最|非常|特别|也)?(?:不喜欢|不爱|讨厌)(?P<value>{ZH_VALUE}{{1,20}})"),
+            0.85,
+        )
+        .with_polarity(Polarity::Negative),
+        CardPattern::new(
+            CardKind::Preference,
+            slots::PREFERENCE,
+            format!(r"我(?:很|最|非常|特别|也)?(?:喜欢|爱|热爱)(?P<value>{ZH_VALUE}{{1,20}})"),
+            0.85,
+        )
+        .with_polarity(Polarity::Positive),
+        // Possessions need a count, so "我有点累" is not one
+        CardPattern::new(
+            CardKind::Fact,
+            slots::POSSESSION,
+            format!(
+                r"我(?:有|养了|养着|买了)(?P<value>[一二两三四五六七八九十几\d]+[只个条辆台部本套件]{ZH_VALUE}{{1,20}})"
+            ),
+            0.7,
+        ),
+        CardPattern::new(
+            CardKind::Fact,
+            slots::SKILL,
+            format!(
+                r"我会(?P<value>(?:说|弹|拉|写|做){ZH_VALUE}{{1,10}}|游泳|开车|编程|滑雪|画画)"
+            ),
+            0.75,
+        ),
+    ]
+}
+
+/// "I live in Berlin", "my name is Ada", ...
+fn english_patterns() -> Vec<CardPattern> {
+    vec![
+        CardPattern::new(
+            CardKind::Profile,
+            slots::NAME,
+            format!(r"(?i)\bmy name is (?P<value>{EN_VALUE}{{1,40}})"),
+            0.9,
+        ),
+        CardPattern::new(
+            CardKind::Profile,
+            slots::USER_TYPE,
+            r"(?i)\bi(?:'m| am) an? (?P<value>[a-z0-9 \-]{1,40}?) user\b".to_string(),
+            0.9,
+        ),
+        CardPattern::new(
+            CardKind::Profile,
+            slots::PROFESSION,
+            r"(?i)\bi(?:'m| am) an? (?P<value>[a-z \-]{0,30}?(?:engineer|developer|programmer|designer|doctor|nurse|teacher|student|lawyer|manager|researcher))\b"
+                .to_string(),
+            0.85,
+        ),
+        CardPattern::new(
+            CardKind::Fact,
+            slots::EMPLOYER,
+            format!(r"(?i)\bi work (?:at|for) (?P<value>{EN_VALUE}{{1,40}})"),
+            0.85,
+        ),
+        CardPattern::new(
+            CardKind::Fact,
+            slots::LOCATION,
+            format!(
+                r"(?i)\bi (?:live|am living|moved|have moved) (?:in|to) (?P<value>{EN_VALUE}{{1,40}})"
+            ),
+            0.9,
+        ),
+        CardPattern::new(
+            CardKind::Preference,
+            slots::PREFERENCE,
+            format!(
+                r"(?i)\bi (?:really )?(?:hate|dislike|don't like|do not like) (?P<value>{EN_VALUE}{{1,40}})"
+            ),
+            0.85,
+        )
+        .with_polarity(Polarity::Negative),
+        CardPattern::new(
+            CardKind::Preference,
+            slots::PREFERENCE,
+            format!(
+                r"(?i)\bi (?:really )?(?:like|love|enjoy|prefer) (?P<value>{EN_VALUE}{{1,40}})"
+            ),
+            0.85,
+        )
+        .with_polarity(Polarity::Positive),
+        CardPattern::new(
+            CardKind::Fact,
+            slots::POSSESSION,
+            format!(
+                r"(?i)\bi (?:have|own|bought) (?P<value>(?:a|an|one|two|three|\d+) {EN_VALUE}{{1,30}})"
+            ),
+            0.6,
+        ),
+        CardPattern::new(
+            CardKind::Fact,
+            slots::SKILL,
+            r"(?i)\bi can (?P<value>(?:speak|play|cook|swim|drive|code|program)(?: [a-z]+)?)"
+                .to_string(),
+            0.75,
+        ),
+    ]
+}
@@ -0,0 +1,440 @@
+//! Hierarchical navigable small world graph for approximate nearest
+//! neighbour search by cosine distance.
+//!
+//! A small, dependency-free implementation of Malkov & Yashunin's HNSW,
+//! sized for a personal memory store (thousands to low millions of
+//! vectors). Vectors are normalised on insert, so distance is `1 - dot`.
+//! Removal marks a node deleted; it keeps routing searches but is never
+//! returned, and [`Hnsw::needs_compaction`] tells the owner when to rebuild.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// Neighbours kept per node on upper layers (twice this on layer 0).
+const M: usize = 16;
+/// Candidate list size while inserting.
+const EF_CONSTRUCTION: usize = 100;
+/// Minimum candidate list size while searching.
+const EF_SEARCH: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    id: Uuid,
+    /// Caller-supplied version (e.g. `updated_at` in milliseconds)
+    version: i64,
+    vector: Vec<f32>,
+    /// Neighbour slots per layer, layer 0 first
+    links: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+/// A candidate with its distance to the query, ordered by distance.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    dist: f32,
+    slot: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then(self.slot.cmp(&other.slot))
+    }
+}
+
+/// An HNSW graph keyed by memory id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Hnsw {
+    nodes: Vec<Node>,
+    slots: HashMap<Uuid, usize>,
+    entry: Option<usize>,
+    deleted: usize,
+}
+
+impl Hnsw {
+    /// Number of live (not deleted) vectors.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Version recorded for `id`, if indexed.
+    #[must_use]
+    pub fn version(&self, id: &Uuid) -> Option<i64> {
+        self.slots.get(id).map(|&slot| self.nodes[slot].version)
+    }
+
+    /// Ids and versions of all live vectors.
+    pub fn entries(&self) -> impl Iterator<Item = (Uuid, i64)> + '_ {
+        self.slots
+            .values()
+            .map(|&slot| (self.nodes[slot].id, self.nodes[slot].version))
+    }
+
+    /// Whether deleted nodes make up more than half the graph.
+    #[must_use]
+    pub fn needs_compaction(&self) -> bool {
+        self.deleted > 0 && self.deleted * 2 > self.nodes.len()
+    }
+
+    /// Rebuild the graph from its live vectors, dropping deleted nodes.
+    #[must_use]
+    pub fn compacted(&self) -> Self {
+        let mut rebuilt = Self::default();
+        for node in self.nodes.iter().filter(|n| !n.deleted) {
+            rebuilt.insert(node.id, node.version, &node.vector);
+        }
+        rebuilt
+    }
+
+    /// Insert or replace the vector for `id`.
+    pub fn insert(&mut self, id: Uuid, version: i64, vector: &[f32]) {
+        self.remove(&id);
+        let Some(vector) = normalize(vector) else {
+            return;
+        };
+
+        let level = level_for(&id);
+        let slot = self.nodes.len();
+        self.nodes.push(Node {
+            id,
+            version,
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.slots.insert(id, slot);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(slot);
+            return;
+        };
+        let query = self.nodes[slot].vector.clone();
+        let top = self.nodes[entry].links.len() - 1;
+
+        let mut nearest = Candidate {
+            dist: self.distance(&query, entry),
+            slot: entry,
+        };
+        for layer in (level + 1..=top).rev() {
+            nearest = self.greedy(&query, nearest, layer);
+        }
+
+        let mut entry_points = vec![nearest];
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entry_points, EF_CONSTRUCTION, layer);
+            let neighbours: Vec<usize> = found
+                .iter()
+                .filter(|c| c.slot != slot && self.nodes[c.slot].vector.len() == query.len())
+                .take(M)
+                .map(|c| c.slot)
+                .collect();
+            for &neighbour in &neighbours {
+                self.link(neighbour, slot, layer);
+            }
+            self.nodes[slot].links[layer] = neighbours;
+            entry_points = found;
+        }
+
+        if level > top {
+            self.entry = Some(slot);
+        }
+    }
+
+    /// Mark `id` deleted. Returns whether it was indexed.
+    pub fn remove(&mut self, id: &Uuid) -> bool {
+        let Some(slot) = self.slots.remove(id) else {
+            return false;
+        };
+        self.nodes[slot].deleted = true;
+        self.deleted += 1;
+        true
+    }
+
+    /// Up to `k` live ids nearest to `query`, nearest first, with their
+    /// cosine similarity.
+    #[must_use]
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(Uuid, f32)> {
+        let (Some(entry), Some(query)) = (self.entry, normalize(query)) else {
+            return Vec::new();
+        };
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut nearest = Candidate {
+            dist: self.distance(&query, entry),
+            slot: entry,
+        };
+        for layer in (1..self.nodes[entry].links.len()).rev() {
+            nearest = self.greedy(&query, nearest, layer);
+        }
+
+        // Deleted nodes still route but take up candidate slots
+        let ef = (k + self.deleted.min(k)).max(EF_SEARCH);
+        self.search_layer(&query, &[nearest], ef, 0)
+            .into_iter()
+            .filter(|c| !self.nodes[c.slot].deleted)
+            .take(k)
+            .map(|c| (self.nodes[c.slot].id, 1.0 - c.dist))
+            .collect()
+    }
+
+    fn distance(&self, query: &[f32], slot: usize) -> f32 {
+        let vector = &self.nodes[slot].vector;
+        if vector.len() != query.len() {
+            return f32::MAX;
+        }
+        1.0 - query.iter().zip(vector).map(|(a, b)| a * b).sum::<f32>()
+    }
+
+    /// Walk to the closest node on `layer`, one hop at a time.
+    fn greedy(&self, query: &[f32], mut nearest: Candidate, layer: usize) -> Candidate {
+        loop {
+            let mut improved = false;
+            for &neighbour in self.nodes[nearest.slot]
+                .links
+                .get(layer)
+                .into_iter()
+                .flatten()
+            {
+                let dist = self.distance(query, neighbour);
+                if dist < nearest.dist {
+                    nearest = Candidate {
+                        dist,
+                        slot: neighbour,
+                    };
+                    improved = true;
+                }
+            }
+            if !improved {
+                return nearest;
+            }
+        }
+    }
+
+    /// Best-first search on one layer, returning up to `ef` candidates
+    /// sorted nearest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry_points: &[Candidate],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entry_points.iter().map(|c| c.slot).collect();
+        let mut frontier: BinaryHeap<Reverse<Candidate>> =
+            entry_points.iter().copied().map(Reverse).collect();
+        let mut best: BinaryHeap<Candidate> = entry_points.iter().copied().collect();
+        while best.len() > ef {
+            best.pop();
+        }
+
+        while let Some(Reverse(current)) = frontier.pop() {
+            if best.len() >= ef && best.peek().is_some_and(|worst| current.dist > worst.dist) {
+                break;
+            }
+            for &neighbour in self.nodes[current.slot]
+                .links
+                .get(layer)
+                .into_iter()
+                .flatten()
+            {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let candidate = Candidate {
+                    dist: self.distance(query, neighbour),
+                    slot: neighbour,
+                };
+                if best.len() < ef || best.peek().is_some_and(|worst| candidate < *worst) {
+                    frontier.push(Reverse(candidate));
+                    best.push(candidate);
+                    if best.len() > ef {
+                        best.pop();
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+    }
+
+    /// Add `to` to `from`'s neighbours on `layer`, keeping only the closest.
+    fn link(&mut self, from: usize, to: usize, layer: usize) {
+        let max = if layer == 0 { 2 * M } else { M };
+        let Some(links) = self.nodes[from].links.get(layer) else {
+            return;
+        };
+        let mut links = links.clone();
+        links.push(to);
+        if links.len() > max {
+            let base = self.nodes[from].vector.clone();
+            links.sort_by(|&a, &b| self.distance(&base, a).total_cmp(&self.distance(&base, b)));
+            links.truncate(max);
+        }
+        self.nodes[from].links[layer] = links;
+    }
+}
+
+/// Unit-length copy of `vector`, or `None` for an empty or zero vector.
+fn normalize(vector: &[f32]) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    (norm > f32::EPSILON).then(|| vector.iter().map(|x| x / norm).collect())
+}
+
+/// Layer for a new node, drawn from the usual exponential distribution.
+///
+/// The draw is derived from the id, so rebuilding a graph from the same
+/// memories gives the same layout.
+#[expect(clippy::cast_sign_loss, reason = "the level is clamped to 0..=16")]
+fn level_for(id: &Uuid) -> usize {
+    let bits = id.as_u64_pair().0 ^ id.as_u64_pair().1.rotate_left(17);
+    // Uniform in (0, 1]
+    let uniform = ((bits >> 11) as f64 + 1.0) / (1_u64 << 53) as f64;
+    let level = (-uniform.ln() / (M as f64).ln()).floor();
+    level.clamp(0.0, 16.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random vectors (64-bit LCG).
+    fn vectors(count: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6_364_136_223_846_793_005)
+                            .wrapping_add(1_442_695_040_888_963_407);
+                        ((state >> 33) as f32 / (1_u64 << 31) as f32) - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn brute_force(data: &[(Uuid, Vec<f32>)], query: &[f32], k: usize) -> Vec<Uuid> {
+        let query = normalize(query).unwrap_or_default();
+        let mut scored: Vec<(f32, Uuid)> = data
+            .iter()
+            .map(|(id, v)| {
+                let v = normalize(v).unwrap_or_default();
+                (query.iter().zip(&v).map(|(a, b)| a * b).sum(), *id)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(k).map(|(_, id)| id).collect()
+    }
+
+    #[test]
+    fn test_search_recall_against_brute_force() {
+        let data: Vec<(Uuid, Vec<f32>)> = vectors(1000, 32, 7)
+            .into_iter()
+            .map(|v| (Uuid::now_v7(), v))
+            .collect();
+        let mut index = Hnsw::default();
+        for (id, v) in &data {
+            index.insert(*id, 0, v);
+        }
+        assert_eq!(index.len(), 1000);
+
+        let mut hits = 0;
+        for query in vectors(20, 32, 99) {
+            let expected: HashSet<Uuid> = brute_force(&data, &query, 10).into_iter().collect();
+            let found = index.search(&query, 10);
+            assert_eq!(found.len(), 10);
+            hits += found.iter().filter(|(id, _)| expected.contains(id)).count();
+        }
+        // Recall@10 over 20 queries
+        assert!(hits >= 180, "recall too low: {hits}/200");
+    }
+
+    #[test]
+    fn test_exact_match_is_nearest() {
+        let mut index = Hnsw::default();
+        let data = vectors(200, 16, 3);
+        let ids: Vec<Uuid> = data
+            .iter()
+            .map(|v| {
+                let id = Uuid::now_v7();
+                index.insert(id, 1, v);
+                id
+            })
+            .collect();
+        let found = index.search(&data[42], 1);
+        assert_eq!(found[0].0, ids[42]);
+        assert!((found[0].1 - 1.0).abs() < 1e-5);
+        assert_eq!(index.version(&ids[42]), Some(1));
+    }
+
+    #[test]
+    fn test_remove_and_replace() {
+        let mut index = Hnsw::default();
+        let data = vectors(100, 8, 11);
+        let ids: Vec<Uuid> = data
+            .iter()
+            .map(|v| {
+                let id = Uuid::now_v7();
+                index.insert(id, 0, v);
+                id
+            })
+            .collect();
+
+        assert!(index.remove(&ids[5]));
+        assert!(!index.remove(&ids[5]));
+        assert!(
+            index
+                .search(&data[5], 100)
+                .iter()
+                .all(|(id, _)| *id != ids[5])
+        );
+
+        // Replacing moves the id to its new vector
+        index.insert(ids[6], 2, &data[7]);
+        assert_eq!(index.version(&ids[6]), Some(2));
+        assert_eq!(index.len(), 99);
+
+        for id in &ids[..60] {
+            index.remove(id);
+        }
+        assert!(index.needs_compaction());
+        let compacted = index.compacted();
+        assert_eq!(compacted.len(), 40);
+        assert!(!compacted.needs_compaction());
+        assert_eq!(compacted.search(&data[80], 1)[0].0, ids[80]);
+    }
+
+    #[test]
+    fn test_empty_and_zero_vectors() {
+        let mut index = Hnsw::default();
+        assert!(index.search(&[1.0, 0.0], 5).is_empty());
+        index.insert(Uuid::now_v7(), 0, &[0.0, 0.0]);
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,258 @@
+//! In-process BM25 index over memory summaries.
+//!
+//! One inverted index per memory scope, built from the current (not
+//! superseded) memories at startup and kept in step on insert, update and
+//! delete like the vector index. Before each search `MemoryManager`
+//! refreshes both from the rows changed since, so writes by other processes
+//! sharing the database are found too. Works the same on every backend.
+//!
+//! Scores are normalized by the score a memory identical to the query would
+//! get, so they read as 0.0-1.0 similarities comparable across queries.
+//!
+//! Tokens are lowercased ASCII words and, for CJK text, single characters
+//! plus adjacent character pairs, so "我住在东城" matches "东城" without a
+//! segmenter.
+
+use nanors_entities::memory_items;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::info;
+use uuid::Uuid;
+
+/// Term frequency saturation.
+const K1: f64 = 1.2;
+
+/// Document length normalization.
+const B: f64 = 0.75;
+
+/// Split `text` into index terms.
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut previous_cjk: Option<char> = None;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            tokens.push(c.to_string());
+            if let Some(previous) = previous_cjk {
+                tokens.push([previous, c].iter().collect());
+            }
+            previous_cjk = Some(c);
+        } else {
+            previous_cjk = None;
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else {
+                flush_word(&mut word, &mut tokens);
+            }
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    tokens
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    if !word.is_empty() {
+        tokens.push(std::mem::take(word));
+    }
+}
+
+/// Han, kana and hangul: scripts written without spaces between words.
+const fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{ac00}'..='\u{d7af}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2ebef}')
+}
+
+/// BM25 statistics of one scope.
+#[derive(Default)]
+struct Bm25 {
+    /// Term frequencies and length of each document
+    docs: HashMap<Uuid, (HashMap<String, u32>, usize)>,
+    /// Documents containing each term, with the term's frequency
+    postings: HashMap<String, HashMap<Uuid, u32>>,
+    total_len: usize,
+}
+
+impl Bm25 {
+    fn insert(&mut self, id: Uuid, text: &str) {
+        self.remove(&id);
+        let tokens = tokenize(text);
+        let mut terms: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *terms.entry(token.clone()).or_default() += 1;
+        }
+        for (term, tf) in &terms {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(id, *tf);
+        }
+        self.total_len += tokens.len();
+        self.docs.insert(id, (terms, tokens.len()));
+    }
+
+    fn remove(&mut self, id: &Uuid) {
+        let Some((terms, len)) = self.docs.remove(id) else {
+            return;
+        };
+        for term in terms.keys() {
+            if let Some(posting) = self.postings.get_mut(term) {
+                posting.remove(id);
+                if posting.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_len -= len;
+    }
+
+    /// BM25 term weight of a term occurring `tf` times in a document of
+    /// `len` tokens.
+    fn term_weight(idf: f64, tf: f64, len: f64, average_len: f64) -> f64 {
+        let norm = K1 * B.mul_add(len / average_len, 1.0 - B);
+        idf * tf * (K1 + 1.0) / (tf + norm)
+    }
+
+    /// BM25 score of each matching document over the score a document
+    /// identical to `query` would get, capped at 1.0.
+    fn scores(&self, query: &str) -> HashMap<Uuid, f64> {
+        let mut scores: HashMap<Uuid, f64> = HashMap::new();
+        let tokens = tokenize(query);
+        if self.docs.is_empty() || tokens.is_empty() {
+            return scores;
+        }
+        let n = self.docs.len() as f64;
+        let average_len = (self.total_len as f64 / n).max(1.0);
+        let query_len = tokens.len() as f64;
+        let mut query_terms: HashMap<String, u32> = HashMap::new();
+        for token in tokens {
+            *query_terms.entry(token).or_default() += 1;
+        }
+
+        let mut perfect = 0.0;
+        for (term, query_tf) in &query_terms {
+            let posting = self.postings.get(term);
+            let df = posting.map_or(0, HashMap::len) as f64;
+            let idf = ((n - df + 0.5) / (df + 0.5)).ln_1p();
+            perfect += Self::term_weight(idf, f64::from(*query_tf), query_len, average_len);
+            for (id, tf) in posting.into_iter().flatten() {
+                let len = self.docs.get(id).map_or(0, |(_, len)| *len) as f64;
+                *scores.entry(*id).or_default() +=
+                    Self::term_weight(idf, f64::from(*tf), len, average_len);
+            }
+        }
+        for score in scores.values_mut() {
+            *score = (*score / perfect).min(1.0);
+        }
+        scores
+    }
+}
+
+/// Per-scope BM25 indexes.
+#[derive(Default)]
+pub struct LexicalIndex {
+    scopes: RwLock<HashMap<String, Bm25>>,
+}
+
+impl LexicalIndex {
+    /// Index every current memory in the database, replacing what was
+    /// indexed before.
+    pub async fn sync(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let rows: Vec<(Uuid, String, String)> = memory_items::Entity::find()
+            .select_only()
+            .column(memory_items::Column::Id)
+            .column(memory_items::Column::UserScope)
+            .column(memory_items::Column::Summary)
+            .filter(memory_items::Column::SupersededAt.is_null())
+            .into_tuple()
+            .all(db)
+            .await?;
+        let mut scopes: HashMap<String, Bm25> = HashMap::new();
+        for (id, scope, summary) in &rows {
+            scopes
+                .entry(scope.clone())
+                .or_default()
+                .insert(*id, summary);
+        }
+        if let Ok(mut current) = self.scopes.write() {
+            *current = scopes;
+        }
+        info!("Lexical index ready: {} memories", rows.len());
+        Ok(())
+    }
+
+    /// Index (or re-index) a memory's summary.
+    pub fn upsert(&self, scope: &str, id: Uuid, summary: &str) {
+        if let Ok(mut scopes) = self.scopes.write() {
+            scopes
+                .entry(scope.to_string())
+                .or_default()
+                .insert(id, summary);
+        }
+    }
+
+    /// Drop a memory from the index.
+    pub fn remove(&self, scope: &str, id: &Uuid) {
+        if let Ok(mut scopes) = self.scopes.write() {
+            if let Some(index) = scopes.get_mut(scope) {
+                index.remove(id);
+            }
+        }
+    }
+
+    /// Lexical similarity (0.0-1.0) of every memory in `scope` sharing a
+    /// term with `query`: its BM25 score relative to a memory identical to
+    /// the query.
+    #[must_use]
+    pub fn scores(&self, scope: &str, query: &str) -> HashMap<Uuid, f64> {
+        self.scopes
+            .read()
+            .ok()
+            .and_then(|scopes| scopes.get(scope).map(|index| index.scores(query)))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_mixed_text() {
+        assert_eq!(
+            tokenize("User: 我住东城, OnePlus 13"),
+            [
+                "user", "我", "住", "我住", "东", "住东", "城", "东城", "oneplus", "13"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bm25_prefers_rare_terms_and_short_documents() {
+        let index = LexicalIndex::default();
+        let (home, work, long) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+        index.upsert("global", home, "User: 我住在东城");
+        index.upsert("global", work, "User: 我在西城上班");
+        index.upsert(
+            "global",
+            long,
+            "User: 周末去东城的公园散步，然后在附近吃了午饭，下午又去看了电影",
+        );
+
+        let scores = index.scores("global", "东城");
+        assert!(scores.values().all(|score| (0.0..=1.0).contains(score)));
+        assert!(scores[&home] > scores[&long]);
+        assert!(scores[&long] > scores[&work]);
+        assert!(index.scores("other", "东城").is_empty());
+
+        index.remove("global", &home);
+        assert!(!index.scores("global", "东城").contains_key(&home));
+    }
+}
@@ -19,15 +19,25 @@
     clippy::cast_possible_truncation
 )]
 
+mod card_store;
+mod chat_session;
 mod convert;
 mod dedup;
+pub mod enrichment;
+pub mod eval;
+pub mod extraction;
+mod hnsw;
+mod lexical_index;
 mod manager;
+mod pgvector;
 pub mod query;
 pub mod rerank;
 mod scoring;
 mod session;
+mod vector_index;
+pub mod versioning;
 
 // Re-export SessionStorage so MemoryManager can be used as session storage
 pub use nanors_core::SessionStorage;
 
-pub use manager::MemoryManager;
+pub use manager::{DynMemoryManager, MemoryManager};
@@ -1,21 +1,31 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use nanors_core::MemoryItemRepo;
-use nanors_core::memory::{MemoryItem, SalienceScore};
+use nanors_core::memory::{MemoryItem, MemoryScope, SalienceScore};
+use nanors_core::retrieval::ScoringConfig;
 use nanors_entities::memory_items;
 use nanors_entities::sessions;
 use rayon::prelude::*;
+use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Database, DatabaseConnection, EntityTrait, ModelTrait,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, ModelTrait,
     QueryFilter, Set,
 };
-use tracing::info;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::convert;
 use crate::dedup;
+use crate::extraction::{CardExtractor, ExtractionEngine};
+use crate::lexical_index::LexicalIndex;
+use crate::pgvector;
+use crate::query::detector::QuestionTypeDetector;
+use crate::query::language::LanguagePack;
 use crate::rerank::{Reranker, RuleBasedReranker};
 use crate::scoring;
+use crate::vector_index::{self, VectorIndex};
 
 /// Core memory management for AI agent conversations.
 ///
@@ -29,6 +39,98 @@ pub struct MemoryManager<R: Reranker = RuleBasedReranker> {
     pub(crate) db: DatabaseConnection,
     /// Reranker for result relevance tuning
     pub(crate) reranker: R,
+    /// How search candidates are selected
+    pub(crate) vector_search: VectorSearch,
+    /// BM25 index fused with vector search
+    pub(crate) lexical: LexicalIndex,
+    /// Changes from this time on are refreshed into the indexes before
+    /// the next search
+    pub(crate) refreshed_at: Mutex<DateTime<Utc>>,
+    /// Thresholds and weights of search and storage
+    pub(crate) scoring: ScoringConfig,
+    /// Words marking a memory as a question
+    pub(crate) question_words: Vec<String>,
+    /// Card extractors run on every stored memory
+    pub(crate) extractors: Vec<Arc<dyn CardExtractor>>,
+    /// Picks the card slots a query asks about
+    pub(crate) question_detector: QuestionTypeDetector,
+}
+
+/// A `MemoryManager` whose reranker is chosen at runtime, e.g. from config.
+pub type DynMemoryManager = MemoryManager<Box<dyn Reranker>>;
+
+/// How `search_by_embedding` selects the candidates it reranks.
+pub enum VectorSearch {
+    /// Nearest neighbours from the pgvector index in Postgres
+    PgVector(pgvector::SearchOptions),
+    /// Nearest neighbours from the in-process HNSW index
+    Index(VectorIndex),
+    /// Score every memory in the scope
+    Scan,
+}
+
+impl VectorSearch {
+    /// pgvector when the database has it, the in-process index otherwise.
+    async fn connect(db: &DatabaseConnection, database_url: &str) -> Self {
+        if pgvector::is_available(db).await {
+            return Self::PgVector(pgvector::SearchOptions::detect(db).await);
+        }
+        let index = VectorIndex::open(vector_index::sidecar_path(database_url));
+        match index.sync(db).await {
+            Ok(()) => Self::Index(index),
+            Err(e) => {
+                warn!("Vector index unavailable, scanning memories instead: {e}");
+                Self::Scan
+            }
+        }
+    }
+}
+
+/// BM25 index of the stored memories; empty (vector search only) when
+/// indexing fails.
+async fn lexical_index(db: &DatabaseConnection) -> LexicalIndex {
+    let index = LexicalIndex::default();
+    if let Err(e) = index.sync(db).await {
+        warn!("Lexical index unavailable, using vector search only: {e}");
+    }
+    index
+}
+
+/// Bring the schema up to date before anything touches it.
+async fn run_migrations(db: &DatabaseConnection) -> anyhow::Result<()> {
+    let applied = nanors_migration::migrate(db).await?;
+    if !applied.is_empty() {
+        info!("Applied {} database migrations", applied.len());
+    }
+    Ok(())
+}
+
+/// Whether a memory holds an assistant reply rather than something the
+/// user said.
+fn is_assistant_memory(summary: &str) -> bool {
+    summary.starts_with("Assistant:")
+}
+
+/// Writes stamped this long before a refresh may still be committing, so
+/// the next refresh reads them again.
+const REFRESH_OVERLAP_SECS: i64 = 10;
+
+/// Candidates fetched from a vector index per requested result; the hybrid
+/// keyword/recency rerank then works on this set only.
+const CANDIDATES_PER_RESULT: usize = 5;
+
+/// Lower bound on the candidate set, so small `top_k` still gets a useful
+/// pool for the rerank.
+const MIN_CANDIDATES: usize = 50;
+
+/// Number of index candidates to fetch for `top_k` results.
+const fn candidate_count(top_k: usize) -> usize {
+    let wanted = top_k.saturating_mul(CANDIDATES_PER_RESULT);
+    if wanted > MIN_CANDIDATES {
+        wanted
+    } else {
+        MIN_CANDIDATES
+    }
 }
 
 impl<R: Reranker> MemoryManager<R> {
@@ -37,13 +139,7 @@ impl<R: Reranker> MemoryManager<R> {
     /// # Arguments
     /// * `database_url` - Database connection string
     pub async fn new(database_url: &str) -> anyhow::Result<MemoryManager<RuleBasedReranker>> {
-        info!("Connecting to database for MemoryManager");
-        let db = Database::connect(database_url).await?;
-        info!("MemoryManager initialized");
-        Ok(MemoryManager {
-            db,
-            reranker: RuleBasedReranker::new(),
-        })
+        Self::with_reranker(database_url, RuleBasedReranker::new()).await
     }
 
     /// Create a new `MemoryManager` with a custom reranker.
@@ -71,9 +167,41 @@ impl<R: Reranker> MemoryManager<R> {
         reranker: CR,
     ) -> anyhow::Result<MemoryManager<CR>> {
         info!("Connecting to database for MemoryManager");
-        let db = Database::connect(database_url).await?;
-        info!("MemoryManager initialized with custom reranker");
-        Ok(MemoryManager { db, reranker })
+        let db = nanors_migration::connect(database_url).await?;
+        run_migrations(&db).await?;
+        // Before syncing, so writes made meanwhile are refreshed later
+        let refreshed_at = Utc::now();
+        let vector_search = VectorSearch::connect(&db, database_url).await;
+        let lexical = lexical_index(&db).await;
+        info!("MemoryManager initialized");
+        Ok(MemoryManager {
+            db,
+            reranker,
+            vector_search,
+            lexical,
+            refreshed_at: Mutex::new(refreshed_at),
+            scoring: ScoringConfig::default(),
+            question_words: LanguagePack::default().question_words,
+            extractors: vec![Arc::new(ExtractionEngine::with_defaults())],
+            question_detector: QuestionTypeDetector::with_defaults(),
+        })
+    }
+
+    /// Score and version memories with `scoring` instead of the default
+    /// thresholds and weights.
+    #[must_use]
+    pub const fn with_scoring(mut self, scoring: ScoringConfig) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Detect question types and question-like memories with `language`
+    /// instead of the built-in packs.
+    #[must_use]
+    pub fn with_language(mut self, language: &LanguagePack) -> Self {
+        self.question_detector = QuestionTypeDetector::new(language.detector_config());
+        self.question_words.clone_from(&language.question_words);
+        self
     }
 
     /// Clear a session by ID.
@@ -89,14 +217,18 @@ impl<R: Reranker> MemoryManager<R> {
     /// If an item with the same content hash already exists in the scope,
     /// its reinforcement count is incremented instead of creating a
     /// duplicate.
-    pub async fn upsert_memory(&self, item: &MemoryItem) -> anyhow::Result<Uuid> {
+    pub async fn upsert_memory(
+        &self,
+        scope: &MemoryScope,
+        item: &MemoryItem,
+    ) -> anyhow::Result<Uuid> {
         let hash = dedup::content_hash(&item.memory_type.to_string(), &item.summary);
 
-        if let Some(existing) = MemoryItemRepo::find_by_content_hash(self, &hash).await? {
+        if let Some(existing) = MemoryItemRepo::find_by_content_hash(self, scope, &hash).await? {
             let mut updated = existing.clone();
             updated.reinforcement_count += 1;
             updated.updated_at = Utc::now();
-            MemoryItemRepo::update(self, &updated).await?;
+            MemoryItemRepo::update(self, scope, &updated).await?;
             info!(
                 "Reinforced existing memory: {} (count={})",
                 updated.id, updated.reinforcement_count
@@ -105,8 +237,8 @@ impl<R: Reranker> MemoryManager<R> {
         } else {
             let mut new_item = item.clone();
             new_item.content_hash = hash;
-            MemoryItemRepo::insert(self, &new_item).await?;
-            info!("Inserted new memory: {}", new_item.id);
+            MemoryItemRepo::insert(self, scope, &new_item).await?;
+            info!("Inserted new memory: {} ({scope})", new_item.id);
             Ok(new_item.id)
         }
     }
@@ -116,10 +248,12 @@ impl<R: Reranker> MemoryManager<R> {
     /// This method implements semantic memory versioning:
     /// 1. First checks for exact duplicate via `content_hash`
     /// 2. If no exact match, searches for semantically similar memories
-    /// 3. If similarity > threshold, creates a new version of that memory
+    /// 3. If similarity > threshold, appends a new version superseding that
+    ///    memory (see [`crate::versioning`])
     /// 4. Otherwise inserts as a new memory
     ///
     /// # Arguments
+    /// * `scope` - Scope the item belongs to; only its memories are compared
     /// * `item` - The memory item to insert or use for update
     /// * `similarity_threshold` - Minimum similarity (0.0-1.0) to consider
     ///   memories as semantically equivalent
@@ -129,16 +263,17 @@ impl<R: Reranker> MemoryManager<R> {
     #[tracing::instrument(skip(self, item))]
     pub async fn semantic_upsert_memory(
         &self,
+        scope: &MemoryScope,
         item: &MemoryItem,
         similarity_threshold: f64,
     ) -> anyhow::Result<Uuid> {
         // Fast path: check for exact duplicate via content_hash
         let hash = dedup::content_hash(&item.memory_type.to_string(), &item.summary);
-        if let Some(existing) = MemoryItemRepo::find_by_content_hash(self, &hash).await? {
+        if let Some(existing) = MemoryItemRepo::find_by_content_hash(self, scope, &hash).await? {
             let mut updated = existing.clone();
             updated.reinforcement_count += 1;
             updated.updated_at = Utc::now();
-            MemoryItemRepo::update(self, &updated).await?;
+            MemoryItemRepo::update(self, scope, &updated).await?;
             info!(
                 "Reinforced exact duplicate memory: {} (count={})",
                 updated.id, updated.reinforcement_count
@@ -150,18 +285,22 @@ impl<R: Reranker> MemoryManager<R> {
         if let Some(ref embedding) = item.embedding {
             // Search for semantically similar memories (fetch more than needed)
             // Use item.summary as query text for hybrid similarity matching
-            let similar_memories =
-                MemoryItemRepo::search_by_embedding(self, embedding, &item.summary, 20).await?;
-
-            // Determine if this is a user memory (starts with "User:")
-            let is_user_memory = item.summary.starts_with("User:");
+            // No rerank: only raw similarity matters here, and a model reranker
+            // would cost a call per stored memory
+            let (candidates, lexical) = self
+                .hybrid_candidates(scope, embedding, &item.summary, 20)
+                .await?;
+            let similar_memories = self.order(
+                self.score(candidates, embedding, &item.summary, &lexical),
+                20,
+            );
 
             // Find the most similar memory above threshold
             for score in &similar_memories {
-                // Only compare memories of the same category (user vs assistant)
-                // User memories start with "User:", assistant memories start with "Assistant:"
-                let is_score_user_memory = score.item.summary.starts_with("User:");
-                if is_user_memory != is_score_user_memory {
+                // Assistant replies never update facts about the user, nor
+                // the other way round. Facts extracted by the model carry no
+                // prefix and compare with raw "User: ..." messages.
+                if is_assistant_memory(&item.summary) != is_assistant_memory(&score.item.summary) {
                     continue;
                 }
 
@@ -171,15 +310,16 @@ impl<R: Reranker> MemoryManager<R> {
                     .as_ref()
                     .map_or(0.0, |emb| scoring::cosine_similarity(embedding, emb));
 
-                // For near-identical content (similarity > 0.97), just reinforce the existing memory
-                // For semantically similar but not identical content (threshold < similarity <= 0.97),
-                // create a new version (useful for fact updates like address changes)
-                if similarity > 0.97 {
+                // For near-identical content (similarity > reinforce_threshold), just reinforce
+                // the existing memory. For semantically similar but not identical content
+                // (threshold < similarity <= reinforce_threshold), create a new version
+                // (useful for fact updates like address changes)
+                if similarity > self.scoring.reinforce_threshold {
                     // Near-identical content - reinforce existing memory instead of creating duplicate
                     let mut updated = score.item.clone();
                     updated.reinforcement_count += 1;
                     updated.updated_at = Utc::now();
-                    MemoryItemRepo::update(self, &updated).await?;
+                    MemoryItemRepo::update(self, scope, &updated).await?;
                     info!(
                         "Reinforced near-duplicate memory: {} (similarity={:.3}, count={})",
                         updated.id, similarity, updated.reinforcement_count
@@ -188,21 +328,13 @@ impl<R: Reranker> MemoryManager<R> {
                 }
 
                 if similarity > similarity_threshold {
-                    // Found a semantically similar memory - create a new version
-                    let mut updated = score.item.clone();
-                    updated.summary = item.summary.clone();
-                    updated.embedding = item.embedding.clone();
-                    updated.happened_at = item.happened_at;
-                    updated.updated_at = Utc::now();
-                    updated.extra = item.extra.clone();
-                    // Note: we keep the original content_hash for version tracking
-
-                    MemoryItemRepo::update(self, &updated).await?;
+                    // Semantically similar: append a new version, keeping the old one
+                    let id = self.supersede(scope, &score.item, item).await?;
                     info!(
                         "Created new version {} of memory {} (similarity={:.3})",
-                        updated.id, score.item.id, similarity
+                        id, score.item.id, similarity
                     );
-                    return Ok(updated.id);
+                    return Ok(id);
                 }
             }
         }
@@ -210,113 +342,237 @@ impl<R: Reranker> MemoryManager<R> {
         // No similar memory found - insert as new
         let mut new_item = item.clone();
         new_item.content_hash = hash;
-        MemoryItemRepo::insert(self, &new_item).await?;
-        info!("Inserted new memory: {}", new_item.id);
+        MemoryItemRepo::insert(self, scope, &new_item).await?;
+        info!("Inserted new memory: {} ({scope})", new_item.id);
         Ok(new_item.id)
     }
-}
 
-#[async_trait]
-impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
-    async fn insert(&self, item: &MemoryItem) -> anyhow::Result<()> {
-        let embedding_json = item
-            .embedding
-            .as_ref()
-            .map(|v| convert::embedding_to_json(v.as_slice()));
-        let model = memory_items::ActiveModel {
-            id: Set(item.id),
-            memory_type: Set(item.memory_type.to_string()),
-            summary: Set(item.summary.clone()),
-            embedding: Set(embedding_json),
-            happened_at: Set(item.happened_at.into()),
-            extra: Set(item.extra.clone()),
-            content_hash: Set(item.content_hash.clone()),
-            reinforcement_count: Set(item.reinforcement_count),
-            created_at: Set(item.created_at.into()),
-            updated_at: Set(item.updated_at.into()),
+    /// Candidate items for a search: the nearest neighbours from a vector
+    /// index, or the whole scope when there is none (or pgvector cannot
+    /// compare the query embedding).
+    ///
+    /// Items without an indexed embedding are not candidates; run
+    /// `backfill_embeddings` to index them.
+    async fn candidates(
+        &self,
+        scope: &MemoryScope,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> anyhow::Result<Vec<MemoryItem>> {
+        let limit = candidate_count(top_k);
+        let ids = match &self.vector_search {
+            VectorSearch::PgVector(options) => {
+                match pgvector::nearest(&self.db, *options, scope, query_embedding, limit).await? {
+                    Some(ids) => ids,
+                    None => return MemoryItemRepo::list(self, scope).await,
+                }
+            }
+            VectorSearch::Index(index) => index.search(&scope.to_string(), query_embedding, limit),
+            VectorSearch::Scan => return MemoryItemRepo::list(self, scope).await,
         };
-        model.insert(&self.db).await?;
-        Ok(())
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let results = memory_items::Entity::find()
+            .filter(memory_items::Column::Id.is_in(ids))
+            .filter(memory_items::Column::SupersededAt.is_null())
+            .all(&self.db)
+            .await?;
+        Ok(results
+            .into_iter()
+            .map(convert::memory_item_from_model)
+            .collect())
     }
 
-    async fn find_by_id(&self, id: &Uuid) -> anyhow::Result<Option<MemoryItem>> {
-        let result = memory_items::Entity::find_by_id(*id).one(&self.db).await?;
-        Ok(result.map(convert::memory_item_from_model))
+    /// Vector candidates merged with the memories BM25 ranks highest for
+    /// `query_text`, the best `candidate_count(top_k)` by reciprocal rank
+    /// fusion. Also returns the lexical similarity (0.0-1.0) of every
+    /// memory matching the query.
+    async fn hybrid_candidates(
+        &self,
+        scope: &MemoryScope,
+        query_embedding: &[f32],
+        query_text: &str,
+        top_k: usize,
+    ) -> anyhow::Result<(Vec<MemoryItem>, HashMap<Uuid, f64>)> {
+        if let Err(e) = self.refresh_indexes().await {
+            warn!("Failed to refresh search indexes: {e}");
+        }
+        let limit = candidate_count(top_k);
+        let mut items = self.candidates(scope, query_embedding, top_k).await?;
+
+        let lexical = self.lexical.scores(&scope.to_string(), query_text);
+        let mut lexical_ranking: Vec<(Uuid, f64)> =
+            lexical.iter().map(|(id, score)| (*id, *score)).collect();
+        lexical_ranking.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+        lexical_ranking.truncate(limit);
+
+        let present: HashSet<Uuid> = items.iter().map(|item| item.id).collect();
+        let missing: Vec<Uuid> = lexical_ranking
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| !present.contains(id))
+            .collect();
+        if !missing.is_empty() {
+            let models = memory_items::Entity::find()
+                .filter(memory_items::Column::Id.is_in(missing))
+                .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+                .filter(memory_items::Column::SupersededAt.is_null())
+                .all(&self.db)
+                .await?;
+            items.extend(models.into_iter().map(convert::memory_item_from_model));
+        }
+
+        let mut by_cosine: Vec<(Uuid, f64)> = items
+            .iter()
+            .filter_map(|item| {
+                item.embedding.as_ref().map(|embedding| {
+                    (
+                        item.id,
+                        scoring::cosine_similarity(query_embedding, embedding),
+                    )
+                })
+            })
+            .collect();
+        by_cosine.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+        let vector_ids: Vec<Uuid> = by_cosine.into_iter().map(|(id, _)| id).collect();
+        let lexical_ids: Vec<Uuid> = lexical_ranking.into_iter().map(|(id, _)| id).collect();
+        let fused: HashMap<Uuid, f64> = self
+            .scoring
+            .fusion
+            .fuse(&vector_ids, &lexical_ids)
+            .into_iter()
+            .collect();
+
+        items.sort_by(|a, b| {
+            let fused_a = fused.get(&a.id).copied().unwrap_or_default();
+            let fused_b = fused.get(&b.id).copied().unwrap_or_default();
+            fused_b.total_cmp(&fused_a)
+        });
+        items.truncate(limit);
+        Ok((items, lexical))
     }
 
-    async fn find_by_content_hash(&self, hash: &str) -> anyhow::Result<Option<MemoryItem>> {
-        let result = memory_items::Entity::find()
-            .filter(memory_items::Column::ContentHash.eq(hash))
-            .one(&self.db)
+    /// Index memories written or superseded since the last refresh,
+    /// including by other processes sharing the database.
+    ///
+    /// Memories deleted elsewhere stay indexed until the next startup, but
+    /// are never returned: candidates are read back from the database.
+    async fn refresh_indexes(&self) -> anyhow::Result<()> {
+        let started = Utc::now();
+        let since = *self
+            .refreshed_at
+            .lock()
+            .map_err(|_| anyhow::anyhow!("refresh lock poisoned"))?;
+        let changed = memory_items::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(memory_items::Column::UpdatedAt.gte(since))
+                    .add(memory_items::Column::SupersededAt.gte(since)),
+            )
+            .all(&self.db)
             .await?;
-        Ok(result.map(convert::memory_item_from_model))
-    }
 
-    async fn update(&self, item: &MemoryItem) -> anyhow::Result<()> {
-        let existing = memory_items::Entity::find_by_id(item.id)
-            .one(&self.db)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("MemoryItem not found: {}", item.id))?;
+        for model in changed {
+            let scope = model.user_scope.clone();
+            let current = model.superseded_at.is_none();
+            let item = convert::memory_item_from_model(model);
+            let index = match &self.vector_search {
+                VectorSearch::Index(index) => Some(index),
+                VectorSearch::PgVector(_) | VectorSearch::Scan => None,
+            };
+            if !current {
+                self.lexical.remove(&scope, &item.id);
+                if let Some(index) = index {
+                    index.remove(&scope, &item.id);
+                }
+                continue;
+            }
+            let version = vector_index::version_of(item.updated_at);
+            if let Some(index) = index
+                && index.version(&scope, &item.id) == Some(version)
+            {
+                continue;
+            }
+            self.lexical.upsert(&scope, item.id, &item.summary);
+            if let Some(index) = index {
+                index.upsert(&scope, item.id, version, item.embedding.as_deref());
+            }
+        }
 
-        let model = memory_items::ActiveModel {
-            id: Set(existing.id),
-            memory_type: Set(item.memory_type.to_string()),
-            summary: Set(item.summary.clone()),
-            embedding: Set(item
-                .embedding
-                .as_ref()
-                .map(|v| convert::embedding_to_json(v.as_slice()))),
-            happened_at: Set(item.happened_at.into()),
-            extra: Set(item.extra.clone()),
-            content_hash: Set(item.content_hash.clone()),
-            reinforcement_count: Set(item.reinforcement_count),
-            created_at: Set(existing.created_at),
-            updated_at: Set(item.updated_at.into()),
-        };
-        model.update(&self.db).await?;
+        if let Ok(mut refreshed_at) = self.refreshed_at.lock() {
+            *refreshed_at = started - chrono::Duration::seconds(REFRESH_OVERLAP_SECS);
+        }
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> anyhow::Result<()> {
-        let existing = memory_items::Entity::find_by_id(*id)
-            .one(&self.db)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("MemoryItem not found: {id}"))?;
-
-        existing.delete(&self.db).await?;
+    /// Keep the vector and lexical indexes in step with a stored item.
+    pub(crate) async fn index_item(&self, scope: &str, item: &MemoryItem) -> anyhow::Result<()> {
+        self.lexical.upsert(scope, item.id, &item.summary);
+        match &self.vector_search {
+            VectorSearch::PgVector(_) => {
+                pgvector::store(&self.db, item.id, item.embedding.as_deref()).await?;
+            }
+            VectorSearch::Index(index) => index.upsert(
+                scope,
+                item.id,
+                vector_index::version_of(item.updated_at),
+                item.embedding.as_deref(),
+            ),
+            VectorSearch::Scan => {}
+        }
         Ok(())
     }
 
-    async fn list_all(&self) -> anyhow::Result<Vec<MemoryItem>> {
-        let results = memory_items::Entity::find().all(&self.db).await?;
-        Ok(results
-            .into_iter()
-            .map(convert::memory_item_from_model)
-            .collect())
+    /// Score, rerank and deduplicate candidate items, keeping the best
+    /// `top_k`.
+    async fn rank(
+        &self,
+        items: Vec<MemoryItem>,
+        query_embedding: &[f32],
+        query_text: &str,
+        lexical: &HashMap<Uuid, f64>,
+        top_k: usize,
+    ) -> Vec<SalienceScore<MemoryItem>> {
+        let scores = self.score(items, query_embedding, query_text, lexical);
+        // Apply reranker for question-type-specific score boosting
+        let boosted = self.reranker.rerank(scores, query_text, lexical).await;
+        self.order(boosted, top_k)
     }
 
-    async fn search_by_embedding(
+    /// Hybrid similarity and salience of each candidate item, without the
+    /// items that are essentially the query itself. `lexical` holds the
+    /// lexical similarity of the items matching the query.
+    fn score(
         &self,
+        items: Vec<MemoryItem>,
         query_embedding: &[f32],
         query_text: &str,
-        top_k: usize,
-    ) -> anyhow::Result<Vec<SalienceScore<MemoryItem>>> {
-        let items: Vec<MemoryItem> = MemoryItemRepo::list_all(self).await?;
+        lexical: &HashMap<Uuid, f64>,
+    ) -> Vec<SalienceScore<MemoryItem>> {
         let now = Utc::now();
 
-        // Filter out items that are essentially the same as the query (similarity >= 0.95)
+        // Filter out items that are essentially the same as the query
+        // (similarity >= self_match_threshold)
         // to avoid returning the exact same question back to the user
-        let filtered_scores: Vec<SalienceScore<MemoryItem>> = items
+        items
             .into_par_iter()
             .map(|item| {
+                let lexical_sim = lexical.get(&item.id).copied().unwrap_or_default();
                 let (similarity, salience) = if let Some(embedding) = &item.embedding {
                     let vector_sim = scoring::cosine_similarity(query_embedding, embedding);
-                    // Compute keyword overlap score
-                    let keyword_overlap = scoring::keyword_overlap(query_text, &item.summary);
-                    // Use hybrid similarity: 70% vector + 30% keyword
-                    let hybrid_sim = scoring::hybrid_similarity(vector_sim, keyword_overlap);
+                    // Weighted blend of vector and BM25 similarity
+                    let hybrid_sim = self
+                        .scoring
+                        .fusion
+                        .hybrid_similarity(vector_sim, lexical_sim);
                     // Apply question penalty: penalize memories that are questions when query is also a question
-                    let question_penalty = scoring::question_penalty(query_text, &item.summary);
+                    let question_penalty = scoring::question_penalty(
+                        query_text,
+                        &item.summary,
+                        &self.question_words,
+                        self.scoring.question_penalty,
+                    );
                     let penalized_sim = hybrid_sim * question_penalty;
                     let sal = scoring::compute_salience(
                         penalized_sim,
@@ -327,11 +583,15 @@ impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
                     (penalized_sim, sal)
                 } else {
                     // Items without embeddings get a low default score based on recency only
-                    // Still compute keyword overlap for relevance
-                    let keyword_overlap = scoring::keyword_overlap(query_text, &item.summary);
-                    let hybrid_sim = scoring::hybrid_similarity(0.0, keyword_overlap);
+                    // Still use BM25 similarity for relevance
+                    let hybrid_sim = self.scoring.fusion.hybrid_similarity(0.0, lexical_sim);
                     // Apply question penalty even for items without embeddings
-                    let question_penalty = scoring::question_penalty(query_text, &item.summary);
+                    let question_penalty = scoring::question_penalty(
+                        query_text,
+                        &item.summary,
+                        &self.question_words,
+                        self.scoring.question_penalty,
+                    );
                     let penalized_sim = hybrid_sim * question_penalty;
                     (
                         penalized_sim,
@@ -350,12 +610,17 @@ impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
                     similarity,
                 }
             })
-            .filter(|score| score.similarity < 0.95)
-            .collect();
-
-        // Apply reranker for question-type-specific score boosting
-        let boosted = self.reranker.rerank(filtered_scores, query_text);
+            .filter(|score| score.similarity < self.scoring.self_match_threshold)
+            .collect()
+    }
 
+    /// Sort scored items and deduplicate them by summary, keeping the best
+    /// `top_k`.
+    fn order(
+        &self,
+        mut sorted: Vec<SalienceScore<MemoryItem>>,
+        top_k: usize,
+    ) -> Vec<SalienceScore<MemoryItem>> {
         // Sort memories with a multi-tier priority system:
         // 1. Primary tier: Facts (no question keywords) > Questions (with question keywords)
         // 2. Secondary tier: For facts, time-weighted similarity (newest wins when close)
@@ -366,13 +631,14 @@ impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
         // - When a user asks a question, factual answers rank higher than questions
         // - Among facts, newer memories are preferred when semantic similarity is close
         // - The time-based tiebreaker uses a small epsilon threshold for "closeness"
-        let similarity_epsilon = 0.05_f64;
+        let similarity_epsilon = self.scoring.recency_tie_epsilon;
 
-        let mut sorted = boosted;
         sorted.par_sort_unstable_by(|a, b| {
             // Primary: Facts (no question keywords) rank higher than questions
-            let a_is_question = scoring::count_question_keywords(&a.item.summary) > 0;
-            let b_is_question = scoring::count_question_keywords(&b.item.summary) > 0;
+            let a_is_question =
+                scoring::count_question_keywords(&a.item.summary, &self.question_words) > 0;
+            let b_is_question =
+                scoring::count_question_keywords(&b.item.summary, &self.question_words) > 0;
             match (!a_is_question, !b_is_question) {
                 (true, false) => return std::cmp::Ordering::Less, // a is fact, b is question: a < b (a first)
                 (false, true) => return std::cmp::Ordering::Greater, // a is question, b is fact: a > b (b first)
@@ -408,17 +674,177 @@ impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
         }
         deduped.truncate(top_k);
 
-        Ok(deduped)
+        deduped
+    }
+}
+
+#[async_trait]
+impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
+    async fn insert(&self, scope: &MemoryScope, item: &MemoryItem) -> anyhow::Result<()> {
+        // Stamped with the write time, which index refreshes key on
+        let item = &MemoryItem {
+            updated_at: Utc::now(),
+            ..item.clone()
+        };
+        let scope = scope.to_string();
+        convert::memory_item_to_model(&scope, item)
+            .insert(&self.db)
+            .await?;
+        self.index_item(&scope, item).await?;
+        self.extract_cards(&scope, item).await;
+        Ok(())
+    }
+
+    async fn find_by_id(
+        &self,
+        scope: &MemoryScope,
+        id: &Uuid,
+    ) -> anyhow::Result<Option<MemoryItem>> {
+        let result = memory_items::Entity::find_by_id(*id)
+            .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+            .one(&self.db)
+            .await?;
+        Ok(result.map(convert::memory_item_from_model))
+    }
+
+    async fn find_by_content_hash(
+        &self,
+        scope: &MemoryScope,
+        hash: &str,
+    ) -> anyhow::Result<Option<MemoryItem>> {
+        let result = memory_items::Entity::find()
+            .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+            .filter(memory_items::Column::ContentHash.eq(hash))
+            .filter(memory_items::Column::SupersededAt.is_null())
+            .one(&self.db)
+            .await?;
+        Ok(result.map(convert::memory_item_from_model))
+    }
+
+    async fn update(&self, scope: &MemoryScope, item: &MemoryItem) -> anyhow::Result<()> {
+        let item = &MemoryItem {
+            updated_at: Utc::now(),
+            ..item.clone()
+        };
+        let existing = memory_items::Entity::find_by_id(item.id)
+            .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("MemoryItem not found: {}", item.id))?;
+
+        let scope = existing.user_scope;
+        let summary_changed = existing.summary != item.summary;
+        let model = memory_items::ActiveModel {
+            id: Set(existing.id),
+            user_scope: Set(scope.clone()),
+            memory_type: Set(item.memory_type.to_string()),
+            summary: Set(item.summary.clone()),
+            embedding: Set(item
+                .embedding
+                .as_ref()
+                .map(|v| convert::embedding_to_json(v.as_slice()))),
+            happened_at: Set(item.happened_at.into()),
+            extra: Set(item.extra.clone()),
+            content_hash: Set(item.content_hash.clone()),
+            reinforcement_count: Set(item.reinforcement_count),
+            previous_version_id: Set(existing.previous_version_id),
+            superseded_at: Set(existing.superseded_at),
+            version_relation: Set(existing.version_relation),
+            created_at: Set(existing.created_at),
+            updated_at: Set(item.updated_at.into()),
+        };
+        model.update(&self.db).await?;
+        self.index_item(&scope, item).await?;
+        if summary_changed {
+            self.extract_cards(&scope, item).await;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, scope: &MemoryScope, id: &Uuid) -> anyhow::Result<()> {
+        let existing = memory_items::Entity::find_by_id(*id)
+            .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("MemoryItem not found: {id}"))?;
+
+        if existing.superseded_at.is_none() {
+            return self.delete_chain(scope, id).await;
+        }
+        self.delete_cards_for(id).await?;
+        // Keep the version chain connected around the deleted version
+        memory_items::Entity::update_many()
+            .col_expr(
+                memory_items::Column::PreviousVersionId,
+                Expr::value(existing.previous_version_id),
+            )
+            .filter(memory_items::Column::PreviousVersionId.eq(*id))
+            .exec(&self.db)
+            .await?;
+        existing.delete(&self.db).await?;
+        self.lexical.remove(&scope.to_string(), id);
+        if let VectorSearch::Index(index) = &self.vector_search {
+            index.remove(&scope.to_string(), id);
+        }
+        Ok(())
+    }
+
+    async fn list(&self, scope: &MemoryScope) -> anyhow::Result<Vec<MemoryItem>> {
+        let results = memory_items::Entity::find()
+            .filter(memory_items::Column::UserScope.eq(scope.to_string()))
+            .filter(memory_items::Column::SupersededAt.is_null())
+            .all(&self.db)
+            .await?;
+        Ok(results
+            .into_iter()
+            .map(convert::memory_item_from_model)
+            .collect())
+    }
+
+    async fn search_by_embedding(
+        &self,
+        scope: &MemoryScope,
+        query_embedding: &[f32],
+        query_text: &str,
+        top_k: usize,
+    ) -> anyhow::Result<Vec<SalienceScore<MemoryItem>>> {
+        let (items, lexical) = self
+            .hybrid_candidates(scope, query_embedding, query_text, top_k)
+            .await?;
+        Ok(self
+            .rank(items, query_embedding, query_text, &lexical, top_k)
+            .await)
+    }
+
+    /// Vector search with the memories behind matching cards fused ahead:
+    /// the query's question types pick the card slots to look up. History
+    /// questions also get the earlier versions of each result.
+    async fn search_enhanced(
+        &self,
+        scope: &MemoryScope,
+        query_embedding: &[f32],
+        query_text: &str,
+        top_k: usize,
+    ) -> anyhow::Result<Vec<SalienceScore<MemoryItem>>> {
+        let results =
+            MemoryItemRepo::search_by_embedding(self, scope, query_embedding, query_text, top_k)
+                .await?;
+        let results = self
+            .search_with_cards(scope, results, query_text, top_k)
+            .await;
+        Ok(self.with_history(scope, results, query_text).await)
     }
 
     async fn backfill_embeddings(
         &self,
         embed_fn: &(dyn Fn(String) -> anyhow::Result<Vec<f32>> + Send + Sync),
     ) -> anyhow::Result<usize> {
-        let items = MemoryItemRepo::list_all(self).await?;
+        let items = memory_items::Entity::find().all(&self.db).await?;
         let mut updated = 0_usize;
 
-        for mut item in items {
+        for model in items {
+            let scope = model.user_scope.parse::<MemoryScope>()?;
+            let mut item = convert::memory_item_from_model(model);
             if item.embedding.is_none() {
                 // Generate embedding from the summary text
                 let summary = item.summary.clone();
@@ -426,7 +852,7 @@ impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
                     Ok(embedding) => {
                         item.embedding = Some(embedding);
                         item.updated_at = Utc::now();
-                        MemoryItemRepo::update(self, &item).await?;
+                        MemoryItemRepo::update(self, &scope, &item).await?;
                         updated += 1;
                         info!("Backfilled embedding for memory: {}", item.id);
                     }
@@ -442,10 +868,10 @@ impl<R: Reranker> MemoryItemRepo for MemoryManager<R> {
 
     async fn semantic_upsert(
         &self,
+        scope: &MemoryScope,
         item: &MemoryItem,
-        similarity_threshold: f64,
     ) -> anyhow::Result<Uuid> {
-        self.semantic_upsert_memory(item, similarity_threshold)
+        self.semantic_upsert_memory(scope, item, self.scoring.supersede_threshold)
             .await
     }
 }
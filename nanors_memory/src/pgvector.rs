@@ -0,0 +1,233 @@
+//! Database-side vector search on Postgres with the pgvector extension.
+//!
+//! When migration `015_add_pgvector` found the extension, embeddings are
+//! mirrored into the `embedding_vec` column (HNSW index, cosine distance) and
+//! top-K candidate selection runs in Postgres. The JSONB `embedding` column
+//! stays the source of truth, so databases without pgvector fall back to
+//! the in-process vector index.
+
+use nanors_core::memory::MemoryScope;
+use sea_orm::prelude::PgVector;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement, TransactionTrait};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Dimensions of the `embedding_vec` column (Zhipu `embedding-2`).
+pub const EMBEDDING_DIMENSIONS: usize = 1024;
+
+/// Largest `hnsw.ef_search` pgvector accepts.
+const MAX_EF_SEARCH: usize = 1000;
+
+/// How `nearest` walks the HNSW index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// pgvector 0.8+ keeps scanning until enough rows pass the scope filter
+    pub iterative_scan: bool,
+}
+
+impl SearchOptions {
+    /// Probe the installed pgvector version.
+    pub async fn detect(db: &DatabaseConnection) -> Self {
+        let stmt = Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT extversion FROM pg_extension WHERE extname = 'vector'",
+        );
+        let version = match db.query_one_raw(stmt).await {
+            Ok(row) => row.and_then(|row| row.try_get::<String>("", "extversion").ok()),
+            Err(e) => {
+                warn!("Could not read the pgvector version: {e}");
+                None
+            }
+        };
+        Self {
+            iterative_scan: version.as_deref().is_some_and(supports_iterative_scan),
+        }
+    }
+
+    /// `SET LOCAL` statements for a query returning `limit` rows.
+    ///
+    /// The HNSW index is filtered by scope after the scan, so a plain scan
+    /// of `ef_search` rows can leave a small scope with almost nothing.
+    /// Iterative scans keep going until `limit` rows match; older versions
+    /// get the widest scan instead.
+    fn settings(self, limit: usize) -> Vec<String> {
+        if self.iterative_scan {
+            vec![
+                "SET LOCAL hnsw.iterative_scan = relaxed_order".to_string(),
+                format!(
+                    "SET LOCAL hnsw.ef_search = {}",
+                    limit.clamp(1, MAX_EF_SEARCH)
+                ),
+            ]
+        } else {
+            vec![format!("SET LOCAL hnsw.ef_search = {MAX_EF_SEARCH}")]
+        }
+    }
+}
+
+/// Whether a pgvector `extversion` has `hnsw.iterative_scan` (0.8.0+).
+fn supports_iterative_scan(version: &str) -> bool {
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let major = parts.next().unwrap_or(0);
+    let minor = parts.next().unwrap_or(0);
+    (major, minor) >= (0, 8)
+}
+
+/// Whether a query embedding can be compared against `embedding_vec`.
+#[must_use]
+pub const fn accepts_query(query: &[f32]) -> bool {
+    query.len() == EMBEDDING_DIMENSIONS
+}
+
+/// Whether the database has the pgvector column from migration 015.
+pub async fn is_available(db: &DatabaseConnection) -> bool {
+    if db.get_database_backend() != DbBackend::Postgres {
+        return false;
+    }
+    let stmt = Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT 1 FROM information_schema.columns \
+         WHERE table_name = 'memory_items' AND column_name = 'embedding_vec'",
+    );
+    match db.query_one_raw(stmt).await {
+        Ok(row) => {
+            let available = row.is_some();
+            if available {
+                info!("Using pgvector for memory search");
+            }
+            available
+        }
+        Err(e) => {
+            warn!("Could not check for pgvector, falling back to scan: {e}");
+            false
+        }
+    }
+}
+
+/// Mirror an item's embedding into `embedding_vec`.
+///
+/// Embeddings with other dimensions cannot go into the indexed column; they
+/// are left out of the index (and of vector search) with a warning.
+pub async fn store(
+    db: &DatabaseConnection,
+    id: Uuid,
+    embedding: Option<&[f32]>,
+) -> anyhow::Result<()> {
+    let vector = match embedding {
+        Some(embedding) if embedding.len() == EMBEDDING_DIMENSIONS => {
+            Some(PgVector::from(embedding.to_vec()))
+        }
+        Some(embedding) => {
+            warn!(
+                "Memory {id} has a {}-dimensional embedding, expected {EMBEDDING_DIMENSIONS}; \
+                 not indexed",
+                embedding.len()
+            );
+            None
+        }
+        None => None,
+    };
+    let stmt = Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "UPDATE memory_items SET embedding_vec = $1 WHERE id = $2",
+        [vector.into(), id.into()],
+    );
+    db.execute_raw(stmt).await?;
+    Ok(())
+}
+
+/// Ids of the `limit` current items in `scope` nearest to `query` by
+/// cosine distance, nearest first.
+///
+/// `None` when `query` has other dimensions than the column (see
+/// [`accepts_query`]); callers fall back to scoring the scope.
+pub async fn nearest(
+    db: &DatabaseConnection,
+    options: SearchOptions,
+    scope: &MemoryScope,
+    query: &[f32],
+    limit: usize,
+) -> anyhow::Result<Option<Vec<Uuid>>> {
+    if !accepts_query(query) {
+        warn!(
+            "Query embedding has {} dimensions, expected {EMBEDDING_DIMENSIONS}; \
+             not using pgvector",
+            query.len()
+        );
+        return Ok(None);
+    }
+
+    // SET LOCAL only lasts until the end of the transaction
+    let txn = db.begin().await?;
+    for setting in options.settings(limit) {
+        txn.execute_raw(Statement::from_string(DbBackend::Postgres, setting))
+            .await?;
+    }
+    let stmt = Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT id FROM memory_items \
+         WHERE user_scope = $1 AND embedding_vec IS NOT NULL AND superseded_at IS NULL \
+         ORDER BY embedding_vec <=> $2 \
+         LIMIT $3",
+        [
+            scope.to_string().into(),
+            PgVector::from(query.to_vec()).into(),
+            i64::try_from(limit).unwrap_or(i64::MAX).into(),
+        ],
+    );
+    let ids = txn
+        .query_all_raw(stmt)
+        .await?
+        .iter()
+        .map(|row| Ok(row.try_get::<Uuid>("", "id")?))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    txn.commit().await?;
+    Ok(Some(ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterative_scan_needs_pgvector_0_8() {
+        assert!(supports_iterative_scan("0.8.0"));
+        assert!(supports_iterative_scan("0.10.1"));
+        assert!(supports_iterative_scan("1.0"));
+        assert!(!supports_iterative_scan("0.7.4"));
+        assert!(!supports_iterative_scan("0.5.1"));
+        assert!(!supports_iterative_scan("garbage"));
+    }
+
+    #[test]
+    fn test_settings_widen_the_scan() {
+        let iterative = SearchOptions {
+            iterative_scan: true,
+        };
+        assert_eq!(
+            iterative.settings(150),
+            [
+                "SET LOCAL hnsw.iterative_scan = relaxed_order",
+                "SET LOCAL hnsw.ef_search = 150",
+            ]
+        );
+        assert_eq!(
+            iterative.settings(5000)[1],
+            "SET LOCAL hnsw.ef_search = 1000"
+        );
+
+        let plain = SearchOptions {
+            iterative_scan: false,
+        };
+        assert_eq!(plain.settings(50), ["SET LOCAL hnsw.ef_search = 1000"]);
+    }
+
+    #[test]
+    fn test_query_dimensions_are_checked() {
+        assert!(accepts_query(&[0.0; EMBEDDING_DIMENSIONS]));
+        assert!(!accepts_query(&[0.0; 3]));
+        assert!(!accepts_query(&[]));
+    }
+}
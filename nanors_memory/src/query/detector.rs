@@ -6,6 +6,8 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use super::language::LanguagePack;
+
 /// The detected type of a question/query.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -80,7 +82,7 @@ impl std::str::FromStr for QuestionType {
 }
 
 /// Pattern definition for detecting a question type.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct QuestionPattern {
     /// The question type this pattern detects.
     pub question_type: QuestionType,
@@ -148,73 +150,11 @@ impl Default for QuestionDetectorConfig {
     }
 }
 
-/// Default question patterns for Chinese and English.
+/// Default question patterns for Chinese and English: those of the
+/// built-in [`LanguagePack`]s.
 #[must_use]
 pub fn default_patterns() -> Vec<QuestionPattern> {
-    vec![
-        // WhatKind patterns - highest priority for identity questions
-        QuestionPattern {
-            question_type: QuestionType::WhatKind,
-            pattern: r"(?i)(我是什么|我是谁|我的身份|我的类型|我属于|我算.*用户|我属于.*吗)"
-                .to_string(),
-            priority: 100,
-        },
-        QuestionPattern {
-            question_type: QuestionType::WhatKind,
-            pattern: r"(?i)(what kind|what type|who am i|what am i|my identity)".to_string(),
-            priority: 90,
-        },
-        // Recency patterns
-        QuestionPattern {
-            question_type: QuestionType::Recency,
-            pattern:
-                r"(?i)(现在|目前|最新|当前|最近|current|latest|right now|at the moment|up to date)"
-                    .to_string(),
-            priority: 80,
-        },
-        // HowMany patterns
-        QuestionPattern {
-            question_type: QuestionType::HowMany,
-            pattern: r"(?i)(多少|有几个|几多|how many|how much|count of|number of)".to_string(),
-            priority: 70,
-        },
-        // Update/change patterns
-        QuestionPattern {
-            question_type: QuestionType::Update,
-            pattern: r"(?i)(之前|原来|之前是|以前.*现在|changed|updated|was.*now)".to_string(),
-            priority: 60,
-        },
-        // Where patterns
-        QuestionPattern {
-            question_type: QuestionType::Where,
-            pattern: r"(?i)(在哪|在哪里|在哪里|where|which place|which location)".to_string(),
-            priority: 50,
-        },
-        // When patterns
-        QuestionPattern {
-            question_type: QuestionType::When,
-            pattern: r"(?i)(什么时候|何时|when|at what time|what time)".to_string(),
-            priority: 45,
-        },
-        // Preference patterns
-        QuestionPattern {
-            question_type: QuestionType::Preference,
-            pattern: r"(?i)(喜欢什么|爱什么|偏好|what.*like|what do you like)".to_string(),
-            priority: 40,
-        },
-        // Have/possession patterns
-        QuestionPattern {
-            question_type: QuestionType::Have,
-            pattern: r"(?i)(有什么|拥有|have|have.*got|possess)".to_string(),
-            priority: 35,
-        },
-        // Can/capability patterns
-        QuestionPattern {
-            question_type: QuestionType::Can,
-            pattern: r"(?i)(会.*吗|能.*吗|can you|able to|capable of)".to_string(),
-            priority: 30,
-        },
-    ]
+    LanguagePack::default().question_patterns
 }
 
 /// Question type detector.
@@ -262,6 +202,27 @@ impl QuestionTypeDetector {
         QuestionType::Generic
     }
 
+    /// Every question type the query matches, highest priority first.
+    ///
+    /// "我现在住在哪" is both [`QuestionType::Recency`] and
+    /// [`QuestionType::Where`]; [`detect`](Self::detect) only reports the
+    /// first.
+    #[must_use]
+    pub fn detect_all(&self, query: &str) -> Vec<QuestionType> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        let lower = query.to_lowercase();
+        let mut types = Vec::new();
+        for pattern in &self.patterns {
+            if !types.contains(&pattern.question_type) && pattern.matches(&lower) {
+                types.push(pattern.question_type);
+            }
+        }
+        types
+    }
+
     /// Check if a query is of a specific type.
     #[must_use]
     pub fn is_type(&self, query: &str, question_type: QuestionType) -> bool {
@@ -357,6 +318,16 @@ mod tests {
         assert_eq!(detector.detect("告诉我的情况"), QuestionType::Generic);
     }
 
+    #[test]
+    fn test_detect_all() {
+        let detector = QuestionTypeDetector::with_defaults();
+        assert_eq!(
+            detector.detect_all("我现在住在哪"),
+            vec![QuestionType::Recency, QuestionType::Where]
+        );
+        assert!(detector.detect_all("hello").is_empty());
+    }
+
     #[test]
     fn test_is_type() {
         let detector = QuestionTypeDetector::with_defaults();
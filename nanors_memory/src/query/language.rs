@@ -0,0 +1,306 @@
+//! Language packs: the words and patterns retrieval scoring matches on.
+//!
+//! Question detection, the rule-based reranker's keyword boosts and the
+//! question penalty all work on surface text, so each language brings its
+//! own patterns and keyword lists. Chinese (`zh`) and English (`en`) are
+//! built in; further packs are JSON files with the same shape:
+//!
+//! ```json
+//! {
+//!   "name": "de",
+//!   "question_patterns": [
+//!     {"question_type": "where", "pattern": "(?i)(wo |woher)", "priority": 50}
+//!   ],
+//!   "rerank_keywords": {"location": ["wohne", "stadt"]},
+//!   "question_words": ["wer", "was", "wo"]
+//! }
+//! ```
+//!
+//! Packs are merged in order, so several languages can be active at once.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::detector::{QuestionDetectorConfig, QuestionPattern, QuestionType};
+
+/// Chinese question words: a memory containing them is likely a question
+/// rather than an answer.
+pub const ZH_QUESTION_WORDS: &[&str] = &[
+    // Standard question words
+    "什么",
+    "谁",
+    "多少",
+    "怎么",
+    "如何",
+    "为什么",
+    "干啥",
+    "啥",
+    "吗",
+    "是不是",
+    "是否",
+    "呢",
+    "哪",
+    // Colloquial question words (use compound words to avoid substring overlap)
+    "咋",
+    // Time/quantity questions
+    "几",
+    "几时",
+    "何时",
+];
+
+/// Keywords the rule-based reranker boosts per question type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RerankKeywords {
+    /// Identity words, for "what kind of user am I"
+    pub profile: Vec<String>,
+    /// Place words, for "where"
+    pub location: Vec<String>,
+    /// Liking words, for "what do I like"
+    pub preference: Vec<String>,
+    /// Counting words, for "how many"
+    pub count: Vec<String>,
+    /// Work words, also boosted for identity questions
+    pub profession: Vec<String>,
+}
+
+impl RerankKeywords {
+    fn extend(&mut self, other: Self) {
+        self.profile.extend(other.profile);
+        self.location.extend(other.location);
+        self.preference.extend(other.preference);
+        self.count.extend(other.count);
+        self.profession.extend(other.profession);
+    }
+}
+
+/// Question patterns and keyword lists of one language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguagePack {
+    /// Name the pack is selected by, e.g. `zh`
+    pub name: String,
+    /// Patterns that detect the question type of a query
+    #[serde(default)]
+    pub question_patterns: Vec<QuestionPattern>,
+    /// Keywords the rule-based reranker boosts
+    #[serde(default)]
+    pub rerank_keywords: RerankKeywords,
+    /// Words marking text as a question, for the question penalty
+    #[serde(default)]
+    pub question_words: Vec<String>,
+}
+
+fn strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(ToString::to_string).collect()
+}
+
+fn pattern(question_type: QuestionType, pattern: &str, priority: i32) -> QuestionPattern {
+    QuestionPattern {
+        question_type,
+        pattern: pattern.to_string(),
+        priority,
+    }
+}
+
+impl LanguagePack {
+    /// Built-in Chinese pack.
+    #[must_use]
+    pub fn zh() -> Self {
+        Self {
+            name: "zh".to_string(),
+            question_patterns: vec![
+                pattern(
+                    QuestionType::WhatKind,
+                    r"(?i)(我是什么|我是谁|我的身份|我的类型|我属于|我算.*用户|我属于.*吗)",
+                    100,
+                ),
+                pattern(QuestionType::Recency, r"(?i)(现在|目前|最新|当前|最近)", 80),
+                pattern(QuestionType::HowMany, r"(?i)(多少|有几个|几多)", 70),
+                pattern(
+                    QuestionType::Update,
+                    r"(?i)(之前|原来|之前是|以前.*现在)",
+                    60,
+                ),
+                pattern(QuestionType::Where, r"(?i)(在哪|在哪里)", 50),
+                pattern(QuestionType::When, r"(?i)(什么时候|何时)", 45),
+                pattern(QuestionType::Preference, r"(?i)(喜欢什么|爱什么|偏好)", 40),
+                pattern(QuestionType::Have, r"(?i)(有什么|拥有)", 35),
+                pattern(QuestionType::Can, r"(?i)(会.*吗|能.*吗)", 30),
+            ],
+            rerank_keywords: RerankKeywords {
+                profile: strings(&["用户", "类型", "角色", "身份"]),
+                location: strings(&["住", "居住", "位置", "地点", "城市", "地址"]),
+                preference: strings(&["喜欢", "爱", "偏好", "爱好", "感兴趣"]),
+                count: strings(&["个", "只", "次", "数量", "一共"]),
+                profession: strings(&["工作", "就职", "公司", "职业", "工程师", "开发"]),
+            },
+            question_words: strings(ZH_QUESTION_WORDS),
+        }
+    }
+
+    /// Built-in English pack.
+    ///
+    /// Has no question words: English ones ("what", "where") are common in
+    /// stated facts too.
+    #[must_use]
+    pub fn en() -> Self {
+        Self {
+            name: "en".to_string(),
+            question_patterns: vec![
+                pattern(
+                    QuestionType::WhatKind,
+                    r"(?i)(what kind|what type|who am i|what am i|my identity)",
+                    90,
+                ),
+                pattern(
+                    QuestionType::Recency,
+                    r"(?i)(current|latest|right now|at the moment|up to date)",
+                    80,
+                ),
+                pattern(
+                    QuestionType::HowMany,
+                    r"(?i)(how many|how much|count of|number of)",
+                    70,
+                ),
+                pattern(QuestionType::Update, r"(?i)(changed|updated|was.*now)", 60),
+                pattern(
+                    QuestionType::Where,
+                    r"(?i)(where|which place|which location)",
+                    50,
+                ),
+                pattern(QuestionType::When, r"(?i)(when|at what time|what time)", 45),
+                pattern(
+                    QuestionType::Preference,
+                    r"(?i)(what.*like|what do you like)",
+                    40,
+                ),
+                pattern(QuestionType::Have, r"(?i)(have|have.*got|possess)", 35),
+                pattern(QuestionType::Can, r"(?i)(can you|able to|capable of)", 30),
+            ],
+            rerank_keywords: RerankKeywords {
+                profile: strings(&["user", "type", "role", "identity"]),
+                location: strings(&["location", "place", "city", "address"]),
+                preference: strings(&["prefer", "hobby", "interest"]),
+                count: strings(&["count", "number", "total"]),
+                profession: strings(&[
+                    "company",
+                    "work",
+                    "job",
+                    "profession",
+                    "engineer",
+                    "developer",
+                ]),
+            },
+            question_words: Vec::new(),
+        }
+    }
+
+    /// The built-in pack called `name`.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "zh" => Some(Self::zh()),
+            "en" => Some(Self::en()),
+            _ => None,
+        }
+    }
+
+    /// Read a pack from a JSON file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read language pack {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Invalid language pack {}", path.display()))
+    }
+
+    /// A built-in pack by name, or else a pack file; relative paths are
+    /// resolved against `base_dir`.
+    pub fn resolve(spec: &str, base_dir: &Path) -> anyhow::Result<Self> {
+        Self::builtin(spec).map_or_else(|| Self::load(&base_dir.join(spec)), Ok)
+    }
+
+    /// Merge packs into one with all their patterns and words.
+    #[must_use]
+    pub fn merge(packs: impl IntoIterator<Item = Self>) -> Self {
+        let mut merged = Self {
+            name: String::new(),
+            question_patterns: Vec::new(),
+            rerank_keywords: RerankKeywords::default(),
+            question_words: Vec::new(),
+        };
+        let mut names = Vec::new();
+        for pack in packs {
+            names.push(pack.name);
+            merged.question_patterns.extend(pack.question_patterns);
+            merged.rerank_keywords.extend(pack.rerank_keywords);
+            merged.question_words.extend(pack.question_words);
+        }
+        merged.name = names.join("+");
+        merged
+    }
+
+    /// Question detector configuration with this pack's patterns.
+    #[must_use]
+    pub fn detector_config(&self) -> QuestionDetectorConfig {
+        QuestionDetectorConfig {
+            patterns: self.question_patterns.clone(),
+            enabled: true,
+        }
+    }
+}
+
+impl Default for LanguagePack {
+    /// Chinese and English merged.
+    fn default() -> Self {
+        Self::merge([Self::zh(), Self::en()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::detector::QuestionTypeDetector;
+
+    #[test]
+    fn test_default_pack_detects_both_languages() {
+        let pack = LanguagePack::default();
+        assert_eq!(pack.name, "zh+en");
+
+        let detector = QuestionTypeDetector::new(pack.detector_config());
+        assert_eq!(detector.detect("我是什么用户"), QuestionType::WhatKind);
+        assert_eq!(detector.detect("where do I live"), QuestionType::Where);
+        assert_eq!(
+            detector.detect_all("我现在住在哪"),
+            vec![QuestionType::Recency, QuestionType::Where]
+        );
+    }
+
+    #[test]
+    fn test_single_pack_only_knows_its_language() {
+        let detector = QuestionTypeDetector::new(LanguagePack::en().detector_config());
+        assert_eq!(detector.detect("我在哪"), QuestionType::Generic);
+        assert_eq!(detector.detect("where am I"), QuestionType::Where);
+    }
+
+    #[test]
+    fn test_resolve_loads_pack_files() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("nanors_lang_{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("de.json"),
+            r#"{"name": "de", "question_patterns": [{"question_type": "where", "pattern": "wo "}],
+                "rerank_keywords": {"location": ["wohne"]}}"#,
+        )?;
+
+        let pack = LanguagePack::resolve("de.json", &dir)?;
+        assert_eq!(pack.question_patterns[0].question_type, QuestionType::Where);
+        assert_eq!(pack.rerank_keywords.location, ["wohne"]);
+        assert!(pack.question_words.is_empty());
+        assert_eq!(LanguagePack::resolve("zh", &dir)?, LanguagePack::zh());
+        assert!(LanguagePack::resolve("missing.json", &dir).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 //! Query analysis module.
 //!
-//! This module provides tools for detecting question intent.
+//! This module provides tools for detecting question intent, configured by
+//! per-language packs of patterns and keywords.
 
 pub mod detector;
+pub mod language;
@@ -0,0 +1,182 @@
+//! Cross-encoder reranking through a rerank HTTP endpoint.
+//!
+//! Two request shapes are supported:
+//! - [text-embeddings-inference](https://github.com/huggingface/text-embeddings-inference)
+//!   `POST /rerank` with `{"query", "texts"}`, used when no model is set
+//! - Jina-compatible `POST /v1/rerank` with `{"model", "query", "documents"}`,
+//!   used when a model is set
+//!
+//! Both reply with the index and score of each text.
+
+use async_trait::async_trait;
+use nanors_core::memory::{MemoryItem, SalienceScore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::{DEFAULT_MAX_CANDIDATES, Reranker, apply_relevance, top_candidates, with_rest};
+
+/// Time allowed for one rerank request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Scores candidates with a cross-encoder served over HTTP.
+///
+/// Only the best `max_candidates` results (by salience) are scored; the
+/// rest follow them unchanged. When the request fails all keep their
+/// scores.
+pub struct CrossEncoderReranker {
+    client: reqwest::Client,
+    url: String,
+    model: Option<String>,
+    max_candidates: usize,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RerankRequest<'a> {
+    Tei {
+        query: &'a str,
+        texts: Vec<&'a str>,
+    },
+    Jina {
+        model: &'a str,
+        query: &'a str,
+        documents: Vec<&'a str>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RerankResponse {
+    /// text-embeddings-inference: a bare array
+    Tei(Vec<RankedText>),
+    /// Jina: the array under `results`
+    Jina { results: Vec<RankedText> },
+}
+
+#[derive(Deserialize)]
+struct RankedText {
+    index: usize,
+    #[serde(alias = "relevance_score")]
+    score: f64,
+}
+
+impl CrossEncoderReranker {
+    /// A reranker posting to `url`, the full endpoint, e.g.
+    /// `http://localhost:8080/rerank`.
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            model: None,
+            max_candidates: DEFAULT_MAX_CANDIDATES,
+        }
+    }
+
+    /// Send Jina-style requests for `model`.
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Score at most `max_candidates` results per query.
+    #[must_use]
+    pub fn with_max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = max_candidates.max(1);
+        self
+    }
+
+    /// Relevance of each text to `query_text`, in the order of `texts`.
+    async fn score(&self, query_text: &str, texts: Vec<&str>) -> anyhow::Result<Vec<f64>> {
+        let count = texts.len();
+        let request = match &self.model {
+            Some(model) => RerankRequest::Jina {
+                model,
+                query: query_text,
+                documents: texts,
+            },
+            None => RerankRequest::Tei {
+                query: query_text,
+                texts,
+            },
+        };
+        let response: RerankResponse = self
+            .client
+            .post(&self.url)
+            .timeout(REQUEST_TIMEOUT)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        relevance_in_order(response, count)
+    }
+}
+
+#[async_trait]
+impl Reranker for CrossEncoderReranker {
+    async fn rerank(
+        &self,
+        results: Vec<SalienceScore<MemoryItem>>,
+        query_text: &str,
+        _lexical: &HashMap<Uuid, f64>,
+    ) -> Vec<SalienceScore<MemoryItem>> {
+        if results.is_empty() {
+            return results;
+        }
+        let (candidates, rest) = top_candidates(results, self.max_candidates);
+        let texts = candidates
+            .iter()
+            .map(|result| result.item.summary.as_str())
+            .collect();
+        match self.score(query_text, texts).await {
+            Ok(relevance) => with_rest(apply_relevance(candidates, &relevance), rest),
+            Err(e) => {
+                warn!("Cross-encoder rerank failed, keeping the original order: {e}");
+                with_rest(candidates, rest)
+            }
+        }
+    }
+}
+
+/// Scores from `response` by text index; every one of `count` texts must
+/// be scored.
+fn relevance_in_order(response: RerankResponse, count: usize) -> anyhow::Result<Vec<f64>> {
+    let ranked = match response {
+        RerankResponse::Tei(ranked) | RerankResponse::Jina { results: ranked } => ranked,
+    };
+    let mut relevance = vec![None; count];
+    for text in ranked {
+        if let Some(slot) = relevance.get_mut(text.index) {
+            *slot = Some(text.score);
+        }
+    }
+    relevance
+        .into_iter()
+        .collect::<Option<Vec<f64>>>()
+        .ok_or_else(|| anyhow::anyhow!("rerank response does not score all {count} texts"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relevance_in_order() {
+        let tei: RerankResponse =
+            serde_json::from_str(r#"[{"index": 1, "score": 0.9}, {"index": 0, "score": 0.2}]"#)
+                .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(relevance_in_order(tei, 2).ok(), Some(vec![0.2, 0.9]));
+
+        let jina: RerankResponse = serde_json::from_str(
+            r#"{"model": "jina-reranker-v2", "results": [{"index": 0, "relevance_score": 0.7}]}"#,
+        )
+        .unwrap_or_else(|e| panic!("{e}"));
+        assert!(relevance_in_order(jina, 2).is_err());
+    }
+}
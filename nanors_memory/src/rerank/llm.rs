@@ -0,0 +1,119 @@
+//! LLM-based reranking.
+
+use async_trait::async_trait;
+use nanors_core::memory::{MemoryItem, SalienceScore};
+use nanors_core::{ChatMessage, LLMProvider, MessageContent, Role, json_array_in_reply};
+use std::collections::HashMap;
+use std::fmt::Write;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::{DEFAULT_MAX_CANDIDATES, Reranker, apply_relevance, top_candidates, with_rest};
+
+/// Asks the provider to rate every candidate against the query in one
+/// batched call.
+///
+/// Only the best `max_candidates` results (by salience) are rated; the
+/// rest follow them unchanged. When the call fails or the reply does not
+/// rate each candidate, all keep their scores.
+pub struct LlmReranker<P: LLMProvider> {
+    provider: P,
+    model: String,
+    max_candidates: usize,
+}
+
+impl<P: LLMProvider> LlmReranker<P> {
+    #[must_use]
+    pub fn new(provider: P, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            max_candidates: DEFAULT_MAX_CANDIDATES,
+        }
+    }
+
+    /// Rate at most `max_candidates` results per query.
+    #[must_use]
+    pub fn with_max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = max_candidates.max(1);
+        self
+    }
+
+    fn prompt(query_text: &str, results: &[SalienceScore<MemoryItem>]) -> String {
+        let mut memories = String::new();
+        for (i, result) in results.iter().enumerate() {
+            let _ = writeln!(memories, "{i}. {}", result.item.summary.replace('\n', " "));
+        }
+        format!(
+            "Rate how useful each memory below is for answering the query.\n\
+             Reply with a JSON array only: one number from 0 (unrelated) to 10 \
+             (answers the query) per memory, in the order given.\n\n\
+             Query: {query_text}\n\n\
+             Memories:\n{memories}"
+        )
+    }
+}
+
+#[async_trait]
+impl<P: LLMProvider> Reranker for LlmReranker<P> {
+    async fn rerank(
+        &self,
+        results: Vec<SalienceScore<MemoryItem>>,
+        query_text: &str,
+        _lexical: &HashMap<Uuid, f64>,
+    ) -> Vec<SalienceScore<MemoryItem>> {
+        if results.is_empty() {
+            return results;
+        }
+        let (candidates, rest) = top_candidates(results, self.max_candidates);
+        let messages = [ChatMessage {
+            role: Role::User,
+            content: MessageContent::Text(Self::prompt(query_text, &candidates)),
+        }];
+        let reply = match self.provider.chat(&messages, &self.model).await {
+            Ok(response) => response.content,
+            Err(e) => {
+                warn!("LLM rerank failed, keeping the original order: {e}");
+                return with_rest(candidates, rest);
+            }
+        };
+        let Some(relevance) = parse_ratings(&reply, candidates.len()) else {
+            warn!(
+                "LLM rerank reply does not rate {} memories, keeping the original order",
+                candidates.len()
+            );
+            return with_rest(candidates, rest);
+        };
+        with_rest(apply_relevance(candidates, &relevance), rest)
+    }
+}
+
+/// Relevance (0.0-1.0) from a model reply: the outermost JSON array of
+/// 0-10 ratings. `None` unless it holds exactly `expected` ratings.
+fn parse_ratings(reply: &str, expected: usize) -> Option<Vec<f64>> {
+    let ratings: Vec<f64> = json_array_in_reply(reply)?
+        .iter()
+        .map(serde_json::Value::as_f64)
+        .collect::<Option<_>>()?;
+    (ratings.len() == expected).then(|| {
+        ratings
+            .into_iter()
+            .map(|rating| (rating / 10.0).clamp(0.0, 1.0))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ratings() {
+        assert_eq!(
+            parse_ratings("```json\n[10, 2.5, 0, 12]\n```", 4),
+            Some(vec![1.0, 0.25, 0.0, 1.0])
+        );
+        assert_eq!(parse_ratings("[10, 2]", 3), None);
+        assert_eq!(parse_ratings("No memory is relevant.", 1), None);
+    }
+}
@@ -2,80 +2,111 @@
 //!
 //! This module provides reranking capabilities that can be applied to search
 //! results after the initial vector similarity search. The rule-based reranker
-//! applies question-type-specific boosting to improve result quality.
+//! applies question-type-specific boosting to improve result quality; the
+//! LLM and cross-encoder rerankers score each candidate against the query
+//! with a model.
 //!
+mod cross_encoder;
+mod llm;
+
+pub use cross_encoder::CrossEncoderReranker;
+pub use llm::LlmReranker;
+
 use crate::query::detector::{QuestionType, QuestionTypeDetector};
+use crate::query::language::{LanguagePack, RerankKeywords};
 use crate::scoring;
+use async_trait::async_trait;
 use chrono::Utc;
 use nanors_core::memory::{MemoryItem, SalienceScore};
+use nanors_core::retrieval::BoostWeights;
 use rayon::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Candidates a model reranker scores by default.
+const DEFAULT_MAX_CANDIDATES: usize = 20;
 
 /// Trait for reranking search results.
+#[async_trait]
 pub trait Reranker: Send + Sync {
     /// Rerank search results based on the query.
     ///
     /// # Arguments
     /// * `results` - Search results to rerank
     /// * `query_text` - Original query text
+    /// * `lexical` - BM25 similarity (0.0-1.0) to the query of the results
+    ///   matching it, by memory id
     ///
     /// # Returns
     /// Reranked results with adjusted scores
-    fn rerank(
+    async fn rerank(
         &self,
         results: Vec<SalienceScore<MemoryItem>>,
         query_text: &str,
+        lexical: &HashMap<Uuid, f64>,
     ) -> Vec<SalienceScore<MemoryItem>>;
 }
 
-/// Keyword definitions for different question types.
-const QUESTION_KEYWORDS: &[(&str, &[&str])] = &[
-    (
-        "profile",
-        &[
-            "用户", "user", "类型", "type", "角色", "role", "身份", "identity",
-        ],
-    ),
-    (
-        "location",
-        &[
-            "住", "居住", "位置", "location", "地点", "place", "城市", "city", "地址", "address",
-        ],
-    ),
-    (
-        "preference",
-        &[
-            "喜欢",
-            "爱",
-            "偏好",
-            "prefer",
-            "爱好",
-            "hobby",
-            "感兴趣",
-            "interest",
-        ],
-    ),
-    (
-        "count",
-        &["个", "只", "次", "数量", "count", "number", "total", "一共"],
-    ),
-    (
-        "profession",
-        &[
-            "工作",
-            "就职",
-            "公司",
-            "company",
-            "work",
-            "job",
-            "职业",
-            "profession",
-            "工程师",
-            "engineer",
-            "开发",
-            "developer",
-        ],
-    ),
-];
+#[async_trait]
+impl<T: Reranker + ?Sized> Reranker for Box<T> {
+    async fn rerank(
+        &self,
+        results: Vec<SalienceScore<MemoryItem>>,
+        query_text: &str,
+        lexical: &HashMap<Uuid, f64>,
+    ) -> Vec<SalienceScore<MemoryItem>> {
+        (**self).rerank(results, query_text, lexical).await
+    }
+}
+
+/// The `limit` highest-scoring results, best first, which a model reranker
+/// sends to its model, and the rest, which it returns unchanged after them.
+fn top_candidates(
+    mut results: Vec<SalienceScore<MemoryItem>>,
+    limit: usize,
+) -> (
+    Vec<SalienceScore<MemoryItem>>,
+    Vec<SalienceScore<MemoryItem>>,
+) {
+    results.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+    let rest = results.split_off(limit.min(results.len()));
+    (results, rest)
+}
+
+/// `head` followed by `rest`.
+fn with_rest(
+    mut head: Vec<SalienceScore<MemoryItem>>,
+    rest: Vec<SalienceScore<MemoryItem>>,
+) -> Vec<SalienceScore<MemoryItem>> {
+    head.extend(rest);
+    head
+}
+
+/// Replace each result's similarity with the model's `relevance` (0.0-1.0)
+/// and recompute its salience from it, so ranking follows the model.
+fn apply_relevance(
+    results: Vec<SalienceScore<MemoryItem>>,
+    relevance: &[f64],
+) -> Vec<SalienceScore<MemoryItem>> {
+    let now = Utc::now();
+    results
+        .into_iter()
+        .zip(relevance)
+        .map(|(result, &relevance)| {
+            let relevance = relevance.clamp(0.0, 1.0);
+            SalienceScore {
+                score: scoring::compute_salience(
+                    relevance,
+                    result.item.reinforcement_count,
+                    result.item.happened_at,
+                    now,
+                ),
+                similarity: relevance,
+                item: result.item,
+            }
+        })
+        .collect()
+}
 
 /// Rule-based reranker that applies question-type-specific boosts.
 ///
@@ -83,53 +114,75 @@ const QUESTION_KEYWORDS: &[(&str, &[&str])] = &[
 /// (<1ms) while providing 5-15% improvement in search accuracy.
 pub struct RuleBasedReranker {
     question_detector: QuestionTypeDetector,
-    keyword_boost_weight: f64,
-    recency_boost_weight: f64,
-    profile_boost_weight: f64,
+    /// Keywords boosted per question type, lowercased
+    keywords: RerankKeywords,
+    /// Words marking a memory as a question
+    question_words: Vec<String>,
+    boosts: BoostWeights,
 }
 
 impl RuleBasedReranker {
-    /// Create a new rule-based reranker with default weights.
+    /// Create a new rule-based reranker with default weights and the
+    /// built-in language packs.
     #[must_use]
     pub fn new() -> Self {
+        Self::with_config(BoostWeights::default(), &LanguagePack::default())
+    }
+
+    /// Create a rule-based reranker with `boosts`, detecting questions and
+    /// matching keywords with `language`.
+    #[must_use]
+    pub fn with_config(boosts: BoostWeights, language: &LanguagePack) -> Self {
+        let lowercase = |words: &[String]| -> Vec<String> {
+            words.iter().map(|word| word.to_lowercase()).collect()
+        };
+        let keywords = &language.rerank_keywords;
         Self {
-            question_detector: QuestionTypeDetector::with_defaults(),
-            keyword_boost_weight: 0.2,
-            recency_boost_weight: 0.15,
-            profile_boost_weight: 0.25,
+            question_detector: QuestionTypeDetector::new(language.detector_config()),
+            keywords: RerankKeywords {
+                profile: lowercase(&keywords.profile),
+                location: lowercase(&keywords.location),
+                preference: lowercase(&keywords.preference),
+                count: lowercase(&keywords.count),
+                profession: lowercase(&keywords.profession),
+            },
+            question_words: language.question_words.clone(),
+            boosts,
         }
     }
 
-    /// Compute boost based on question type.
-    fn compute_boost(&self, item: &MemoryItem, question_type: QuestionType) -> f64 {
+    /// Compute boost based on question type; `lexical` is the item's BM25
+    /// similarity to the query.
+    fn compute_boost(&self, item: &MemoryItem, question_type: QuestionType, lexical: f64) -> f64 {
         let summary_lower = item.summary.to_lowercase();
 
         match question_type {
             QuestionType::WhatKind => {
                 // Profile + Profession
-                (Self::get_keyword_match_count(&summary_lower, QUESTION_KEYWORDS[0].1) as f64)
+                (Self::get_keyword_match_count(&summary_lower, &self.keywords.profile) as f64)
                     .mul_add(
-                        self.profile_boost_weight,
-                        Self::get_keyword_match_count(&summary_lower, QUESTION_KEYWORDS[4].1)
+                        self.boosts.profile,
+                        Self::get_keyword_match_count(&summary_lower, &self.keywords.profession)
                             as f64
-                            * self.keyword_boost_weight
-                            * 1.2,
+                            * self.boosts.keyword
+                            * self.boosts.profession_factor,
                     )
             }
             QuestionType::Where => {
                 // Location (based on match count)
-                let count = Self::get_keyword_match_count(&summary_lower, QUESTION_KEYWORDS[1].1);
-                (count as f64) * self.keyword_boost_weight
+                let count = Self::get_keyword_match_count(&summary_lower, &self.keywords.location);
+                (count as f64) * self.boosts.keyword
             }
             QuestionType::Preference => {
                 // Preference (higher weight)
-                let count = Self::get_keyword_match_count(&summary_lower, QUESTION_KEYWORDS[2].1);
-                (count as f64) * self.keyword_boost_weight * 1.5
+                let count =
+                    Self::get_keyword_match_count(&summary_lower, &self.keywords.preference);
+                (count as f64) * self.boosts.keyword * self.boosts.preference_factor
             }
             QuestionType::HowMany => {
                 // Count
-                let count = Self::get_keyword_match_count(&summary_lower, QUESTION_KEYWORDS[3].1);
-                (count as f64) * self.keyword_boost_weight
+                let count = Self::get_keyword_match_count(&summary_lower, &self.keywords.count);
+                (count as f64) * self.boosts.keyword
             }
             QuestionType::Recency => {
                 // Recency decay
@@ -140,21 +193,18 @@ impl RuleBasedReranker {
             | QuestionType::Can
             | QuestionType::Update
             | QuestionType::Generic => {
-                // Generic keyword overlap
-                let keyword_overlap =
-                    scoring::keyword_overlap(&item.summary, summary_lower.as_str());
-                if keyword_overlap > 0.3 {
-                    self.keyword_boost_weight
-                } else {
-                    0.0
-                }
+                // Words shared with the query
+                lexical * self.boosts.keyword
             }
         }
     }
 
     /// Get keyword match count for a keyword list.
-    fn get_keyword_match_count(summary: &str, keywords: &[&str]) -> usize {
-        keywords.iter().filter(|k| summary.contains(*k)).count()
+    fn get_keyword_match_count(summary: &str, keywords: &[String]) -> usize {
+        keywords
+            .iter()
+            .filter(|k| summary.contains(k.as_str()))
+            .count()
     }
 
     /// Calculate recency boost using exponential decay.
@@ -164,12 +214,18 @@ impl RuleBasedReranker {
         let hours_ago = (Utc::now() - item.happened_at).num_hours().max(0) as f64;
         // Exponential decay: 24 hours = full boost, decays over time
         let decay = 24.0 / (hours_ago + 24.0);
-        decay * self.recency_boost_weight
+        decay * self.boosts.recency
     }
 
     /// Apply question-type-specific boost to a single result.
-    fn apply_boost(&self, result: &mut SalienceScore<MemoryItem>, question_type: QuestionType) {
-        let boost = self.compute_boost(&result.item, question_type);
+    fn apply_boost(
+        &self,
+        result: &mut SalienceScore<MemoryItem>,
+        question_type: QuestionType,
+        lexical: &HashMap<Uuid, f64>,
+    ) {
+        let lexical = lexical.get(&result.item.id).copied().unwrap_or_default();
+        let boost = self.compute_boost(&result.item, question_type, lexical);
 
         // Apply boost multiplicatively: score *= (1 + boost)
         if boost > 0.0 {
@@ -184,25 +240,29 @@ impl Default for RuleBasedReranker {
     }
 }
 
+#[async_trait]
 impl Reranker for RuleBasedReranker {
-    fn rerank(
+    async fn rerank(
         &self,
         mut results: Vec<SalienceScore<MemoryItem>>,
         query_text: &str,
+        lexical: &HashMap<Uuid, f64>,
     ) -> Vec<SalienceScore<MemoryItem>> {
         // Detect question type
         let question_type = self.question_detector.detect(query_text);
 
         // Apply question-type-specific boosts
         for result in &mut results {
-            self.apply_boost(result, question_type);
+            self.apply_boost(result, question_type, lexical);
         }
 
         // Re-sort by adjusted scores
         results.par_sort_unstable_by(|a, b| {
             // Primary: Facts (no question keywords) > Questions (with question keywords)
-            let a_is_question = scoring::count_question_keywords(&a.item.summary) > 0;
-            let b_is_question = scoring::count_question_keywords(&b.item.summary) > 0;
+            let a_is_question =
+                scoring::count_question_keywords(&a.item.summary, &self.question_words) > 0;
+            let b_is_question =
+                scoring::count_question_keywords(&b.item.summary, &self.question_words) > 0;
             match (!a_is_question, !b_is_question) {
                 (true, false) => return std::cmp::Ordering::Less,
                 (false, true) => return std::cmp::Ordering::Greater,
@@ -221,7 +281,7 @@ impl Reranker for RuleBasedReranker {
 mod tests {
     use super::*;
     use chrono::Duration;
-    use nanors_core::memory::MemoryType;
+    use nanors_core::memory::{MemoryType, VersionRelation};
 
     fn create_test_memory(summary: &str, hours_ago: i64) -> MemoryItem {
         MemoryItem {
@@ -233,6 +293,9 @@ mod tests {
             extra: None,
             content_hash: "test".to_string(),
             reinforcement_count: 1,
+            previous_version_id: None,
+            superseded_at: None,
+            version_relation: VersionRelation::Sets,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -251,7 +314,7 @@ mod tests {
         let reranker = RuleBasedReranker::new();
 
         let profile_memory = create_test_memory("User: 我是Android用户", 1);
-        let boost = reranker.compute_boost(&profile_memory, QuestionType::WhatKind);
+        let boost = reranker.compute_boost(&profile_memory, QuestionType::WhatKind, 0.0);
 
         assert!(boost > 0.0, "Profile memory should get boost");
     }
@@ -261,12 +324,12 @@ mod tests {
         let reranker = RuleBasedReranker::new();
 
         let location_memory = create_test_memory("User: 我住在西城区", 1);
-        let boost = reranker.compute_boost(&location_memory, QuestionType::Where);
+        let boost = reranker.compute_boost(&location_memory, QuestionType::Where, 0.0);
 
         assert!(boost > 0.0, "Location memory should get boost");
 
         let multi_location = create_test_memory("User: 我居住在北京这个位置", 1);
-        let boost_multi = reranker.compute_boost(&multi_location, QuestionType::Where);
+        let boost_multi = reranker.compute_boost(&multi_location, QuestionType::Where, 0.0);
 
         assert!(
             boost_multi > boost,
@@ -279,10 +342,10 @@ mod tests {
         let reranker = RuleBasedReranker::new();
 
         let recent = create_test_memory("User: 测试", 1);
-        let boost_recent = reranker.compute_boost(&recent, QuestionType::Recency);
+        let boost_recent = reranker.compute_boost(&recent, QuestionType::Recency, 0.0);
 
         let old = create_test_memory("User: 测试", 100);
-        let boost_old = reranker.compute_boost(&old, QuestionType::Recency);
+        let boost_old = reranker.compute_boost(&old, QuestionType::Recency, 0.0);
 
         assert!(
             boost_recent > boost_old,
@@ -295,12 +358,12 @@ mod tests {
         let reranker = RuleBasedReranker::new();
 
         let preference_memory = create_test_memory("User: 我喜欢红色", 1);
-        let boost = reranker.compute_boost(&preference_memory, QuestionType::Preference);
+        let boost = reranker.compute_boost(&preference_memory, QuestionType::Preference, 0.0);
 
         assert!(boost > 0.0, "Preference memory should get boost");
 
         let normal_memory = create_test_memory("今天天气很好", 1);
-        let boost_normal = reranker.compute_boost(&normal_memory, QuestionType::Preference);
+        let boost_normal = reranker.compute_boost(&normal_memory, QuestionType::Preference, 0.0);
 
         assert!(
             boost_normal <= 0.0,
@@ -309,7 +372,26 @@ mod tests {
     }
 
     #[test]
-    fn test_rerank_preserves_facts_priority() {
+    fn test_with_config_uses_pack_keywords_and_weights() {
+        let mut language = LanguagePack::en();
+        language.rerank_keywords.location = vec!["Wohne".to_string()];
+        let boosts = BoostWeights {
+            keyword: 0.5,
+            ..BoostWeights::default()
+        };
+        let reranker = RuleBasedReranker::with_config(boosts, &language);
+
+        let memory = create_test_memory("User: ich wohne in Berlin", 1);
+        let boost = reranker.compute_boost(&memory, QuestionType::Where, 0.0);
+        assert!((boost - 0.5).abs() < 1e-9);
+
+        // The Chinese keywords are not loaded
+        let chinese = create_test_memory("User: 我住在西城区", 1);
+        assert!(reranker.compute_boost(&chinese, QuestionType::Where, 0.0) <= 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_rerank_preserves_facts_priority() {
         let reranker = RuleBasedReranker::new();
 
         let mut results = vec![
@@ -318,7 +400,7 @@ mod tests {
             create_test_score("这是什么", 1, 0.6),         // Question
         ];
 
-        results = reranker.rerank(results, "我住哪");
+        results = reranker.rerank(results, "我住哪", &HashMap::new()).await;
 
         // First result should be fact (answer), not question
         assert!(
@@ -327,8 +409,29 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_rerank_with_recency_question() {
+    #[tokio::test]
+    async fn test_rerank_boosts_lexical_matches_on_other_questions() {
+        let reranker = RuleBasedReranker::new();
+        let query = "tell me about green tea";
+        assert_eq!(
+            reranker.question_detector.detect(query),
+            QuestionType::Generic
+        );
+
+        let results = vec![
+            create_test_score("User: I went hiking", 1, 0.7),
+            create_test_score("User: I drink green tea", 1, 0.7),
+        ];
+        let lexical = HashMap::from([(results[1].item.id, 1.0)]);
+
+        let results = reranker.rerank(results, query, &lexical).await;
+
+        assert_eq!(results[0].item.summary, "User: I drink green tea");
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[tokio::test]
+    async fn test_rerank_with_recency_question() {
         let reranker = RuleBasedReranker::new();
 
         let mut results = vec![
@@ -336,7 +439,9 @@ mod tests {
             create_test_score("User: 用户类型B", 1, 0.7),   // Recent, same initial score
         ];
 
-        results = reranker.rerank(results, "我最新的用户类型是什么");
+        results = reranker
+            .rerank(results, "我最新的用户类型是什么", &HashMap::new())
+            .await;
 
         // Recent memory should rank higher for recency question
         assert!(
@@ -344,4 +449,23 @@ mod tests {
             "Recent memory should rank higher for recency question"
         );
     }
+
+    #[test]
+    fn test_apply_relevance_follows_the_model() {
+        let (results, rest) = top_candidates(
+            vec![
+                create_test_score("User: 我住在西城区", 1, 0.9),
+                create_test_score("User: 我喜欢红色", 1, 0.5),
+                create_test_score("User: 今天天气很好", 1, 0.1),
+            ],
+            2,
+        );
+        let results = apply_relevance(results, &[0.1, 1.5]);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(rest.len(), 1);
+        assert!((results[0].similarity - 0.1).abs() < 1e-9);
+        assert!((results[1].similarity - 1.0).abs() < 1e-9);
+        assert!(results[1].score > results[0].score);
+    }
 }
@@ -1,65 +1,4 @@
 use chrono::{DateTime, Utc};
-use std::collections::HashSet;
-
-/// Compute keyword overlap score between two strings using character-level bigrams.
-///
-/// This is a simple approximation of text similarity that helps match questions
-/// with answers when vector similarity alone is insufficient.
-///
-/// Returns 0.0 if either string is empty.
-#[must_use]
-pub fn keyword_overlap(a: &str, b: &str) -> f64 {
-    if a.is_empty() || b.is_empty() {
-        return 0.0;
-    }
-
-    // Extract character bigrams from both strings using &str to avoid allocations
-    let bigrams_a: BigramSet<'_> = BigramSet::from_str(a);
-    let bigrams_b: BigramSet<'_> = BigramSet::from_str(b);
-
-    if bigrams_a.0.is_empty() || bigrams_b.0.is_empty() {
-        return 0.0;
-    }
-
-    // Jaccard similarity: |A ∩ B| / |A ∪ B|
-    let intersection = bigrams_a.0.intersection(&bigrams_b.0).count();
-    let union = bigrams_a.0.union(&bigrams_b.0).count();
-
-    if union == 0 {
-        return 0.0;
-    }
-
-    intersection as f64 / union as f64
-}
-
-/// Wrapper for a set of bigram string slices.
-///
-/// Using a newtype pattern avoids allocating String for each bigram,
-/// instead borrowing slices from the original text.
-struct BigramSet<'a>(HashSet<&'a str>);
-
-impl<'a> BigramSet<'a> {
-    fn from_str(text: &'a str) -> Self {
-        let bytes = text.as_bytes();
-        let bigrams: HashSet<&'a str> = bytes
-            .windows(2)
-            .filter_map(|w| std::str::from_utf8(w).ok())
-            .collect();
-        Self(bigrams)
-    }
-}
-
-/// Compute hybrid similarity score combining vector and keyword similarity.
-///
-/// Formula: `vector_sim * 0.7 + keyword_overlap * 0.3`
-///
-/// This gives 70% weight to semantic vector similarity and 30% to keyword overlap.
-/// The keyword overlap helps match Q&A pairs where the semantic meaning differs
-/// but key terms are shared (e.g., "What is my phone?" vs "My phone is `OnePlus` 13").
-#[must_use]
-pub fn hybrid_similarity(vector_sim: f64, keyword_overlap: f64) -> f64 {
-    vector_sim.mul_add(0.7, keyword_overlap * 0.3)
-}
 
 /// Compute cosine similarity between two embedding vectors.
 ///
@@ -90,42 +29,18 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
     dot / denom
 }
 
-/// Chinese question keywords that indicate a question rather than an answer.
-/// When these appear in memory, and the query also contains them, the memory
-/// should be penalized to avoid retrieving questions instead of answers.
-const QUESTION_KEYWORDS: &[&str] = &[
-    // Standard question words
-    "什么",
-    "谁",
-    "多少",
-    "怎么",
-    "如何",
-    "为什么",
-    "干啥",
-    "啥",
-    "吗",
-    "是不是",
-    "是否",
-    "呢",
-    "哪",
-    // Colloquial question words (use compound words to avoid substring overlap)
-    "咋",
-    // Time/quantity questions
-    "几",
-    "几时",
-    "何时",
-];
-
-/// Detect if text contains Chinese question keywords.
+/// Count the question words (see [`LanguagePack`]) found in `text`.
 ///
-/// Returns the count of unique question keywords found in the text.
+/// Returns the count of unique question words found in the text.
 /// This is used to penalize memories that are questions when the user's
 /// query is also a question.
+///
+/// [`LanguagePack`]: crate::query::language::LanguagePack
 #[must_use]
-pub fn count_question_keywords(text: &str) -> usize {
-    QUESTION_KEYWORDS
+pub fn count_question_keywords<S: AsRef<str>>(text: &str, words: &[S]) -> usize {
+    words
         .iter()
-        .filter(|keyword| text.contains(**keyword))
+        .filter(|word| text.contains(word.as_ref()))
         .count()
 }
 
@@ -134,14 +49,14 @@ pub fn count_question_keywords(text: &str) -> usize {
 /// When both the query and the memory contain question keywords, we heavily penalize
 /// the memory to avoid returning questions instead of answers.
 ///
-/// Penalty formula: `1.0 - (query_question_count * memory_question_count) * 0.50`
+/// Penalty formula: `1.0 - (query_question_count * memory_question_count) * penalty`
 ///
 /// This ensures that:
 /// - If neither contains question keywords, no penalty (factor = 1.0)
 /// - If only one contains question keywords, no penalty (factor = 1.0)
-/// - If both contain question keywords, 50% penalty per matching pair
+/// - If both contain question keywords, `penalty` per matching pair
 ///
-/// # Examples
+/// # Examples (with a `penalty` of 0.5)
 /// - Query "我住哪" (1 keyword) vs Memory "我住西城区" (0 keywords): factor = 1.0
 /// - Query "我住哪" (1 keyword) vs Memory "你住哪里" (1 keyword): factor = 0.50
 /// - Query "我住在哪里呢" (2 keywords) vs Memory "你住哪" (1 keyword): factor = 0.0
@@ -149,19 +64,22 @@ pub fn count_question_keywords(text: &str) -> usize {
 /// The heavy penalty ensures that answers (which typically don't contain question
 /// keywords) rank much higher than questions when the user asks a question.
 #[must_use]
-pub fn question_penalty(query_text: &str, memory_text: &str) -> f64 {
-    let query_count = count_question_keywords(query_text);
-    let memory_count = count_question_keywords(memory_text);
+pub fn question_penalty<S: AsRef<str>>(
+    query_text: &str,
+    memory_text: &str,
+    words: &[S],
+    penalty: f64,
+) -> f64 {
+    let query_count = count_question_keywords(query_text, words);
+    let memory_count = count_question_keywords(memory_text, words);
 
     // Only apply penalty if both contain question keywords
     if query_count == 0 || memory_count == 0 {
         return 1.0;
     }
 
-    // Heavy penalty: 50% reduction per matching pair of question keywords
-    // This ensures answers rank much higher than questions
-    let penalty = query_count * memory_count * 50;
-    (1.0 - penalty.min(100) as f64 / 100.0).max(0.0)
+    let pairs = (query_count * memory_count) as f64;
+    pairs.mul_add(-penalty, 1.0).clamp(0.0, 1.0)
 }
 
 /// Compute salience score for a memory item.
@@ -193,6 +111,15 @@ pub fn compute_salience(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::language::ZH_QUESTION_WORDS;
+
+    fn count_question_keywords(text: &str) -> usize {
+        super::count_question_keywords(text, ZH_QUESTION_WORDS)
+    }
+
+    fn question_penalty(query_text: &str, memory_text: &str) -> f64 {
+        super::question_penalty(query_text, memory_text, ZH_QUESTION_WORDS, 0.5)
+    }
 
     #[test]
     fn identical_vectors_similarity_one() {
@@ -8,27 +8,31 @@ use nanors_entities::sessions;
 
 #[async_trait]
 impl<R: crate::rerank::Reranker> SessionStorage for MemoryManager<R> {
-    async fn get_or_create(&self, id: &Uuid) -> anyhow::Result<Session> {
-        let session_model = sessions::Entity::find_by_id(*id).one(&self.db).await?;
+    async fn get(&self, id: &Uuid) -> anyhow::Result<Option<Session>> {
+        let Some(model) = sessions::Entity::find_by_id(*id).one(&self.db).await? else {
+            return Ok(None);
+        };
+        let messages: Vec<ChatMessage> = serde_json::from_str(&model.messages)?;
 
-        if let Some(model) = session_model {
-            let messages: Vec<ChatMessage> = serde_json::from_str(&model.messages)?;
+        Ok(Some(Session {
+            id: model.id,
+            messages,
+            created_at: model.created_at.and_utc(),
+            updated_at: model.updated_at.and_utc(),
+        }))
+    }
 
-            Ok(Session {
-                id: model.id,
-                messages,
-                created_at: model.created_at.and_utc(),
-                updated_at: model.updated_at.and_utc(),
-            })
-        } else {
-            let now = chrono::Utc::now();
-            Ok(Session {
-                id: *id,
-                messages: vec![],
-                created_at: now,
-                updated_at: now,
-            })
+    async fn get_or_create(&self, id: &Uuid) -> anyhow::Result<Session> {
+        if let Some(session) = self.get(id).await? {
+            return Ok(session);
         }
+        let now = chrono::Utc::now();
+        Ok(Session {
+            id: *id,
+            messages: vec![],
+            created_at: now,
+            updated_at: now,
+        })
     }
 
     async fn add_message(&self, id: &Uuid, role: Role, content: &str) -> anyhow::Result<()> {
@@ -0,0 +1,411 @@
+//! In-process vector index for backends without native vector search.
+//!
+//! One [`Hnsw`] graph per memory scope, built from the stored embeddings and
+//! kept current on insert, update and delete. For file-backed `SQLite`
+//! databases the graphs are persisted to a sidecar file next to the
+//! database (`nanors.db` → `nanors.db.hnsw`), so startup only indexes what
+//! changed since the last run.
+//!
+//! Several processes may share one database (e.g. the bot and `mcp-serve`).
+//! Each keeps its own graphs, picks up the others' writes before searching
+//! (`MemoryManager` refreshes from `updated_at`) and saves the whole index
+//! to the sidecar. There is no lock: the last process to save wins, and the
+//! sidecar is only a cache, so whatever it lacks is re-indexed by the next
+//! [`sync`](VectorIndex::sync).
+//!
+//! Compaction and saving run on the blocking thread pool, one flush at a
+//! time, so index changes on the async path never wait for the disk.
+
+use nanors_entities::memory_items;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::convert;
+use crate::hnsw::Hnsw;
+
+/// Sidecar format version; files with another version are rebuilt.
+const SIDECAR_FORMAT: u32 = 1;
+
+/// Write the sidecar after this many index changes.
+const SAVE_EVERY: usize = 64;
+
+/// Rows fetched per query while indexing (below the `SQLite` variable limit).
+const FETCH_CHUNK: usize = 500;
+
+#[derive(Deserialize)]
+struct Sidecar {
+    format: u32,
+    scopes: HashMap<String, Hnsw>,
+}
+
+/// [`Sidecar`] borrowing the graphs, so saving does not copy them.
+#[derive(Serialize)]
+struct SidecarRef<'a> {
+    format: u32,
+    scopes: &'a HashMap<String, Hnsw>,
+}
+
+/// Per-scope HNSW graphs with an optional sidecar file.
+pub struct VectorIndex {
+    shared: Arc<Shared>,
+}
+
+/// State shared with background flushes.
+struct Shared {
+    scopes: RwLock<HashMap<String, Hnsw>>,
+    sidecar: Option<PathBuf>,
+    /// Changes not yet written to the sidecar
+    dirty: AtomicUsize,
+    /// A background flush is queued or running
+    flushing: AtomicBool,
+    /// Held while writing the sidecar, so saves don't share the temporary file
+    saving: Mutex<()>,
+}
+
+/// Sidecar location for a database URL: file-backed `SQLite` only.
+#[must_use]
+pub fn sidecar_path(database_url: &str) -> Option<PathBuf> {
+    let mut path = nanors_migration::sqlite_file(database_url)?.into_os_string();
+    path.push(".hnsw");
+    Some(PathBuf::from(path))
+}
+
+/// Index version for a memory row (milliseconds keep it stable across the
+/// timestamp precision of each backend).
+#[must_use]
+pub fn version_of(updated_at: impl Into<chrono::DateTime<chrono::Utc>>) -> i64 {
+    updated_at.into().timestamp_millis()
+}
+
+impl VectorIndex {
+    /// Open the index, starting from the sidecar file when there is one.
+    ///
+    /// A missing or unreadable sidecar just means a full rebuild in
+    /// [`sync`](Self::sync).
+    #[must_use]
+    pub fn open(sidecar: Option<PathBuf>) -> Self {
+        let scopes = sidecar
+            .as_deref()
+            .filter(|path| path.exists())
+            .and_then(|path| match read_sidecar(path) {
+                Ok(scopes) => Some(scopes),
+                Err(e) => {
+                    warn!("Ignoring vector index {}: {e}", path.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            shared: Arc::new(Shared {
+                scopes: RwLock::new(scopes),
+                sidecar,
+                dirty: AtomicUsize::new(0),
+                flushing: AtomicBool::new(false),
+                saving: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// Bring the index in line with the database: drop vectors of deleted,
+    /// superseded or changed memories and index everything missing.
+    pub async fn sync(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let rows: Vec<(Uuid, String, DateTimeWithTimeZone)> = memory_items::Entity::find()
+            .select_only()
+            .column(memory_items::Column::Id)
+            .column(memory_items::Column::UserScope)
+            .column(memory_items::Column::UpdatedAt)
+            .filter(memory_items::Column::Embedding.is_not_null())
+            .filter(memory_items::Column::SupersededAt.is_null())
+            .into_tuple()
+            .all(db)
+            .await?;
+        let stored: HashMap<Uuid, (String, i64)> = rows
+            .into_iter()
+            .map(|(id, scope, updated_at)| (id, (scope, version_of(updated_at))))
+            .collect();
+
+        let (removed, missing) = self.diff(&stored);
+        for chunk in missing.chunks(FETCH_CHUNK) {
+            let models = memory_items::Entity::find()
+                .filter(memory_items::Column::Id.is_in(chunk.iter().copied()))
+                .all(db)
+                .await?;
+            for model in models {
+                let scope = model.user_scope.clone();
+                let item = convert::memory_item_from_model(model);
+                self.upsert(
+                    &scope,
+                    item.id,
+                    version_of(item.updated_at),
+                    item.embedding.as_deref(),
+                );
+            }
+        }
+
+        let changed = removed + missing.len() > 0;
+        let shared = Arc::clone(&self.shared);
+        tokio::task::spawn_blocking(move || {
+            shared.compact();
+            if changed { shared.save() } else { Ok(()) }
+        })
+        .await??;
+
+        info!(
+            "Vector index ready: {} vectors ({} newly indexed, {removed} dropped)",
+            self.len(),
+            missing.len()
+        );
+        Ok(())
+    }
+
+    /// Remove stale entries; return how many were removed and which stored
+    /// ids still need indexing.
+    fn diff(&self, stored: &HashMap<Uuid, (String, i64)>) -> (usize, Vec<Uuid>) {
+        let Ok(mut scopes) = self.shared.scopes.write() else {
+            return (0, stored.keys().copied().collect());
+        };
+        let mut removed = 0;
+        for (scope, graph) in scopes.iter_mut() {
+            let stale: Vec<Uuid> = graph
+                .entries()
+                .filter(|(id, version)| stored.get(id) != Some(&(scope.clone(), *version)))
+                .map(|(id, _)| id)
+                .collect();
+            for id in &stale {
+                graph.remove(id);
+            }
+            removed += stale.len();
+        }
+        let missing = stored
+            .iter()
+            .filter(|(id, (scope, _))| scopes.get(scope).is_none_or(|g| g.version(id).is_none()))
+            .map(|(id, _)| *id)
+            .collect();
+        (removed, missing)
+    }
+
+    /// Number of indexed vectors across all scopes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shared
+            .scopes
+            .read()
+            .map_or(0, |scopes| scopes.values().map(Hnsw::len).sum())
+    }
+
+    /// Index (or re-index) a memory's embedding; `None` removes it.
+    pub fn upsert(&self, scope: &str, id: Uuid, version: i64, embedding: Option<&[f32]>) {
+        let Some(embedding) = embedding else {
+            self.remove(scope, &id);
+            return;
+        };
+        if let Ok(mut scopes) = self.shared.scopes.write() {
+            scopes
+                .entry(scope.to_string())
+                .or_default()
+                .insert(id, version, embedding);
+        }
+        self.changed();
+    }
+
+    /// Indexed version of a memory, if it is indexed.
+    #[must_use]
+    pub fn version(&self, scope: &str, id: &Uuid) -> Option<i64> {
+        self.shared
+            .scopes
+            .read()
+            .ok()
+            .and_then(|scopes| scopes.get(scope).and_then(|graph| graph.version(id)))
+    }
+
+    /// Drop a memory from the index.
+    pub fn remove(&self, scope: &str, id: &Uuid) {
+        let removed = self
+            .shared
+            .scopes
+            .write()
+            .is_ok_and(|mut scopes| scopes.get_mut(scope).is_some_and(|g| g.remove(id)));
+        if removed {
+            self.changed();
+        }
+    }
+
+    /// Up to `k` memory ids in `scope` nearest to `query`, nearest first.
+    #[must_use]
+    pub fn search(&self, scope: &str, query: &[f32], k: usize) -> Vec<Uuid> {
+        self.shared
+            .scopes
+            .read()
+            .ok()
+            .and_then(|scopes| {
+                scopes.get(scope).map(|graph| {
+                    graph
+                        .search(query, k)
+                        .into_iter()
+                        .map(|(id, _)| id)
+                        .collect()
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Compact and save in the background every `SAVE_EVERY` changes.
+    fn changed(&self) {
+        let dirty = self.shared.dirty.fetch_add(1, Ordering::Relaxed) + 1;
+        if dirty < SAVE_EVERY || self.shared.flushing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let shared = Arc::clone(&self.shared);
+        let flush = move || {
+            shared.compact();
+            if let Err(e) = shared.save() {
+                warn!("Failed to save vector index: {e}");
+            }
+            shared.flushing.store(false, Ordering::Release);
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(flush)),
+            Err(_) => flush(),
+        }
+    }
+}
+
+impl Shared {
+    /// Write the sidecar file, if the index has one.
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.sidecar else {
+            return Ok(());
+        };
+        let _saving = self
+            .saving
+            .lock()
+            .map_err(|_| anyhow::anyhow!("vector index lock poisoned"))?;
+        let scopes = self
+            .scopes
+            .read()
+            .map_err(|_| anyhow::anyhow!("vector index lock poisoned"))?;
+        // Changes made after this snapshot count towards the next save
+        let saved = self.dirty.load(Ordering::Relaxed);
+        let bytes = bincode::serialize(&SidecarRef {
+            format: SIDECAR_FORMAT,
+            scopes: &scopes,
+        })?;
+        drop(scopes);
+        // Write then rename, so a crash never leaves a truncated sidecar;
+        // one temporary file per process, so concurrent saves don't mix
+        let tmp = path.with_extension(format!("hnsw.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+        self.dirty.fetch_sub(saved, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Rebuild graphs that are mostly deleted nodes.
+    fn compact(&self) {
+        if let Ok(mut scopes) = self.scopes.write() {
+            scopes.retain(|_, graph| !graph.is_empty());
+            for graph in scopes.values_mut() {
+                if graph.needs_compaction() {
+                    *graph = graph.compacted();
+                }
+            }
+        }
+    }
+}
+
+impl Drop for VectorIndex {
+    /// Save what is left on shutdown; this last write is synchronous so it
+    /// completes before the process exits.
+    fn drop(&mut self) {
+        if self.shared.dirty.load(Ordering::Relaxed) > 0
+            && let Err(e) = self.shared.save()
+        {
+            warn!("Failed to save vector index: {e}");
+        }
+    }
+}
+
+fn read_sidecar(path: &Path) -> anyhow::Result<HashMap<String, Hnsw>> {
+    let sidecar: Sidecar = bincode::deserialize(&std::fs::read(path)?)?;
+    anyhow::ensure!(
+        sidecar.format == SIDECAR_FORMAT,
+        "unsupported format {}",
+        sidecar.format
+    );
+    Ok(sidecar.scopes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar_path_for_sqlite_files_only() {
+        assert_eq!(
+            sidecar_path("sqlite:///home/me/.nanors/nanors.db?mode=rwc"),
+            Some(PathBuf::from("/home/me/.nanors/nanors.db.hnsw"))
+        );
+        assert_eq!(
+            sidecar_path("sqlite:nanors.db"),
+            Some(PathBuf::from("nanors.db.hnsw"))
+        );
+        assert_eq!(sidecar_path("sqlite::memory:"), None);
+        assert_eq!(sidecar_path("sqlite://file.db?mode=memory"), None);
+        assert_eq!(sidecar_path("postgresql://localhost/nanors"), None);
+    }
+
+    #[test]
+    fn test_index_persists_to_sidecar() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("nanors_hnsw_{}", Uuid::now_v7()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("nanors.db.hnsw");
+        let (alice, bob) = (Uuid::now_v7(), Uuid::now_v7());
+
+        {
+            let index = VectorIndex::open(Some(path.clone()));
+            index.upsert("user:a", alice, 1, Some(&[1.0, 0.0, 0.0]));
+            index.upsert("user:b", bob, 1, Some(&[0.9, 0.1, 0.0]));
+        }
+        assert!(path.exists());
+
+        let index = VectorIndex::open(Some(path));
+        assert_eq!(index.search("user:a", &[1.0, 0.0, 0.0], 5), vec![alice]);
+        assert_eq!(index.search("user:b", &[1.0, 0.0, 0.0], 5), vec![bob]);
+        assert!(index.search("global", &[1.0, 0.0, 0.0], 5).is_empty());
+
+        index.upsert("user:a", alice, 2, None);
+        assert!(index.search("user:a", &[1.0, 0.0, 0.0], 5).is_empty());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_index_saves_in_the_background() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("nanors_hnsw_{}", Uuid::now_v7()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("nanors.db.hnsw");
+
+        let index = VectorIndex::open(Some(path.clone()));
+        for i in 0..u16::try_from(SAVE_EVERY)? {
+            index.upsert("global", Uuid::now_v7(), 1, Some(&[1.0, f32::from(i)]));
+        }
+        for _ in 0..100 {
+            if !index.shared.flushing.load(Ordering::Acquire) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(read_sidecar(&path)?["global"].len(), SAVE_EVERY);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
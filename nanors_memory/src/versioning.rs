@@ -0,0 +1,305 @@
+//! Append-only version chains for memory facts.
+//!
+//! A fact that changes gets a new row pointing at the version it supersedes
+//! (`previous_version_id`); the old row is kept and marked `superseded_at`.
+//! Retrieval reads current versions only, except for history questions
+//! ([`QuestionType::Update`]), which also get each result's earlier
+//! versions.
+
+use chrono::Utc;
+use nanors_core::MemoryItemRepo;
+use nanors_core::memory::{MemoryItem, MemoryScope, SalienceScore, VersionRelation};
+use nanors_entities::{memory_cards, memory_items};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
+use std::collections::HashSet;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::convert;
+use crate::dedup;
+use crate::manager::{MemoryManager, VectorSearch};
+use crate::query::detector::QuestionType;
+use crate::rerank::Reranker;
+
+/// Times `supersede` re-resolves the current version before giving up.
+const SUPERSEDE_ATTEMPTS: usize = 3;
+
+/// Phrases that withdraw a fact rather than replace it.
+const RETRACTION_MARKERS: &[&str] = &[
+    "不再",
+    "已经不",
+    "没有了",
+    "不是了",
+    "no longer",
+    "not anymore",
+    "anymore",
+];
+
+/// How `next` relates to `previous`, two versions of the same fact.
+#[must_use]
+pub fn relation_between(previous: &str, next: &str) -> VersionRelation {
+    let previous = strip_role(previous).trim().to_lowercase();
+    let next = strip_role(next).trim().to_lowercase();
+    if RETRACTION_MARKERS
+        .iter()
+        .any(|marker| next.contains(marker))
+    {
+        VersionRelation::Retracts
+    } else if !previous.is_empty() && next.len() > previous.len() && next.contains(&previous) {
+        VersionRelation::Extends
+    } else {
+        VersionRelation::Updates
+    }
+}
+
+fn strip_role(summary: &str) -> &str {
+    summary
+        .strip_prefix("User:")
+        .or_else(|| summary.strip_prefix("Assistant:"))
+        .unwrap_or(summary)
+}
+
+impl<R: Reranker> MemoryManager<R> {
+    /// Store `item` as the next version of `previous`, which stays stored
+    /// but is no longer current. Returns the new version's id.
+    ///
+    /// When another writer superseded `previous` first, `item` becomes the
+    /// next version of the fact's current head instead.
+    pub(crate) async fn supersede(
+        &self,
+        scope: &MemoryScope,
+        previous: &MemoryItem,
+        item: &MemoryItem,
+    ) -> anyhow::Result<Uuid> {
+        let scope = scope.to_string();
+        let mut previous = previous.clone();
+        for _ in 0..SUPERSEDE_ATTEMPTS {
+            let mut next = item.clone();
+            next.content_hash = dedup::content_hash(&item.memory_type.to_string(), &item.summary);
+            next.previous_version_id = Some(previous.id);
+            next.version_relation = relation_between(&previous.summary, &item.summary);
+            next.superseded_at = None;
+            next.updated_at = Utc::now();
+
+            // Both rows or neither: a failure in between would leave two
+            // current versions of the fact
+            let txn = self.db.begin().await?;
+            convert::memory_item_to_model(&scope, &next)
+                .insert(&txn)
+                .await?;
+            let marked = memory_items::Entity::update_many()
+                .col_expr(
+                    memory_items::Column::SupersededAt,
+                    Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+                )
+                .filter(memory_items::Column::Id.eq(previous.id))
+                .filter(memory_items::Column::SupersededAt.is_null())
+                .exec(&txn)
+                .await?;
+            if marked.rows_affected != 1 {
+                txn.rollback().await?;
+                previous = self
+                    .current_head(&scope, previous.id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("MemoryItem not found: {}", previous.id))?;
+                continue;
+            }
+            // Cards answer from current versions only
+            memory_cards::Entity::delete_many()
+                .filter(memory_cards::Column::SourceMemoryId.eq(previous.id))
+                .exec(&txn)
+                .await?;
+            txn.commit().await?;
+
+            self.lexical.remove(&scope, &previous.id);
+            if let VectorSearch::Index(index) = &self.vector_search {
+                index.remove(&scope, &previous.id);
+            }
+            self.index_item(&scope, &next).await?;
+            self.extract_cards(&scope, &next).await;
+
+            info!(
+                "Memory {} {} {}",
+                next.id, next.version_relation, previous.id
+            );
+            return Ok(next.id);
+        }
+        anyhow::bail!("memory {} kept changing while superseding it", previous.id)
+    }
+
+    /// Delete the current version `id` together with the versions it
+    /// superseded, and their cards.
+    pub(crate) async fn delete_chain(&self, scope: &MemoryScope, id: &Uuid) -> anyhow::Result<()> {
+        let ids: Vec<Uuid> = self
+            .history(scope, id)
+            .await?
+            .iter()
+            .map(|i| i.id)
+            .collect();
+        let txn = self.db.begin().await?;
+        memory_cards::Entity::delete_many()
+            .filter(memory_cards::Column::SourceMemoryId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        memory_items::Entity::delete_many()
+            .filter(memory_items::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        let scope = scope.to_string();
+        for id in &ids {
+            self.lexical.remove(&scope, id);
+            if let VectorSearch::Index(index) = &self.vector_search {
+                index.remove(&scope, id);
+            }
+        }
+        info!("Deleted memory {id} and {} earlier versions", ids.len() - 1);
+        Ok(())
+    }
+
+    /// The current version of the fact `id` is a version of, following
+    /// the chain forward. `None` when the chain was deleted.
+    async fn current_head(&self, scope: &str, id: Uuid) -> anyhow::Result<Option<MemoryItem>> {
+        let mut seen = HashSet::new();
+        let mut id = id;
+        while seen.insert(id) {
+            let Some(model) = memory_items::Entity::find_by_id(id)
+                .filter(memory_items::Column::UserScope.eq(scope))
+                .one(&self.db)
+                .await?
+            else {
+                return Ok(None);
+            };
+            if model.superseded_at.is_none() {
+                return Ok(Some(convert::memory_item_from_model(model)));
+            }
+            let Some(successor) = memory_items::Entity::find()
+                .filter(memory_items::Column::UserScope.eq(scope))
+                .filter(memory_items::Column::PreviousVersionId.eq(id))
+                .one(&self.db)
+                .await?
+            else {
+                return Ok(None);
+            };
+            id = successor.id;
+        }
+        Ok(None)
+    }
+
+    /// The memory `id` followed by the versions it superseded, newest
+    /// first. Empty when `id` is not in `scope`.
+    pub async fn history(&self, scope: &MemoryScope, id: &Uuid) -> anyhow::Result<Vec<MemoryItem>> {
+        let mut chain: Vec<MemoryItem> = Vec::new();
+        let mut next = Some(*id);
+        while let Some(id) = next {
+            if chain.iter().any(|item| item.id == id) {
+                break;
+            }
+            let Some(item) = MemoryItemRepo::find_by_id(self, scope, &id).await? else {
+                break;
+            };
+            next = item.previous_version_id;
+            chain.push(item);
+        }
+        Ok(chain)
+    }
+
+    /// Earlier versions of each result right after it, for history
+    /// questions; other queries get the results unchanged.
+    pub(crate) async fn with_history(
+        &self,
+        scope: &MemoryScope,
+        results: Vec<SalienceScore<MemoryItem>>,
+        query_text: &str,
+    ) -> Vec<SalienceScore<MemoryItem>> {
+        if !self
+            .question_detector
+            .detect_all(query_text)
+            .contains(&QuestionType::Update)
+        {
+            return results;
+        }
+
+        let mut seen: HashSet<Uuid> = results.iter().map(|s| s.item.id).collect();
+        let mut expanded = Vec::with_capacity(results.len());
+        for result in results {
+            let older = match self.history(scope, &result.item.id).await {
+                Ok(chain) => chain,
+                Err(e) => {
+                    warn!("Failed to load history of memory {}: {e}", result.item.id);
+                    Vec::new()
+                }
+            };
+            let (score, similarity) = (result.score, result.similarity);
+            expanded.push(result);
+            for item in older.into_iter().skip(1) {
+                if seen.insert(item.id) {
+                    expanded.push(SalienceScore {
+                        item,
+                        score,
+                        similarity,
+                    });
+                }
+            }
+        }
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relation_between() {
+        assert_eq!(
+            relation_between("User: 我住在丰台", "User: 我从丰台搬到了东城"),
+            VersionRelation::Updates
+        );
+        assert_eq!(
+            relation_between("User: 我养了一只猫", "User: 我养了一只猫，叫咪咪"),
+            VersionRelation::Extends
+        );
+        assert_eq!(
+            relation_between("User: 我住在东城", "User: 我不再住在东城了"),
+            VersionRelation::Retracts
+        );
+        assert_eq!(
+            relation_between("I work at Acme", "I no longer work at Acme"),
+            VersionRelation::Retracts
+        );
+    }
+
+    #[tokio::test]
+    async fn test_supersede_follows_a_concurrent_version() -> anyhow::Result<()> {
+        let manager =
+            MemoryManager::<crate::rerank::RuleBasedReranker>::new("sqlite::memory:").await?;
+        let scope = MemoryScope::Global;
+        let item = |summary: &str| {
+            MemoryItem::new(
+                nanors_core::memory::MemoryType::Episodic,
+                summary,
+                Some(vec![1.0, 0.0]),
+                Utc::now(),
+            )
+        };
+        let original = item("User: 我住在丰台");
+        MemoryItemRepo::insert(&manager, &scope, &original).await?;
+        let first = manager
+            .supersede(&scope, &original, &item("User: 我搬到了东城"))
+            .await?;
+
+        // A writer that still sees the original extends the new head
+        let second = manager
+            .supersede(&scope, &original, &item("User: 我搬到了西城"))
+            .await?;
+        let current = MemoryItemRepo::list(&manager, &scope).await?;
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].id, second);
+        assert_eq!(current[0].previous_version_id, Some(first));
+        Ok(())
+    }
+}
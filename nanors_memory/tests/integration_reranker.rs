@@ -1,11 +1,20 @@
 //! Integration tests for the reranking functionality.
 //!
 //! These tests verify that the reranker correctly applies question-type-specific
-//! boosts and improves search result relevance.
+//! boosts and improves search result relevance, and that the model-backed
+//! rerankers follow their model and keep the order when it fails.
 
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
 use chrono::{Duration, Utc};
-use nanors_core::memory::{MemoryItem, MemoryType, SalienceScore};
-use nanors_memory::rerank::{Reranker, RuleBasedReranker};
+use nanors_core::memory::{MemoryItem, MemoryType, SalienceScore, VersionRelation};
+use nanors_core::testing::ScriptedProvider;
+use nanors_memory::rerank::{CrossEncoderReranker, LlmReranker, Reranker, RuleBasedReranker};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 fn create_test_memory(summary: &str, hours_ago: i64) -> MemoryItem {
@@ -18,6 +27,9 @@ fn create_test_memory(summary: &str, hours_ago: i64) -> MemoryItem {
         extra: None,
         content_hash: "test".to_string(),
         reinforcement_count: 1,
+        previous_version_id: None,
+        superseded_at: None,
+        version_relation: VersionRelation::Sets,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     }
@@ -31,8 +43,8 @@ fn create_test_score(summary: &str, hours_ago: i64, score: f64) -> SalienceScore
     }
 }
 
-#[test]
-fn test_reranker_boosts_profile_facts_for_what_kind_questions() {
+#[tokio::test]
+async fn test_reranker_boosts_profile_facts_for_what_kind_questions() {
     let reranker = RuleBasedReranker::new();
 
     let mut results = vec![
@@ -41,7 +53,9 @@ fn test_reranker_boosts_profile_facts_for_what_kind_questions() {
         create_test_score("User: 我住西城区", 1, 0.6),
     ];
 
-    results = reranker.rerank(results, "我是什么用户");
+    results = reranker
+        .rerank(results, "我是什么用户", &HashMap::new())
+        .await;
 
     // Profile fact should be boosted to top
     assert!(
@@ -50,8 +64,8 @@ fn test_reranker_boosts_profile_facts_for_what_kind_questions() {
     );
 }
 
-#[test]
-fn test_reranker_boosts_location_facts_for_where_questions() {
+#[tokio::test]
+async fn test_reranker_boosts_location_facts_for_where_questions() {
     let reranker = RuleBasedReranker::new();
 
     let mut results = vec![
@@ -59,7 +73,7 @@ fn test_reranker_boosts_location_facts_for_where_questions() {
         create_test_score("User: 我住西城区", 1, 0.5), // Lower initial score but location boost should help
     ];
 
-    results = reranker.rerank(results, "我住哪");
+    results = reranker.rerank(results, "我住哪", &HashMap::new()).await;
 
     // Location fact should be boosted to top
     assert!(
@@ -69,8 +83,8 @@ fn test_reranker_boosts_location_facts_for_where_questions() {
     );
 }
 
-#[test]
-fn test_reranker_boosts_recent_memories_for_recency_questions() {
+#[tokio::test]
+async fn test_reranker_boosts_recent_memories_for_recency_questions() {
     let reranker = RuleBasedReranker::new();
 
     let mut results = vec![
@@ -78,7 +92,9 @@ fn test_reranker_boosts_recent_memories_for_recency_questions() {
         create_test_score("User: 用户类型B", 1, 0.7),
     ];
 
-    results = reranker.rerank(results, "我最新的用户类型是什么");
+    results = reranker
+        .rerank(results, "我最新的用户类型是什么", &HashMap::new())
+        .await;
 
     // Recent memory should rank higher for recency question
     assert!(
@@ -87,8 +103,8 @@ fn test_reranker_boosts_recent_memories_for_recency_questions() {
     );
 }
 
-#[test]
-fn test_reranker_preserves_facts_over_questions() {
+#[tokio::test]
+async fn test_reranker_preserves_facts_over_questions() {
     let reranker = RuleBasedReranker::new();
 
     let mut results = vec![
@@ -97,7 +113,7 @@ fn test_reranker_preserves_facts_over_questions() {
         create_test_score("这是什么", 1, 0.6),
     ];
 
-    results = reranker.rerank(results, "我住哪");
+    results = reranker.rerank(results, "我住哪", &HashMap::new()).await;
 
     // Fact (answer) should rank higher than question
     assert!(
@@ -110,8 +126,8 @@ fn test_reranker_preserves_facts_over_questions() {
     );
 }
 
-#[test]
-fn test_reranker_boosts_preferences_for_preference_questions() {
+#[tokio::test]
+async fn test_reranker_boosts_preferences_for_preference_questions() {
     let reranker = RuleBasedReranker::new();
 
     let mut results = vec![
@@ -120,7 +136,9 @@ fn test_reranker_boosts_preferences_for_preference_questions() {
         create_test_score("你住在哪里呢", 1, 0.8),
     ];
 
-    results = reranker.rerank(results, "我喜欢什么颜色");
+    results = reranker
+        .rerank(results, "我喜欢什么颜色", &HashMap::new())
+        .await;
 
     // Preference should be boosted
     assert!(
@@ -129,8 +147,8 @@ fn test_reranker_boosts_preferences_for_preference_questions() {
     );
 }
 
-#[test]
-fn test_reranker_default_weights() {
+#[tokio::test]
+async fn test_reranker_default_weights() {
     let reranker = RuleBasedReranker::new();
 
     let mut results = vec![
@@ -140,7 +158,9 @@ fn test_reranker_default_weights() {
 
     let original_scores: Vec<f64> = results.iter().map(|r| r.score).collect();
 
-    results = reranker.rerank(results, "我是什么用户");
+    results = reranker
+        .rerank(results, "我是什么用户", &HashMap::new())
+        .await;
 
     // Scores should be modified by reranking
     let new_scores: Vec<f64> = results.iter().map(|r| r.score).collect();
@@ -154,8 +174,8 @@ fn test_reranker_default_weights() {
     assert!(scores_changed, "Reranking should modify scores");
 }
 
-#[test]
-fn test_reranker_multiple_location_keywords_get_higher_boost() {
+#[tokio::test]
+async fn test_reranker_multiple_location_keywords_get_higher_boost() {
     let reranker = RuleBasedReranker::new();
 
     let single_location = create_test_score("User: 我住西城", 1, 0.7);
@@ -163,7 +183,7 @@ fn test_reranker_multiple_location_keywords_get_higher_boost() {
 
     let mut results = vec![single_location.clone(), multi_location.clone()];
 
-    results = reranker.rerank(results, "我住哪");
+    results = reranker.rerank(results, "我住哪", &HashMap::new()).await;
 
     // Multi-location memory should be boosted higher
     assert!(
@@ -173,3 +193,193 @@ fn test_reranker_multiple_location_keywords_get_higher_boost() {
         "Memory with multiple location keywords should be boosted"
     );
 }
+
+/// Candidates best first by salience, as the manager hands them over.
+fn candidates() -> Vec<SalienceScore<MemoryItem>> {
+    vec![
+        create_test_score("User: 我喜欢喝茶", 1, 0.9),
+        create_test_score("User: 我住在东城", 1, 0.5),
+        create_test_score("User: 我养了一只猫", 1, 0.1),
+    ]
+}
+
+/// Summaries ordered by score, as the manager ranks reranked results.
+fn ranked(mut results: Vec<SalienceScore<MemoryItem>>) -> Vec<String> {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.into_iter().map(|r| r.item.summary).collect()
+}
+
+#[tokio::test]
+async fn test_llm_reranker_follows_the_ratings() {
+    let provider = ScriptedProvider::replying("```json\n[1, 9, 3]\n```");
+    let reranker = LlmReranker::new(provider.clone(), "glm-4-flash");
+
+    let results = reranker
+        .rerank(candidates(), "我住在哪", &HashMap::new())
+        .await;
+
+    assert_eq!(
+        ranked(results),
+        ["User: 我住在东城", "User: 我养了一只猫", "User: 我喜欢喝茶"]
+    );
+    let prompts = provider.prompts().await;
+    assert_eq!(prompts.len(), 1);
+    assert!(prompts[0].contains("Query: 我住在哪"));
+    assert!(prompts[0].contains("1. User: 我住在东城"));
+}
+
+#[tokio::test]
+async fn test_llm_reranker_keeps_the_order_on_a_bad_reply() {
+    for provider in [
+        ScriptedProvider::replying("They all look relevant."),
+        ScriptedProvider::replying("[9, 1]"),
+        ScriptedProvider::failing(),
+    ] {
+        let reranker = LlmReranker::new(provider, "glm-4-flash");
+        let results = reranker
+            .rerank(candidates(), "我住在哪", &HashMap::new())
+            .await;
+        let scores: Vec<f64> = results.iter().map(|r| r.score).collect();
+        assert_eq!(scores, [0.9, 0.5, 0.1]);
+    }
+}
+
+#[tokio::test]
+async fn test_llm_reranker_keeps_the_candidates_past_the_limit() {
+    for provider in [
+        ScriptedProvider::replying("[1, 9]"),
+        ScriptedProvider::failing(),
+    ] {
+        let reranker = LlmReranker::new(provider.clone(), "glm-4-flash").with_max_candidates(2);
+        let results = reranker
+            .rerank(candidates(), "我住在哪", &HashMap::new())
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2].item.summary, "User: 我养了一只猫");
+        assert!((results[2].score - 0.1).abs() < f64::EPSILON);
+        let prompts = provider.prompts().await;
+        assert!(!prompts[0].contains("我养了一只猫"));
+    }
+}
+
+/// Request bodies a stub rerank server received.
+type Requests = Arc<Mutex<Vec<Value>>>;
+
+/// Stub relevance: texts mentioning "东城" score high, the rest low.
+fn stub_scores(texts: &Value) -> impl Iterator<Item = (usize, f64)> + '_ {
+    texts
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, text)| {
+            let relevant = text.as_str().is_some_and(|t| t.contains("东城"));
+            (index, if relevant { 0.95 } else { 0.05 })
+        })
+}
+
+fn record(requests: &Requests, body: &Value) {
+    if let Ok(mut requests) = requests.lock() {
+        requests.push(body.clone());
+    }
+}
+
+/// text-embeddings-inference: a bare array, best first.
+async fn tei_rerank(State(requests): State<Requests>, Json(body): Json<Value>) -> Json<Value> {
+    record(&requests, &body);
+    let mut ranked: Vec<(usize, f64)> = stub_scores(&body["texts"]).collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Json(json!(
+        ranked
+            .into_iter()
+            .map(|(index, score)| json!({"index": index, "score": score}))
+            .collect::<Vec<_>>()
+    ))
+}
+
+/// Jina: results under `results`, with `relevance_score`.
+async fn jina_rerank(State(requests): State<Requests>, Json(body): Json<Value>) -> Json<Value> {
+    record(&requests, &body);
+    let results: Vec<Value> = stub_scores(&body["documents"])
+        .map(|(index, score)| json!({"index": index, "relevance_score": score}))
+        .collect();
+    Json(json!({"model": body["model"], "results": results}))
+}
+
+/// A rerank server answering TEI requests on `/rerank`, Jina requests on
+/// `/v1/rerank` and failing on `/broken`.
+async fn rerank_server() -> anyhow::Result<(String, Requests)> {
+    let requests = Requests::default();
+    let app = Router::new()
+        .route("/rerank", post(tei_rerank))
+        .route("/v1/rerank", post(jina_rerank))
+        .route(
+            "/broken",
+            post(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "model not loaded") }),
+        )
+        .with_state(Arc::clone(&requests));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok((url, requests))
+}
+
+#[tokio::test]
+async fn test_cross_encoder_tei_request() -> anyhow::Result<()> {
+    let (url, requests) = rerank_server().await?;
+    let reranker = CrossEncoderReranker::new(format!("{url}/rerank"));
+
+    let results = reranker
+        .rerank(candidates(), "我住在哪", &HashMap::new())
+        .await;
+
+    assert_eq!(ranked(results)[0], "User: 我住在东城");
+    let requests = requests.lock().map(|r| r.clone()).unwrap_or_default();
+    assert_eq!(
+        requests,
+        [json!({
+            "query": "我住在哪",
+            "texts": ["User: 我喜欢喝茶", "User: 我住在东城", "User: 我养了一只猫"],
+        })]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cross_encoder_jina_request() -> anyhow::Result<()> {
+    let (url, requests) = rerank_server().await?;
+    let reranker =
+        CrossEncoderReranker::new(format!("{url}/v1/rerank")).with_model("jina-reranker-v2");
+
+    let results = reranker
+        .rerank(candidates(), "我住在哪", &HashMap::new())
+        .await;
+
+    assert_eq!(ranked(results)[0], "User: 我住在东城");
+    let requests = requests.lock().map(|r| r.clone()).unwrap_or_default();
+    assert_eq!(
+        requests,
+        [json!({
+            "model": "jina-reranker-v2",
+            "query": "我住在哪",
+            "documents": ["User: 我喜欢喝茶", "User: 我住在东城", "User: 我养了一只猫"],
+        })]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cross_encoder_keeps_the_order_on_errors() -> anyhow::Result<()> {
+    let (url, _requests) = rerank_server().await?;
+    for endpoint in [format!("{url}/broken"), format!("{url}/missing")] {
+        let reranker = CrossEncoderReranker::new(endpoint.clone());
+        let results = reranker
+            .rerank(candidates(), "我住在哪", &HashMap::new())
+            .await;
+        let scores: Vec<f64> = results.iter().map(|r| r.score).collect();
+        assert_eq!(scores, [0.9, 0.5, 0.1], "{endpoint}");
+    }
+    Ok(())
+}
@@ -0,0 +1,603 @@
+//! Integration tests for `MemoryManager` against an in-memory `SQLite`
+//! database.
+//!
+//! These run the real migrations and queries, so they catch SQL that only
+//! works on Postgres (JSON embedding columns, timestamp types).
+
+use chrono::{Duration, Utc};
+use nanors_core::channel::{ChatGateway, ChatSessionStore, LoopbackChannel};
+use nanors_core::memory::{MemoryItem, MemoryItemRepo, MemoryScope, MemoryType, VersionRelation};
+use nanors_core::testing::{InMemorySessions, RepeatProvider, repeat_factory};
+use nanors_core::{AgentConfig, AgentLoop, MemoryQueue, MessageContent, Role, SessionStorage};
+use nanors_memory::MemoryManager;
+use nanors_memory::enrichment::{Enricher, EnrichmentReport, EnrichmentRunner};
+use nanors_memory::eval::{EvalOptions, HashEmbedder, evaluate, parse_dataset};
+use nanors_memory::extraction::{CardKind, MemoryCard};
+use nanors_memory::query::detector::QuestionType;
+use nanors_memory::rerank::RuleBasedReranker;
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn manager() -> anyhow::Result<MemoryManager> {
+    MemoryManager::<RuleBasedReranker>::new("sqlite::memory:").await
+}
+
+fn memory(summary: &str, embedding: &[f32], hours_ago: i64) -> MemoryItem {
+    MemoryItem::new(
+        MemoryType::Episodic,
+        summary,
+        Some(embedding.to_vec()),
+        Utc::now() - Duration::hours(hours_ago),
+    )
+}
+
+#[tokio::test]
+async fn test_memory_round_trip() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::user("telegram", 42);
+    let item = memory("User: 我住在杭州", &[0.25, -0.5, 1.0], 3);
+
+    MemoryItemRepo::insert(&manager, &scope, &item).await?;
+
+    let stored = MemoryItemRepo::find_by_id(&manager, &scope, &item.id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("memory not stored"))?;
+    assert_eq!(stored.summary, item.summary);
+    assert_eq!(stored.embedding, item.embedding);
+    assert_eq!(
+        stored.happened_at.timestamp_millis(),
+        item.happened_at.timestamp_millis()
+    );
+    assert!(
+        MemoryItemRepo::find_by_content_hash(&manager, &scope, &item.content_hash)
+            .await?
+            .is_some()
+    );
+
+    // Other scopes do not see it
+    let other = MemoryScope::user("telegram", 7);
+    assert!(
+        MemoryItemRepo::find_by_id(&manager, &other, &item.id)
+            .await?
+            .is_none()
+    );
+    assert!(MemoryItemRepo::list(&manager, &other).await?.is_empty());
+    let rewrite = MemoryItem {
+        summary: "User: 我住在北京".into(),
+        ..item.clone()
+    };
+    assert!(
+        MemoryItemRepo::update(&manager, &other, &rewrite)
+            .await
+            .is_err()
+    );
+
+    MemoryItemRepo::delete(&manager, &scope, &item.id).await?;
+    assert!(MemoryItemRepo::list(&manager, &scope).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upsert_reinforces_duplicates() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::Global;
+
+    let first = manager
+        .upsert_memory(&scope, &memory("User: 我喜欢喝茶", &[1.0, 0.0], 2))
+        .await?;
+    let second = manager
+        .upsert_memory(&scope, &memory("User: 我喜欢喝茶", &[1.0, 0.0], 1))
+        .await?;
+
+    assert_eq!(first, second);
+    let items = MemoryItemRepo::list(&manager, &scope).await?;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].reinforcement_count, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_by_embedding() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::chat("telegram", -100);
+    for (summary, embedding) in [
+        ("User: 我住在杭州", [0.9, 0.1, 0.0]),
+        ("User: 我养了一只猫", [0.0, 1.0, 0.0]),
+        ("User: 我每天跑步", [0.0, 0.0, 1.0]),
+    ] {
+        MemoryItemRepo::insert(&manager, &scope, &memory(summary, &embedding, 1)).await?;
+    }
+
+    let results =
+        MemoryItemRepo::search_by_embedding(&manager, &scope, &[1.0, 0.0, 0.0], "杭州", 1).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item.summary, "User: 我住在杭州");
+
+    let elsewhere = MemoryItemRepo::search_by_embedding(
+        &manager,
+        &MemoryScope::Global,
+        &[1.0, 0.0, 0.0],
+        "杭州",
+        5,
+    )
+    .await?;
+    assert!(elsewhere.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_semantic_upsert_appends_a_version() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::Global;
+    let original = memory("User: 我住在杭州西湖区", &[1.0, 0.2, 0.0], 5);
+    MemoryItemRepo::insert(&manager, &scope, &original).await?;
+
+    let id = MemoryItemRepo::semantic_upsert(
+        &manager,
+        &scope,
+        &memory("User: 我搬到了杭州滨江区", &[1.0, 0.7, 0.0], 0),
+    )
+    .await?;
+
+    assert_ne!(id, original.id);
+    let items = MemoryItemRepo::list(&manager, &scope).await?;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].summary, "User: 我搬到了杭州滨江区");
+    assert_eq!(items[0].previous_version_id, Some(original.id));
+    assert_eq!(items[0].version_relation, VersionRelation::Updates);
+
+    // The old fact is kept, superseded
+    let history = manager.history(&scope, &id).await?;
+    let summaries: Vec<&str> = history.iter().map(|i| i.summary.as_str()).collect();
+    assert_eq!(
+        summaries,
+        ["User: 我搬到了杭州滨江区", "User: 我住在杭州西湖区"]
+    );
+    assert!(history[0].is_current());
+    assert!(!history[1].is_current());
+
+    // Only the current version answers through cards
+    let cards = manager.list_cards(&scope).await?;
+    assert!(!cards.is_empty());
+    assert!(cards.iter().all(|c| c.source_memory_id == Some(id)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deleting_a_fact_deletes_its_history() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::Global;
+    let original = memory("User: 我住在杭州西湖区", &[1.0, 0.2, 0.0], 5);
+    MemoryItemRepo::insert(&manager, &scope, &original).await?;
+    let id = MemoryItemRepo::semantic_upsert(
+        &manager,
+        &scope,
+        &memory("User: 我搬到了杭州滨江区", &[1.0, 0.7, 0.0], 0),
+    )
+    .await?;
+
+    MemoryItemRepo::delete(&manager, &scope, &id).await?;
+    assert!(
+        MemoryItemRepo::find_by_id(&manager, &scope, &original.id)
+            .await?
+            .is_none()
+    );
+    assert!(manager.list_cards(&scope).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extracted_facts_supersede_raw_messages() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::Global;
+    let raw = memory("User: 我住在杭州西湖区", &[1.0, 0.2, 0.0], 5);
+    MemoryItemRepo::insert(&manager, &scope, &raw).await?;
+    let reply = memory("Assistant: 杭州西湖区很美", &[1.0, 0.6, 0.0], 4);
+    MemoryItemRepo::insert(&manager, &scope, &reply).await?;
+
+    // Extracted facts have no role prefix
+    let id = MemoryItemRepo::semantic_upsert(
+        &manager,
+        &scope,
+        &memory("我搬到了杭州滨江区", &[1.0, 0.7, 0.0], 0),
+    )
+    .await?;
+
+    let items = MemoryItemRepo::list(&manager, &scope).await?;
+    let current = items
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| anyhow::anyhow!("fact not stored"))?;
+    assert_eq!(current.previous_version_id, Some(raw.id));
+    // The assistant reply is left alone
+    assert!(items.iter().any(|item| item.id == reply.id));
+    assert!(items.iter().all(|item| item.id != raw.id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_agent_stores_memories_in_the_background() -> anyhow::Result<()> {
+    let manager = Arc::new(manager().await?);
+    let scope = MemoryScope::user("telegram", 42);
+    let queue = Arc::new(MemoryQueue::default());
+    // One agent per turn, as the gateway and the API server build them
+    let agent = || {
+        AgentLoop::new(
+            RepeatProvider(std::time::Duration::ZERO),
+            Arc::new(InMemorySessions::default()),
+            AgentConfig::default(),
+        )
+        .with_memory(manager.clone())
+        .with_memory_queue(Arc::clone(&queue))
+        .with_memory_scope(scope.clone())
+        .with_memory_extraction(false)
+    };
+
+    agent()
+        .process_message(&Uuid::now_v7(), "我住在东城")
+        .await?;
+    let second = agent();
+    second
+        .process_message(&Uuid::now_v7(), "我养了一只猫")
+        .await?;
+    // Waiting for a turn also waits for the earlier turns of the scope
+    second.wait_for_memory().await;
+
+    let items = MemoryItemRepo::list(manager.as_ref(), &scope).await?;
+    let mut summaries: Vec<&str> = items.iter().map(|i| i.summary.as_str()).collect();
+    summaries.sort_unstable();
+    assert_eq!(summaries, ["User: 我住在东城", "User: 我养了一只猫"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_sees_writes_of_other_processes() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("nanors_shared_db_{}", Uuid::now_v7()));
+    std::fs::create_dir_all(&dir)?;
+    let url = format!("sqlite://{}?mode=rwc", dir.join("nanors.db").display());
+    let scope = MemoryScope::user("telegram", 42);
+    // Both index the database at startup, before any memory exists
+    let writer = MemoryManager::<RuleBasedReranker>::new(&url).await?;
+    let reader = MemoryManager::<RuleBasedReranker>::new(&url).await?;
+
+    let old = memory("User: 我住在杭州西湖区", &[1.0, 0.2, 0.0], 5);
+    MemoryItemRepo::insert(&writer, &scope, &old).await?;
+    let results =
+        MemoryItemRepo::search_by_embedding(&reader, &scope, &[1.0, 0.0, 0.0], "", 5).await?;
+    let ids: Vec<Uuid> = results.iter().map(|s| s.item.id).collect();
+    assert_eq!(ids, [old.id]);
+
+    let new = MemoryItemRepo::semantic_upsert(
+        &writer,
+        &scope,
+        &memory("User: 我搬到了杭州滨江区", &[1.0, 0.7, 0.0], 0),
+    )
+    .await?;
+    let results =
+        MemoryItemRepo::search_by_embedding(&reader, &scope, &[1.0, 0.0, 0.0], "", 5).await?;
+    let ids: Vec<Uuid> = results.iter().map(|s| s.item.id).collect();
+    assert_eq!(ids, [new]);
+
+    // Keyword matches count the same in both: the lexical index caught up
+    let query = [0.0, 0.0, 1.0];
+    let seen_by_reader =
+        MemoryItemRepo::search_by_embedding(&reader, &scope, &query, "滨江区", 1).await?;
+    let seen_by_writer =
+        MemoryItemRepo::search_by_embedding(&writer, &scope, &query, "滨江区", 1).await?;
+    assert_eq!(seen_by_reader.len(), 1);
+    assert!(seen_by_reader[0].similarity > 0.0);
+    assert!((seen_by_reader[0].similarity - seen_by_writer[0].similarity).abs() < 1e-9);
+
+    drop((writer, reader));
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_history_questions_see_the_version_chain() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::user("telegram", 42);
+    let old = memory("User: 我住在丰台", &[1.0, 0.0, 0.0], 72);
+    MemoryItemRepo::insert(&manager, &scope, &old).await?;
+    let new = MemoryItemRepo::semantic_upsert(
+        &manager,
+        &scope,
+        &memory("User: 我搬到了东城", &[1.0, 0.5, 0.0], 1),
+    )
+    .await?;
+
+    // Current questions only see the current version
+    let results =
+        MemoryItemRepo::search_enhanced(&manager, &scope, &[1.0, 0.1, 0.0], "我住在哪", 5).await?;
+    let ids: Vec<Uuid> = results.iter().map(|s| s.item.id).collect();
+    assert_eq!(ids, [new]);
+
+    let results =
+        MemoryItemRepo::search_enhanced(&manager, &scope, &[1.0, 0.1, 0.0], "我之前住在哪", 5)
+            .await?;
+    let ids: Vec<Uuid> = results.iter().map(|s| s.item.id).collect();
+    assert_eq!(ids, [new, old.id]);
+
+    // Deleting the old version leaves the current one intact
+    MemoryItemRepo::delete(&manager, &scope, &old.id).await?;
+    let history = manager.history(&scope, &new).await?;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].previous_version_id, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_backfill_embeddings() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::Global;
+    let item = MemoryItem::new(MemoryType::Semantic, "喜欢爵士乐", None, Utc::now());
+    MemoryItemRepo::insert(&manager, &scope, &item).await?;
+
+    let updated = MemoryItemRepo::backfill_embeddings(&manager, &|_| Ok(vec![0.5, 0.5])).await?;
+
+    assert_eq!(updated, 1);
+    let stored = MemoryItemRepo::find_by_id(&manager, &scope, &item.id).await?;
+    assert_eq!(stored.and_then(|i| i.embedding), Some(vec![0.5, 0.5]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_messages_persist() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let id = Uuid::now_v7();
+
+    assert!(manager.get_or_create(&id).await?.messages.is_empty());
+    assert!(manager.get(&id).await?.is_none());
+
+    manager.add_message(&id, Role::User, "你好").await?;
+    assert!(manager.get(&id).await?.is_some());
+    manager.add_message(&id, Role::Assistant, "你好！").await?;
+
+    let session = manager.get_or_create(&id).await?;
+    assert_eq!(session.messages.len(), 2);
+    assert!(matches!(session.messages[0].role, Role::User));
+    assert!(matches!(
+        &session.messages[1].content,
+        MessageContent::Text(text) if text == "你好！"
+    ));
+    assert!(session.created_at <= session.updated_at);
+
+    manager.clear_session(&id).await?;
+    assert!(manager.get_or_create(&id).await?.messages.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_sessions() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let (first, second) = (Uuid::now_v7(), Uuid::now_v7());
+
+    manager.create_session("telegram", "42", &first).await?;
+    assert_eq!(manager.active_session("telegram", "42").await?, Some(first));
+    assert_eq!(manager.active_session("telegram", "7").await?, None);
+
+    assert_eq!(
+        manager.archive_session("telegram", "42").await?,
+        Some(first)
+    );
+    assert_eq!(manager.active_session("telegram", "42").await?, None);
+    manager.create_session("telegram", "42", &second).await?;
+
+    let sessions = manager.list_sessions("telegram", "42").await?;
+    let ids: Vec<Uuid> = sessions.iter().map(|s| s.session_id).collect();
+    assert_eq!(ids, vec![second, first]);
+    assert!(sessions[1].archived);
+    assert!(!sessions[0].archived);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_listening_chats() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    assert!(manager.listening_chats("telegram").await?.is_empty());
+
+    manager.set_listening("telegram", "-100", true).await?;
+    // Turning it on twice is harmless
+    manager.set_listening("telegram", "-100", true).await?;
+    manager.set_listening("telegram", "-200", true).await?;
+    manager.set_listening("other", "-300", true).await?;
+    let mut chats = manager.listening_chats("telegram").await?;
+    chats.sort();
+    assert_eq!(chats, ["-100", "-200"]);
+
+    manager.set_listening("telegram", "-100", false).await?;
+    manager.set_listening("telegram", "-999", false).await?;
+    assert_eq!(manager.listening_chats("telegram").await?, ["-200"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gateway_resumes_sessions_after_restart() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("nanors_chat_sessions_{}", Uuid::now_v7()));
+    std::fs::create_dir_all(&dir)?;
+    let url = format!("sqlite://{}?mode=rwc", dir.join("nanors.db").display());
+    let gateway = |store: Arc<dyn ChatSessionStore>| {
+        let factory = repeat_factory(
+            Arc::new(InMemorySessions::default()),
+            std::time::Duration::ZERO,
+        );
+        ChatGateway::new(Arc::new(LoopbackChannel::new()), factory, "test-model")
+            .with_session_store(store)
+    };
+
+    let before = gateway(Arc::new(
+        MemoryManager::<RuleBasedReranker>::new(&url).await?,
+    ));
+    let id = before.session_id("chat-1").await?;
+    drop(before);
+
+    // A restart is a new gateway over a new connection to the same database
+    let after = gateway(Arc::new(
+        MemoryManager::<RuleBasedReranker>::new(&url).await?,
+    ));
+    assert_eq!(after.session_id("chat-1").await?, id);
+    assert_ne!(after.session_id("chat-2").await?, id);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_card_lookup_answers_what_kind_questions() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::user("telegram", 42);
+    let answer = memory("User: 我是安卓玩机用户", &[0.0, 1.0, 0.0], 48);
+    MemoryItemRepo::insert(&manager, &scope, &answer).await?;
+    // Closer to the query embedding, but not an answer
+    for summary in ["User: 今天天气不错", "User: 帮我写个脚本"] {
+        MemoryItemRepo::insert(&manager, &scope, &memory(summary, &[1.0, 0.1, 0.0], 1)).await?;
+    }
+
+    let results =
+        MemoryItemRepo::search_enhanced(&manager, &scope, &[1.0, 0.0, 0.0], "我是什么用户", 2)
+            .await?;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].item.id, answer.id);
+
+    // Cards go with their memory
+    assert_eq!(manager.list_cards(&scope).await?.len(), 1);
+    MemoryItemRepo::delete(&manager, &scope, &answer.id).await?;
+    assert!(manager.list_cards(&scope).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_newest_card_wins() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let scope = MemoryScope::Global;
+    let old = memory("User: 我住在丰台", &[0.0, 1.0, 0.0], 72);
+    let new = memory("User: 我搬到了东城", &[0.0, 0.0, 1.0], 1);
+    MemoryItemRepo::insert(&manager, &scope, &old).await?;
+    MemoryItemRepo::insert(&manager, &scope, &new).await?;
+
+    let cards = manager.current_cards(&scope, &["location"]).await?;
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].value, "东城");
+
+    let results =
+        MemoryItemRepo::search_enhanced(&manager, &scope, &[1.0, 0.0, 0.0], "我现在住在哪", 5)
+            .await?;
+    assert_eq!(results[0].item.id, new.id);
+    Ok(())
+}
+
+/// Tags every memory with its length; fails on memories mentioning "fail".
+struct LengthEnricher {
+    version: &'static str,
+}
+
+#[async_trait::async_trait]
+impl Enricher for LengthEnricher {
+    fn kind(&self) -> &'static str {
+        "length"
+    }
+
+    fn version(&self) -> &str {
+        self.version
+    }
+
+    async fn enrich(&self, scope: &str, item: &MemoryItem) -> anyhow::Result<Vec<MemoryCard>> {
+        if item.summary.contains("fail") {
+            anyhow::bail!("cannot enrich {}", item.id);
+        }
+        let mut card = MemoryCard::new(
+            CardKind::Fact,
+            "memory",
+            "length",
+            &item.summary.chars().count().to_string(),
+        );
+        card.user_scope = scope.to_string();
+        card.source_memory_id = Some(item.id);
+        card.engine = self.kind().to_string();
+        card.engine_version = self.version.to_string();
+        Ok(vec![card])
+    }
+}
+
+#[tokio::test]
+async fn test_enrichment_is_incremental() -> anyhow::Result<()> {
+    let manager = Arc::new(manager().await?);
+    let scope = MemoryScope::Global;
+    for (summary, embedding) in [
+        ("User: 我住在杭州", [1.0, 0.0]),
+        ("User: 我养了一只猫", [0.0, 1.0]),
+        ("User: please fail", [-1.0, 0.0]),
+    ] {
+        MemoryItemRepo::insert(manager.as_ref(), &scope, &memory(summary, &embedding, 1)).await?;
+    }
+
+    // Extraction on insert already counts as enrichment by the rule engine
+    let report = EnrichmentRunner::new(Arc::clone(&manager)).run().await?;
+    assert_eq!(report, EnrichmentReport::default());
+    let rule_cards = manager.list_cards(&scope).await?.len();
+
+    let runner = |version| {
+        EnrichmentRunner::new(Arc::clone(&manager))
+            .with_enricher(LengthEnricher { version })
+            .with_batch_size(2)
+    };
+    let report = runner("1").spawn().await??;
+    assert_eq!(
+        report,
+        EnrichmentReport {
+            enriched: 2,
+            failed: 1,
+            cards: 2,
+        }
+    );
+    assert_eq!(manager.list_cards(&scope).await?.len(), rule_cards + 2);
+
+    // Only the failure is retried
+    let report = runner("1").run().await?;
+    assert_eq!((report.enriched, report.failed), (0, 1));
+
+    // A new version reprocesses everything, replacing the old cards
+    let report = runner("2").run().await?;
+    assert_eq!((report.enriched, report.failed), (2, 1));
+    assert_eq!(manager.list_cards(&scope).await?.len(), rule_cards + 2);
+
+    // Superseded versions are not enriched again
+    MemoryItemRepo::semantic_upsert(
+        manager.as_ref(),
+        &scope,
+        &memory("User: 我搬到了杭州滨江区", &[1.0, 0.4], 0),
+    )
+    .await?;
+    let report = runner("3").run().await?;
+    assert_eq!((report.enriched, report.failed), (2, 1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retrieval_eval_on_the_sample_dataset() -> anyhow::Result<()> {
+    let manager = manager().await?;
+    let cases = parse_dataset(include_str!("data/retrieval.jsonl"))?;
+    let options = EvalOptions::default();
+
+    let report = evaluate(&manager, &cases, &HashEmbedder::default(), &options).await?;
+
+    assert_eq!(report.overall.cases, cases.len());
+    assert_eq!(report.overall.recall.len(), options.ks.len());
+    // Every case only has three memories
+    assert!((report.overall.recall[options.ks.len() - 1] - 1.0).abs() < 1e-9);
+    assert!(report.overall.mrr > 0.5);
+    let moved = report
+        .cases
+        .iter()
+        .find(|c| c.id == "moved-district")
+        .ok_or_else(|| anyhow::anyhow!("case missing"))?;
+    assert_eq!(moved.question_type, QuestionType::Recency);
+    assert_eq!(moved.ranked[0], "dongcheng");
+    assert!(
+        report
+            .by_question_type
+            .iter()
+            .any(|(question_type, _)| *question_type == QuestionType::Where)
+    );
+    Ok(())
+}
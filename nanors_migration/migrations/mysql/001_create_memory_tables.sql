@@ -0,0 +1,21 @@
+-- Memory Tables Migration for MySQL
+-- Creates memory_items in its current shape. The Postgres history (resources,
+-- categories, version columns) was dropped again before MySQL was
+-- supported, so it is not replayed here.
+
+CREATE TABLE IF NOT EXISTS memory_items (
+    id BINARY(16) PRIMARY KEY,
+    user_scope VARCHAR(255) NOT NULL,
+    memory_type VARCHAR(64) NOT NULL,
+    summary TEXT NOT NULL,
+    embedding JSON,
+    happened_at TIMESTAMP(6) NOT NULL,
+    extra JSON,
+    content_hash VARCHAR(64) NOT NULL,
+    reinforcement_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_memory_items_user_scope (user_scope),
+    INDEX idx_memory_items_content_hash (content_hash),
+    INDEX idx_memory_items_user_scope_hash (user_scope, content_hash)
+);
@@ -0,0 +1,8 @@
+-- Sessions Table Migration for MySQL
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id BINARY(16) PRIMARY KEY,
+    messages LONGTEXT NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6)
+);
@@ -0,0 +1,2 @@
+-- Migration: Add keyword-triggered memory versioning support
+-- Nothing to do on MySQL: these columns were removed in 005.
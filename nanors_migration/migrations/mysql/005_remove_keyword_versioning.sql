@@ -0,0 +1,2 @@
+-- Migration: Remove keyword-triggered memory versioning support
+-- Nothing to do on MySQL: the columns were never created.
@@ -0,0 +1,2 @@
+-- Migration: Remove unused tiered retrieval tables
+-- Nothing to do on MySQL: the tables were never created.
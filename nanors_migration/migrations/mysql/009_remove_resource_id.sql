@@ -0,0 +1,2 @@
+-- Migration: Remove resource_id column from memory_items
+-- Nothing to do on MySQL: the column was never created.
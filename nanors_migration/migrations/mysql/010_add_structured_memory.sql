@@ -0,0 +1,33 @@
+-- Migration: Add structured memory cards
+-- memory_cards holds entity/slot/value triples extracted from memories.
+-- (query_expansions from the Postgres migration was removed in 012.)
+
+CREATE TABLE IF NOT EXISTS memory_cards (
+    id BINARY(16) PRIMARY KEY,
+    user_scope VARCHAR(255) NOT NULL,
+    kind VARCHAR(32) NOT NULL DEFAULT 'fact',
+    entity VARCHAR(255) NOT NULL,
+    slot VARCHAR(255) NOT NULL,
+    value TEXT NOT NULL,
+    polarity VARCHAR(16),
+    event_date TIMESTAMP(6) NULL,
+    document_date TIMESTAMP(6) NULL,
+    version_key VARCHAR(511),
+    version_relation VARCHAR(32) NOT NULL DEFAULT 'Sets',
+    source_memory_id BINARY(16),
+    engine VARCHAR(64) NOT NULL DEFAULT 'rules',
+    engine_version VARCHAR(64) NOT NULL DEFAULT '1.0.0',
+    confidence DOUBLE,
+    extra JSON,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_cards_entity_slot (user_scope, entity, slot),
+    INDEX idx_cards_version_key (user_scope, version_key),
+    INDEX idx_cards_source_memory (source_memory_id),
+    -- TEXT columns need a prefix length to be indexed
+    INDEX idx_cards_slot_value (user_scope, slot, value(255)),
+    CONSTRAINT fk_card_memory
+        FOREIGN KEY (source_memory_id)
+        REFERENCES memory_items(id)
+        ON DELETE CASCADE
+);
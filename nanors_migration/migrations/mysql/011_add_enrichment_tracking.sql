@@ -0,0 +1,25 @@
+-- Migration: Add enrichment tracking for incremental processing
+-- card_ids is a JSON array of card ids (Postgres uses UUID[]). MySQL has no
+-- partial indexes, so idx_enrichment_failed covers all rows.
+
+CREATE TABLE IF NOT EXISTS enrichment_records (
+    id BINARY(16) PRIMARY KEY,
+    user_scope VARCHAR(255) NOT NULL,
+    memory_id BINARY(16) NOT NULL,
+    engine_kind VARCHAR(64) NOT NULL,
+    engine_version VARCHAR(64) NOT NULL,
+    enriched_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    card_ids JSON,
+    success BOOLEAN NOT NULL DEFAULT TRUE,
+    error_message TEXT,
+    extra JSON,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_enrichment_memory (user_scope, memory_id),
+    INDEX idx_enrichment_time (user_scope, engine_kind, enriched_at DESC),
+    INDEX idx_enrichment_failed (user_scope, success, enriched_at),
+    CONSTRAINT unique_enrichment UNIQUE (user_scope, memory_id, engine_kind, engine_version),
+    CONSTRAINT fk_enrichment_memory
+        FOREIGN KEY (memory_id)
+        REFERENCES memory_items(id)
+        ON DELETE CASCADE
+);
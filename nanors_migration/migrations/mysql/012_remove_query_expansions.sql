@@ -0,0 +1,2 @@
+-- Migration: Remove unused query_expansions table
+-- Nothing to do on MySQL: the table was never created.
@@ -0,0 +1,14 @@
+-- Migration: Persist chat-to-session mapping for chat channels
+-- Each channel chat has at most one active session; resetting archives it.
+-- MySQL has no partial unique indexes, so "one active session per chat" is
+-- upheld by the chat session store rather than the schema.
+
+CREATE TABLE IF NOT EXISTS chat_sessions (
+    session_id BINARY(16) PRIMARY KEY,
+    channel VARCHAR(64) NOT NULL,
+    chat_id VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    INDEX idx_chat_sessions_active (channel, chat_id, archived),
+    INDEX idx_chat_sessions_chat (channel, chat_id, created_at DESC)
+);
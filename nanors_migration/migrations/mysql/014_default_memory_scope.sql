@@ -0,0 +1,7 @@
+-- Migration: Default memory scope
+-- Memories are read and written per scope; rows without one belong to the
+-- shared global scope.
+
+UPDATE memory_items SET user_scope = 'global' WHERE user_scope = '';
+
+ALTER TABLE memory_items ALTER COLUMN user_scope SET DEFAULT 'global';
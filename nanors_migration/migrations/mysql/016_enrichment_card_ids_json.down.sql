@@ -0,0 +1,2 @@
+-- Revert: Store enrichment card ids as JSON
+-- Nothing to do on MySQL: card_ids is already a JSON array.
@@ -0,0 +1,9 @@
+-- Revert: Append-only version chain for memory facts
+-- Superseded versions stay as ordinary rows.
+
+ALTER TABLE memory_items
+    DROP INDEX idx_memory_items_current,
+    DROP INDEX idx_memory_items_previous_version,
+    DROP COLUMN version_relation,
+    DROP COLUMN superseded_at,
+    DROP COLUMN previous_version_id;
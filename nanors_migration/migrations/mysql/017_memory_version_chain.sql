@@ -0,0 +1,11 @@
+-- Migration: Append-only version chain for memory facts
+-- New versions point at the row they supersede; retrieval reads rows with
+-- superseded_at IS NULL. MySQL has no partial indexes, so the current-row
+-- index covers superseded rows too.
+
+ALTER TABLE memory_items
+    ADD COLUMN previous_version_id BINARY(16),
+    ADD COLUMN superseded_at TIMESTAMP(6) NULL,
+    ADD COLUMN version_relation VARCHAR(16) NOT NULL DEFAULT 'Sets',
+    ADD INDEX idx_memory_items_previous_version (previous_version_id),
+    ADD INDEX idx_memory_items_current (user_scope, superseded_at);
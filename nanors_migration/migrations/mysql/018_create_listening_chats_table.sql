@@ -0,0 +1,9 @@
+-- Migration: Persist group listen mode
+-- Chats listed here have listen mode on.
+
+CREATE TABLE IF NOT EXISTS listening_chats (
+    channel VARCHAR(64) NOT NULL,
+    chat_id VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (channel, chat_id)
+);
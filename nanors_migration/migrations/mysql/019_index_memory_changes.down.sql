@@ -0,0 +1,5 @@
+-- Revert: Index memory changes by time
+
+ALTER TABLE memory_items
+    DROP INDEX idx_memory_items_superseded_at,
+    DROP INDEX idx_memory_items_updated_at;
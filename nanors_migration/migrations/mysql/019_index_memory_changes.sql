@@ -0,0 +1,7 @@
+-- Migration: Index memory changes by time
+-- Before searching, each process picks up the memories other processes
+-- wrote or superseded since its last look.
+
+ALTER TABLE memory_items
+    ADD INDEX idx_memory_items_updated_at (updated_at),
+    ADD INDEX idx_memory_items_superseded_at (superseded_at);
@@ -0,0 +1,4 @@
+-- Revert: Persist chat-to-session mapping for chat channels
+-- Sessions themselves are kept; only the chat mapping is dropped.
+
+DROP TABLE IF EXISTS chat_sessions;
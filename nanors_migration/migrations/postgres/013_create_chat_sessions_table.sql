@@ -0,0 +1,23 @@
+-- Migration: Persist chat-to-session mapping for chat channels
+-- Each channel chat (e.g. a Telegram chat) has at most one active session.
+-- Resetting a conversation archives the mapping instead of deleting the
+-- session, so old conversations stay available.
+
+CREATE TABLE IF NOT EXISTS chat_sessions (
+    session_id UUID PRIMARY KEY,
+    channel VARCHAR(64) NOT NULL,          -- e.g., "telegram"
+    chat_id VARCHAR(255) NOT NULL,         -- channel-specific chat identifier
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    archived BOOLEAN NOT NULL DEFAULT false
+);
+
+-- At most one active session per chat
+CREATE UNIQUE INDEX IF NOT EXISTS idx_chat_sessions_active
+    ON chat_sessions(channel, chat_id)
+    WHERE archived = false;
+
+-- Index for listing a chat's sessions, newest first
+CREATE INDEX IF NOT EXISTS idx_chat_sessions_chat
+    ON chat_sessions(channel, chat_id, created_at DESC);
+
+COMMENT ON TABLE chat_sessions IS 'Maps channel chats to agent sessions; archived rows are past conversations';
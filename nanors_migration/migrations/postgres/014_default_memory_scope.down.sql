@@ -0,0 +1,3 @@
+-- Revert: Default memory scope
+
+ALTER TABLE memory_items ALTER COLUMN user_scope DROP DEFAULT;
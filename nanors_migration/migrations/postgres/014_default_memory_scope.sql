@@ -0,0 +1,8 @@
+-- Migration: Default memory scope
+-- Memories are now read and written per scope ("user:telegram:42",
+-- "chat:telegram:-100", "agent:<name>" or "global"). Rows written before
+-- scoping was enforced belong to the shared global scope.
+
+UPDATE memory_items SET user_scope = 'global' WHERE user_scope = '';
+
+ALTER TABLE memory_items ALTER COLUMN user_scope SET DEFAULT 'global';
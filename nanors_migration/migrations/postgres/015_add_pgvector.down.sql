@@ -0,0 +1,6 @@
+-- Revert: Database-side vector search with pgvector
+-- The extension itself is left installed; other databases may use it.
+
+DROP INDEX IF EXISTS idx_memory_items_embedding_vec;
+
+ALTER TABLE memory_items DROP COLUMN IF EXISTS embedding_vec;
@@ -0,0 +1,36 @@
+-- Migration: Database-side vector search with pgvector
+-- Uses the pgvector extension (https://github.com/pgvector/pgvector) when the
+-- server has it; otherwise this is a no-op and nanors keeps using its
+-- in-process vector index.
+--
+-- embedding_vec mirrors the JSONB embedding column, which stays the source
+-- of truth. Top-K candidates are selected through the HNSW index and only
+-- that candidate set is reranked by keyword overlap and recency.
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'vector') THEN
+        RAISE NOTICE 'pgvector is not installed; skipping embedding_vec';
+        RETURN;
+    END IF;
+
+    CREATE EXTENSION IF NOT EXISTS vector;
+
+    -- 1024 dimensions matches Zhipu embedding-2
+    ALTER TABLE memory_items ADD COLUMN IF NOT EXISTS embedding_vec vector(1024);
+
+    -- Convert existing JSONB embeddings; other dimensions stay unindexed
+    UPDATE memory_items
+    SET embedding_vec = (embedding::text)::vector
+    WHERE embedding IS NOT NULL
+      AND embedding_vec IS NULL
+      AND jsonb_typeof(embedding) = 'array'
+      AND jsonb_array_length(embedding) = 1024;
+
+    -- Cosine distance, matching the in-process similarity
+    CREATE INDEX IF NOT EXISTS idx_memory_items_embedding_vec
+        ON memory_items USING hnsw (embedding_vec vector_cosine_ops);
+EXCEPTION
+    WHEN insufficient_privilege THEN
+        RAISE NOTICE 'Not allowed to create the pgvector extension; skipping embedding_vec';
+END $$;
@@ -0,0 +1,5 @@
+-- Revert: Store enrichment card ids as JSON
+-- ["a", "b"] becomes the array literal {a, b}.
+
+ALTER TABLE enrichment_records
+    ALTER COLUMN card_ids TYPE UUID[] USING translate(card_ids::text, '[]"', '{}')::uuid[];
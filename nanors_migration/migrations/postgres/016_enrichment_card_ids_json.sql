@@ -0,0 +1,6 @@
+-- Migration: Store enrichment card ids as JSON
+-- SQLite and MySQL keep card_ids as a JSON array; switch Postgres from
+-- UUID[] to JSONB so a single entity maps the column on every backend.
+
+ALTER TABLE enrichment_records
+    ALTER COLUMN card_ids TYPE JSONB USING to_jsonb(card_ids);
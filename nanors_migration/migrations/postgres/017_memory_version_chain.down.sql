@@ -0,0 +1,10 @@
+-- Revert: Append-only version chain for memory facts
+-- Superseded versions stay as ordinary rows.
+
+DROP INDEX IF EXISTS idx_memory_items_current;
+DROP INDEX IF EXISTS idx_memory_items_previous_version;
+
+ALTER TABLE memory_items
+    DROP COLUMN IF EXISTS version_relation,
+    DROP COLUMN IF EXISTS superseded_at,
+    DROP COLUMN IF EXISTS previous_version_id;
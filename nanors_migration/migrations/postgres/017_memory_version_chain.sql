@@ -0,0 +1,19 @@
+-- Migration: Append-only version chain for memory facts
+-- A new version of a fact is a new row pointing at the one it supersedes;
+-- the old row is kept with superseded_at set. Retrieval reads current rows
+-- (superseded_at IS NULL); history questions walk previous_version_id.
+--
+-- version_relation says how a row relates to its previous version:
+-- Sets (first version), Updates, Extends or Retracts.
+
+ALTER TABLE memory_items
+    ADD COLUMN IF NOT EXISTS previous_version_id UUID,
+    ADD COLUMN IF NOT EXISTS superseded_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS version_relation VARCHAR(16) NOT NULL DEFAULT 'Sets';
+
+CREATE INDEX IF NOT EXISTS idx_memory_items_previous_version
+    ON memory_items(previous_version_id);
+
+CREATE INDEX IF NOT EXISTS idx_memory_items_current
+    ON memory_items(user_scope)
+    WHERE superseded_at IS NULL;
@@ -0,0 +1,4 @@
+-- Revert: Persist group listen mode
+-- Listen mode is off everywhere afterwards.
+
+DROP TABLE IF EXISTS listening_chats;
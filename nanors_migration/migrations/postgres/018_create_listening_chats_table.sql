@@ -0,0 +1,12 @@
+-- Migration: Persist group listen mode
+-- Chats listed here have listen mode on: messages not addressed to the bot
+-- are stored in their sender's memory without a reply.
+
+CREATE TABLE IF NOT EXISTS listening_chats (
+    channel VARCHAR(64) NOT NULL,          -- e.g., "telegram"
+    chat_id VARCHAR(255) NOT NULL,         -- channel-specific chat identifier
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (channel, chat_id)
+);
+
+COMMENT ON TABLE listening_chats IS 'Group chats with listen mode on';
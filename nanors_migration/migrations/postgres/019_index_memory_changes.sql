@@ -0,0 +1,8 @@
+-- Migration: Index memory changes by time
+-- Before searching, each process picks up the memories other processes
+-- wrote or superseded since its last look.
+
+CREATE INDEX IF NOT EXISTS idx_memory_items_updated_at
+    ON memory_items (updated_at);
+CREATE INDEX IF NOT EXISTS idx_memory_items_superseded_at
+    ON memory_items (superseded_at);
@@ -0,0 +1,22 @@
+-- Memory Tables Migration for SQLite
+-- Creates memory_items in its current shape. The Postgres history (resources,
+-- categories, version columns) was dropped again before SQLite was
+-- supported, so it is not replayed here.
+
+CREATE TABLE IF NOT EXISTS memory_items (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_scope VARCHAR(255) NOT NULL DEFAULT 'global',
+    memory_type VARCHAR(64) NOT NULL,
+    summary TEXT NOT NULL,
+    embedding TEXT,
+    happened_at TEXT NOT NULL,
+    extra TEXT,
+    content_hash VARCHAR(64) NOT NULL,
+    reinforcement_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_items_user_scope ON memory_items(user_scope);
+CREATE INDEX IF NOT EXISTS idx_memory_items_content_hash ON memory_items(content_hash);
+CREATE INDEX IF NOT EXISTS idx_memory_items_user_scope_hash ON memory_items(user_scope, content_hash);
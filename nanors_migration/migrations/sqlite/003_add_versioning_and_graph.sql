@@ -0,0 +1,2 @@
+-- Migration: Add versioning and graph-aware memory support
+-- Nothing to do on SQLite: these tables and columns were removed in 006 and 007.
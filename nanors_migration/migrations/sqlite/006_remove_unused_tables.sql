@@ -0,0 +1,2 @@
+-- Migration: Remove unused versioning and graph tables
+-- Nothing to do on SQLite: the tables were never created.
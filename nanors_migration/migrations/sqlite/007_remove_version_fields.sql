@@ -0,0 +1,2 @@
+-- Migration: Remove unused version fields from memory_items
+-- Nothing to do on SQLite: the columns were never created.
@@ -0,0 +1,2 @@
+-- Migration: Remove unused tiered retrieval tables
+-- Nothing to do on SQLite: the tables were never created.
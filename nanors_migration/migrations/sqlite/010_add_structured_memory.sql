@@ -0,0 +1,29 @@
+-- Migration: Add structured memory cards
+-- memory_cards holds entity/slot/value triples extracted from memories.
+-- (query_expansions from the Postgres migration was removed in 012.)
+
+CREATE TABLE IF NOT EXISTS memory_cards (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_scope VARCHAR(255) NOT NULL,
+    kind VARCHAR(32) NOT NULL DEFAULT 'fact',
+    entity VARCHAR(255) NOT NULL,
+    slot VARCHAR(255) NOT NULL,
+    value TEXT NOT NULL,
+    polarity VARCHAR(16),
+    event_date TEXT,
+    document_date TEXT,
+    version_key VARCHAR(511),
+    version_relation VARCHAR(32) NOT NULL DEFAULT 'Sets',
+    source_memory_id BLOB REFERENCES memory_items(id) ON DELETE CASCADE,
+    engine VARCHAR(64) NOT NULL DEFAULT 'rules',
+    engine_version VARCHAR(64) NOT NULL DEFAULT '1.0.0',
+    confidence REAL,
+    extra TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_cards_entity_slot ON memory_cards(user_scope, entity, slot);
+CREATE INDEX IF NOT EXISTS idx_cards_version_key ON memory_cards(user_scope, version_key);
+CREATE INDEX IF NOT EXISTS idx_cards_source_memory ON memory_cards(source_memory_id);
+CREATE INDEX IF NOT EXISTS idx_cards_slot_value ON memory_cards(user_scope, slot, value);
@@ -0,0 +1,23 @@
+-- Migration: Add enrichment tracking for incremental processing
+-- card_ids is a JSON array of card ids (Postgres uses UUID[]).
+
+CREATE TABLE IF NOT EXISTS enrichment_records (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_scope VARCHAR(255) NOT NULL,
+    memory_id BLOB NOT NULL REFERENCES memory_items(id) ON DELETE CASCADE,
+    engine_kind VARCHAR(64) NOT NULL,
+    engine_version VARCHAR(64) NOT NULL,
+    enriched_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    card_ids TEXT,
+    success INTEGER NOT NULL DEFAULT 1,
+    error_message TEXT,
+    extra TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT unique_enrichment UNIQUE (user_scope, memory_id, engine_kind, engine_version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_enrichment_memory ON enrichment_records(user_scope, memory_id);
+CREATE INDEX IF NOT EXISTS idx_enrichment_time ON enrichment_records(user_scope, engine_kind, enriched_at DESC);
+CREATE INDEX IF NOT EXISTS idx_enrichment_failed
+    ON enrichment_records(user_scope, success, enriched_at)
+    WHERE success = 0;
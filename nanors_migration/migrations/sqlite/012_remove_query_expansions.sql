@@ -0,0 +1,2 @@
+-- Migration: Remove unused query_expansions table
+-- Nothing to do on SQLite: the table was never created.
@@ -0,0 +1,17 @@
+-- Migration: Persist chat-to-session mapping for chat channels
+-- Each channel chat has at most one active session; resetting archives it.
+
+CREATE TABLE IF NOT EXISTS chat_sessions (
+    session_id BLOB PRIMARY KEY NOT NULL,
+    channel VARCHAR(64) NOT NULL,
+    chat_id VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    archived INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_chat_sessions_active
+    ON chat_sessions(channel, chat_id)
+    WHERE archived = 0;
+
+CREATE INDEX IF NOT EXISTS idx_chat_sessions_chat
+    ON chat_sessions(channel, chat_id, created_at DESC);
@@ -0,0 +1,2 @@
+-- Migration: Default memory scope
+-- Nothing to do on SQLite: memory_items.user_scope defaults to 'global' since 001.
@@ -0,0 +1,2 @@
+-- Revert: Database-side vector search with pgvector
+-- Nothing to do on SQLite: vector search uses the in-process HNSW index.
@@ -0,0 +1,9 @@
+-- Revert: Append-only version chain for memory facts
+-- Superseded versions stay as ordinary rows.
+
+DROP INDEX IF EXISTS idx_memory_items_current;
+DROP INDEX IF EXISTS idx_memory_items_previous_version;
+
+ALTER TABLE memory_items DROP COLUMN version_relation;
+ALTER TABLE memory_items DROP COLUMN superseded_at;
+ALTER TABLE memory_items DROP COLUMN previous_version_id;
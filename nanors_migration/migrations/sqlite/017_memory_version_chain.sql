@@ -0,0 +1,14 @@
+-- Migration: Append-only version chain for memory facts
+-- New versions point at the row they supersede; retrieval reads rows with
+-- superseded_at IS NULL.
+
+ALTER TABLE memory_items ADD COLUMN previous_version_id BLOB;
+ALTER TABLE memory_items ADD COLUMN superseded_at TEXT;
+ALTER TABLE memory_items ADD COLUMN version_relation VARCHAR(16) NOT NULL DEFAULT 'Sets';
+
+CREATE INDEX IF NOT EXISTS idx_memory_items_previous_version
+    ON memory_items(previous_version_id);
+
+CREATE INDEX IF NOT EXISTS idx_memory_items_current
+    ON memory_items(user_scope)
+    WHERE superseded_at IS NULL;
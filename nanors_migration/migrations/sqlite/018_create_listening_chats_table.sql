@@ -0,0 +1,9 @@
+-- Migration: Persist group listen mode
+-- Chats listed here have listen mode on.
+
+CREATE TABLE IF NOT EXISTS listening_chats (
+    channel VARCHAR(64) NOT NULL,
+    chat_id VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (channel, chat_id)
+);
@@ -0,0 +1,4 @@
+-- Revert: Index memory changes by time
+
+DROP INDEX IF EXISTS idx_memory_items_superseded_at;
+DROP INDEX IF EXISTS idx_memory_items_updated_at;
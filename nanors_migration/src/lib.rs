@@ -0,0 +1,405 @@
+#![warn(
+    clippy::all,
+    clippy::nursery,
+    clippy::pedantic,
+    clippy::style,
+    clippy::complexity,
+    clippy::perf,
+    clippy::correctness,
+    clippy::suspicious,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+#![allow(
+    clippy::similar_names,
+    clippy::missing_safety_doc,
+    clippy::missing_panics_doc,
+    clippy::missing_errors_doc
+)]
+
+//! Embedded schema migrations.
+//!
+//! Every migration has a Postgres, `SQLite` and `MySQL` variant under
+//! `migrations/<backend>/`, compiled into the binary. Applied versions are
+//! recorded in the `nanors_migrations` table. [`migrate`] runs on every
+//! `MemoryManager` connect; `nanors db migrate|status|rollback` drives the
+//! same functions by hand.
+//!
+//! Postgres databases set up before the migrator existed (SQL files applied
+//! by hand) are adopted: the migrator detects how far the schema got and
+//! records those versions as applied instead of replaying them.
+
+use sea_orm::{
+    ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement, TransactionTrait, Value,
+};
+use std::path::PathBuf;
+use tracing::info;
+
+mod migrations;
+
+pub use migrations::MIGRATIONS;
+
+/// Table recording applied migrations.
+const MIGRATIONS_TABLE: &str = "nanors_migrations";
+
+/// SQL for one direction of a migration, per backend.
+#[derive(Debug, Clone, Copy)]
+pub struct Sql {
+    pub postgres: &'static str,
+    pub sqlite: &'static str,
+    pub mysql: &'static str,
+}
+
+impl Sql {
+    fn for_backend(&self, backend: DbBackend) -> anyhow::Result<&'static str> {
+        match backend {
+            DbBackend::Postgres => Ok(self.postgres),
+            DbBackend::Sqlite => Ok(self.sqlite),
+            DbBackend::MySql => Ok(self.mysql),
+            other => anyhow::bail!("unsupported database backend: {other:?}"),
+        }
+    }
+}
+
+/// One schema change.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub up: Sql,
+    /// `None` for migrations that drop data and cannot be reverted
+    pub down: Option<Sql>,
+}
+
+/// Whether a migration has been applied, and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: &'static str,
+    /// RFC 3339 timestamp, `None` while pending
+    pub applied_at: Option<String>,
+}
+
+/// Connect without migrating, e.g. to inspect or roll back the schema.
+///
+/// For a file-backed `SQLite` database the parent directory is created
+/// first, so the default `~/.nanors/nanors.db` works on a fresh install.
+pub async fn connect(database_url: &str) -> anyhow::Result<DatabaseConnection> {
+    if let Some(dir) = sqlite_file(database_url)
+        .as_deref()
+        .and_then(std::path::Path::parent)
+        .filter(|dir| !dir.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    Ok(Database::connect(database_url).await?)
+}
+
+/// Database file of a `SQLite` URL; `None` for in-memory databases and
+/// other backends.
+#[must_use]
+pub fn sqlite_file(database_url: &str) -> Option<PathBuf> {
+    let rest = database_url.strip_prefix("sqlite:")?;
+    let rest = rest.strip_prefix("//").unwrap_or(rest);
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if path.is_empty() || path == ":memory:" || query.contains("mode=memory") {
+        return None;
+    }
+    Some(PathBuf::from(path))
+}
+
+/// Apply all pending migrations in order. Returns the versions applied.
+pub async fn migrate(db: &DatabaseConnection) -> anyhow::Result<Vec<u32>> {
+    ensure_migrations_table(db).await?;
+    let mut applied = applied_versions(db).await?;
+    if applied.is_empty() {
+        let baseline = detect_baseline(db).await?;
+        if baseline > 0 {
+            info!("Adopting existing schema at migration {baseline:03}");
+            for migration in MIGRATIONS.iter().filter(|m| m.version <= baseline) {
+                record(db, migration.version, migration.name).await?;
+                applied.push(migration.version);
+            }
+        }
+    }
+
+    let backend = db.get_database_backend();
+    let mut ran = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| !applied.contains(&m.version)) {
+        info!(
+            "Applying migration {:03} {}",
+            migration.version, migration.name
+        );
+        let txn = db.begin().await?;
+        execute_script(&txn, migration.up.for_backend(backend)?)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "migration {:03} {} failed: {e}",
+                    migration.version,
+                    migration.name
+                )
+            })?;
+        record(&txn, migration.version, migration.name).await?;
+        txn.commit().await?;
+        ran.push(migration.version);
+    }
+    Ok(ran)
+}
+
+/// Every known migration with its applied time.
+pub async fn status(db: &DatabaseConnection) -> anyhow::Result<Vec<MigrationStatus>> {
+    ensure_migrations_table(db).await?;
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        format!("SELECT version, applied_at FROM {MIGRATIONS_TABLE}"),
+    );
+    let mut applied = std::collections::HashMap::new();
+    for row in db.query_all_raw(stmt).await? {
+        let version: i32 = row.try_get("", "version")?;
+        let applied_at: String = row.try_get("", "applied_at")?;
+        applied.insert(version, applied_at);
+    }
+    Ok(MIGRATIONS
+        .iter()
+        .map(|m| MigrationStatus {
+            version: m.version,
+            name: m.name,
+            applied_at: i32::try_from(m.version)
+                .ok()
+                .and_then(|v| applied.get(&v).cloned()),
+        })
+        .collect())
+}
+
+/// Revert the last `steps` applied migrations, newest first. Returns the
+/// versions reverted.
+///
+/// Stops with an error at a migration that cannot be reverted; the ones
+/// reverted before it stay reverted.
+pub async fn rollback(db: &DatabaseConnection, steps: usize) -> anyhow::Result<Vec<u32>> {
+    ensure_migrations_table(db).await?;
+    let mut applied = applied_versions(db).await?;
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let backend = db.get_database_backend();
+    let mut reverted = Vec::new();
+    for version in applied.into_iter().take(steps) {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.version == version)
+            .ok_or_else(|| anyhow::anyhow!("migration {version:03} is unknown to this build"))?;
+        let down = migration.down.ok_or_else(|| {
+            anyhow::anyhow!(
+                "migration {:03} {} cannot be rolled back",
+                migration.version,
+                migration.name
+            )
+        })?;
+
+        info!(
+            "Reverting migration {:03} {}",
+            migration.version, migration.name
+        );
+        let txn = db.begin().await?;
+        execute_script(&txn, down.for_backend(backend)?).await?;
+        txn.execute_raw(Statement::from_sql_and_values(
+            backend,
+            format!(
+                "DELETE FROM {MIGRATIONS_TABLE} WHERE version = {}",
+                placeholder(backend, 1)
+            ),
+            [Value::from(i64::from(version))],
+        ))
+        .await?;
+        txn.commit().await?;
+        reverted.push(version);
+    }
+    Ok(reverted)
+}
+
+async fn ensure_migrations_table(db: &DatabaseConnection) -> anyhow::Result<()> {
+    db.execute_unprepared(&format!(
+        "CREATE TABLE IF NOT EXISTS {MIGRATIONS_TABLE} (
+            version INTEGER PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            applied_at VARCHAR(64) NOT NULL
+        )"
+    ))
+    .await?;
+    Ok(())
+}
+
+async fn applied_versions(db: &DatabaseConnection) -> anyhow::Result<Vec<u32>> {
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        format!("SELECT version FROM {MIGRATIONS_TABLE}"),
+    );
+    db.query_all_raw(stmt)
+        .await?
+        .iter()
+        .map(|row| Ok(u32::try_from(row.try_get::<i32>("", "version")?)?))
+        .collect()
+}
+
+async fn record(db: &impl ConnectionTrait, version: u32, name: &str) -> anyhow::Result<()> {
+    let backend = db.get_database_backend();
+    db.execute_raw(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "INSERT INTO {MIGRATIONS_TABLE} (version, name, applied_at) VALUES ({}, {}, {})",
+            placeholder(backend, 1),
+            placeholder(backend, 2),
+            placeholder(backend, 3)
+        ),
+        [
+            Value::from(i64::from(version)),
+            Value::from(name),
+            Value::from(chrono::Utc::now().to_rfc3339()),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Run a migration script, skipping scripts that are only comments.
+async fn execute_script(db: &impl ConnectionTrait, sql: &str) -> anyhow::Result<()> {
+    let has_statements = sql
+        .lines()
+        .map(str::trim)
+        .any(|line| !line.is_empty() && !line.starts_with("--"));
+    if has_statements {
+        db.execute_unprepared(sql).await?;
+    }
+    Ok(())
+}
+
+/// The `n`th bind parameter in the backend's syntax.
+fn placeholder(backend: DbBackend, n: usize) -> String {
+    match backend {
+        DbBackend::Postgres => format!("${n}"),
+        _ => "?".to_string(),
+    }
+}
+
+/// Latest migration a hand-migrated Postgres schema already has, judged by
+/// the tables each migration left behind; `0` for a fresh database.
+///
+/// Everything after the detected version is idempotent, so erring low only
+/// replays harmless statements.
+async fn detect_baseline(db: &DatabaseConnection) -> anyhow::Result<u32> {
+    if db.get_database_backend() != DbBackend::Postgres {
+        return Ok(0);
+    }
+    let baseline = if has_column(db, "chat_sessions", "session_id").await? {
+        13
+    } else if has_column(db, "enrichment_records", "memory_id").await? {
+        12
+    } else if has_column(db, "memory_cards", "source_memory_id").await? {
+        10
+    } else {
+        u32::from(has_column(db, "memory_items", "id").await?)
+    };
+    Ok(baseline)
+}
+
+async fn has_column(db: &DatabaseConnection, table: &str, column: &str) -> anyhow::Result<bool> {
+    let stmt = Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT 1 FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = $1 AND column_name = $2",
+        [Value::from(table), Value::from(column)],
+    );
+    Ok(db.query_one_raw(stmt).await?.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanors_entities::{chat_sessions, memory_items, sessions};
+    use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+
+    #[test]
+    fn test_sqlite_file() {
+        assert_eq!(
+            sqlite_file("sqlite:///home/me/.nanors/nanors.db?mode=rwc"),
+            Some(PathBuf::from("/home/me/.nanors/nanors.db"))
+        );
+        assert_eq!(
+            sqlite_file("sqlite:nanors.db"),
+            Some(PathBuf::from("nanors.db"))
+        );
+        assert_eq!(sqlite_file("sqlite::memory:"), None);
+        assert_eq!(sqlite_file("sqlite://file.db?mode=memory"), None);
+        assert_eq!(sqlite_file("postgresql://localhost/nanors"), None);
+    }
+
+    #[test]
+    fn test_migrations_are_numbered_in_order() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, i + 1);
+            assert!(migration.name.starts_with(&format!("{:03}_", i + 1)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_migrate_status_rollback() -> anyhow::Result<()> {
+        let db = connect("sqlite::memory:").await?;
+        let latest = MIGRATIONS.len();
+
+        assert_eq!(migrate(&db).await?.len(), latest);
+        assert!(migrate(&db).await?.is_empty());
+        assert!(status(&db).await?.iter().all(|s| s.applied_at.is_some()));
+
+        // The entities work against the migrated schema
+        let now = chrono::Utc::now();
+        memory_items::ActiveModel {
+            id: Set(uuid::Uuid::now_v7()),
+            user_scope: Set("global".into()),
+            memory_type: Set("episodic".into()),
+            summary: Set("User: 我住在杭州".into()),
+            embedding: Set(Some(serde_json_value(&[0.1, 0.2]))),
+            happened_at: Set(now.into()),
+            extra: Set(None),
+            content_hash: Set("hash".into()),
+            reinforcement_count: Set(0),
+            previous_version_id: Set(None),
+            superseded_at: Set(None),
+            version_relation: Set("Sets".into()),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        }
+        .insert(&db)
+        .await?;
+        sessions::ActiveModel {
+            id: Set(uuid::Uuid::now_v7()),
+            messages: Set("[]".into()),
+            created_at: Set(now.naive_utc()),
+            updated_at: Set(now.naive_utc()),
+        }
+        .insert(&db)
+        .await?;
+        assert_eq!(memory_items::Entity::find().all(&db).await?.len(), 1);
+
+        assert_eq!(rollback(&db, 7).await?, vec![19, 18, 17, 16, 15, 14, 13]);
+        assert!(chat_sessions::Entity::find().all(&db).await.is_err());
+        let pending: Vec<u32> = status(&db)
+            .await?
+            .iter()
+            .filter(|s| s.applied_at.is_none())
+            .map(|s| s.version)
+            .collect();
+        assert_eq!(pending, vec![13, 14, 15, 16, 17, 18, 19]);
+
+        // 012 dropped a table and cannot be undone
+        assert!(rollback(&db, 1).await.is_err());
+
+        assert_eq!(migrate(&db).await?, vec![13, 14, 15, 16, 17, 18, 19]);
+        assert!(chat_sessions::Entity::find().all(&db).await?.is_empty());
+        Ok(())
+    }
+
+    fn serde_json_value(values: &[f64]) -> sea_orm::JsonValue {
+        sea_orm::JsonValue::Array(values.iter().map(|v| (*v).into()).collect())
+    }
+}
@@ -0,0 +1,57 @@
+//! The migration list. Add new migrations at the end; never renumber.
+
+use crate::{Migration, Sql};
+
+/// Load `migrations/<backend>/<file><ext>` for every backend.
+macro_rules! sql {
+    ($file:literal, $ext:literal) => {
+        Sql {
+            postgres: include_str!(concat!("../migrations/postgres/", $file, $ext)),
+            sqlite: include_str!(concat!("../migrations/sqlite/", $file, $ext)),
+            mysql: include_str!(concat!("../migrations/mysql/", $file, $ext)),
+        }
+    };
+}
+
+/// A migration from `<file>.sql`, reversible through `<file>.down.sql`.
+macro_rules! migration {
+    ($version:literal, $file:literal) => {
+        Migration {
+            version: $version,
+            name: $file,
+            up: sql!($file, ".sql"),
+            down: None,
+        }
+    };
+    ($version:literal, $file:literal, reversible) => {
+        Migration {
+            version: $version,
+            name: $file,
+            up: sql!($file, ".sql"),
+            down: Some(sql!($file, ".down.sql")),
+        }
+    };
+}
+
+/// All migrations, oldest first.
+pub static MIGRATIONS: &[Migration] = &[
+    migration!(1, "001_create_memory_tables"),
+    migration!(2, "002_create_sessions_table"),
+    migration!(3, "003_add_versioning_and_graph"),
+    migration!(4, "004_add_keyword_versioning"),
+    migration!(5, "005_remove_keyword_versioning"),
+    migration!(6, "006_remove_unused_tables"),
+    migration!(7, "007_remove_version_fields"),
+    migration!(8, "008_remove_unused_tables"),
+    migration!(9, "009_remove_resource_id"),
+    migration!(10, "010_add_structured_memory"),
+    migration!(11, "011_add_enrichment_tracking"),
+    migration!(12, "012_remove_query_expansions"),
+    migration!(13, "013_create_chat_sessions_table", reversible),
+    migration!(14, "014_default_memory_scope", reversible),
+    migration!(15, "015_add_pgvector", reversible),
+    migration!(16, "016_enrichment_card_ids_json", reversible),
+    migration!(17, "017_memory_version_chain", reversible),
+    migration!(18, "018_create_listening_chats_table", reversible),
+    migration!(19, "019_index_memory_changes", reversible),
+];
@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use nanors_core::{
     ChatMessage, ContentBlock, LLMProvider, LLMResponse, LLMToolResponse, MessageContent, Role,
+    SpeechToText,
 };
 use reqwest::Client;
+use reqwest::multipart::{Form, Part};
 use serde_json::json;
 use tracing::{info, warn};
 
@@ -90,6 +92,7 @@ impl ZhipuProvider {
                 // Zhipu API format: separate tool_calls and content fields
                 let mut text_parts = Vec::new();
                 let mut tool_calls = Vec::new();
+                let mut image_parts = Vec::new();
 
                 for block in blocks {
                     match block {
@@ -110,6 +113,12 @@ impl ZhipuProvider {
                                 }
                             }));
                         }
+                        ContentBlock::Image { media_type, data } => {
+                            image_parts.push(json!({
+                                "type": "image_url",
+                                "image_url": { "url": format!("data:{media_type};base64,{data}") },
+                            }));
+                        }
                         ContentBlock::Text { .. } | ContentBlock::ToolResult { .. } => {
                             // Skip empty text blocks and ToolResult blocks
                             // (ToolResult should only appear in Role::Tool messages,
@@ -122,8 +131,12 @@ impl ZhipuProvider {
                     "role": role_to_zhipu(&msg.role),
                 });
 
-                // Add content if there's text
-                if !text_parts.is_empty() {
+                // Vision models take text and images as a list of parts
+                if !image_parts.is_empty() {
+                    let mut parts = vec![json!({ "type": "text", "text": text_parts.join("\n") })];
+                    parts.extend(image_parts);
+                    message["content"] = json!(parts);
+                } else if !text_parts.is_empty() {
                     message["content"] = json!(text_parts.join("\n"));
                 } else if tool_calls.is_empty() {
                     // No content and no tool calls - add empty content
@@ -222,6 +235,39 @@ impl ZhipuProvider {
     }
 }
 
+#[async_trait]
+impl SpeechToText for ZhipuProvider {
+    async fn transcribe(&self, audio: Vec<u8>, mime_type: &str) -> anyhow::Result<String> {
+        let extension = mime_type
+            .rsplit('/')
+            .next()
+            .filter(|ext| !ext.is_empty())
+            .unwrap_or("ogg");
+        let file = Part::bytes(audio)
+            .file_name(format!("audio.{extension}"))
+            .mime_str(mime_type)?;
+        let form = Form::new()
+            .text("model", "glm-asr")
+            .text("stream", "false")
+            .part("file", file);
+
+        info!("Sending transcription request to Zhipu API");
+        let response = self
+            .client
+            .post(format!("{}/audio/transcriptions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .multipart(form)
+            .send()
+            .await?;
+
+        let response = Self::handle_http_response(response).await?;
+        response["text"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format: missing text"))
+    }
+}
+
 const fn role_to_zhipu(role: &Role) -> &str {
     match role {
         Role::User => "user",
@@ -262,6 +308,14 @@ impl LLMProvider for ZhipuProvider {
         "glm-4-flash"
     }
 
+    /// GLM vision models carry a `v` version suffix: `glm-4v-plus`, `glm-4.5v`.
+    fn supports_vision(&self, model: &str) -> bool {
+        model.split('-').any(|part| {
+            part.strip_suffix('v')
+                .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        })
+    }
+
     async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
         let response = self
             .client
@@ -399,3 +453,42 @@ impl ZhipuProvider {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_blocks_become_content_parts() {
+        let msg = ChatMessage {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![
+                ContentBlock::Text {
+                    text: "what is this?".to_string(),
+                },
+                ContentBlock::Image {
+                    media_type: "image/jpeg".to_string(),
+                    data: "aGk=".to_string(),
+                },
+            ]),
+        };
+
+        let converted = ZhipuProvider::convert_message_to_zhipu(&msg);
+        assert_eq!(
+            converted["content"],
+            json!([
+                { "type": "text", "text": "what is this?" },
+                { "type": "image_url", "image_url": { "url": "data:image/jpeg;base64,aGk=" } },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_supports_vision_by_model_name() {
+        let provider = ZhipuProvider::new(String::new());
+        assert!(provider.supports_vision("glm-4v-plus"));
+        assert!(provider.supports_vision("glm-4.5v"));
+        assert!(!provider.supports_vision("glm-4-flash"));
+        assert!(!provider.supports_vision("glm-4.7"));
+    }
+}
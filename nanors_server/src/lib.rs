@@ -0,0 +1,31 @@
+#![warn(
+    clippy::all,
+    clippy::nursery,
+    clippy::pedantic,
+    clippy::style,
+    clippy::complexity,
+    clippy::perf,
+    clippy::correctness,
+    clippy::suspicious,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+#![allow(
+    clippy::similar_names,
+    clippy::missing_safety_doc,
+    clippy::missing_panics_doc,
+    clippy::missing_errors_doc
+)]
+
+//! OpenAI-compatible HTTP API for nanors.
+//!
+//! Exposes `/v1/chat/completions` and `/v1/models` so existing `OpenAI` clients
+//! can talk to the agent loop, with memory retrieval and tools, over HTTP.
+//! Streaming is emulated: `stream: true` gets the whole reply as one SSE
+//! delta once the turn is done.
+
+mod openai;
+mod server;
+
+pub use nanors_core::AgentFactory;
+pub use server::{ApiServer, SESSION_HEADER, session_id_for};
@@ -0,0 +1,154 @@
+//! `OpenAI` chat completions wire format.
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Body of `POST /v1/chat/completions`.
+///
+/// Sampling parameters are accepted but ignored; the agent config decides them.
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionRequest {
+    #[serde(default)]
+    pub model: Option<String>,
+    pub messages: Vec<RequestMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    /// End-user id, mapped to a persistent session
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Value,
+}
+
+impl RequestMessage {
+    /// Text of the message; non-text content parts are dropped.
+    pub fn text(&self) -> String {
+        match &self.content {
+            Value::String(text) => text.clone(),
+            Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
+}
+
+impl ChatCompletionRequest {
+    /// The newest user message.
+    ///
+    /// Earlier turns are loaded from the nanors session, so the history the
+    /// client resends is not replayed.
+    pub fn last_user_message(&self) -> Option<String> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.role == "user")
+            .map(RequestMessage::text)
+            .filter(|text| !text.trim().is_empty())
+    }
+}
+
+/// Non-streaming `chat.completion` object.
+pub fn completion(id: &str, model: &str, created: i64, content: &str) -> Value {
+    json!({
+        "id": id,
+        "object": "chat.completion",
+        "created": created,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop",
+        }],
+    })
+}
+
+/// Streaming `chat.completion.chunk` object.
+pub fn chunk(
+    id: &str,
+    model: &str,
+    created: i64,
+    delta: Value,
+    finish_reason: Option<&str>,
+) -> Value {
+    let mut msg = json!({
+        "id": id,
+        "object": "chat.completion.chunk",
+        "created": created,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "finish_reason": finish_reason,
+        }],
+    });
+    msg["choices"][0]["delta"] = delta;
+    msg
+}
+
+/// Body of `GET /v1/models`.
+pub fn model_list(models: &[&str]) -> Value {
+    let data: Vec<Value> = models
+        .iter()
+        .map(|id| {
+            json!({
+                "id": id,
+                "object": "model",
+                "created": 0,
+                "owned_by": "nanors",
+            })
+        })
+        .collect();
+    json!({"object": "list", "data": data})
+}
+
+/// `OpenAI`-style error body.
+pub fn error_body(message: &str, error_type: &str) -> Value {
+    json!({
+        "error": {
+            "message": message,
+            "type": error_type,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_user_message_handles_content_parts() -> anyhow::Result<()> {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "glm-4.7-flash",
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "first"},
+                {"role": "assistant", "content": "ok"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "second"},
+                    {"type": "image_url", "image_url": {"url": "http://x"}}
+                ]}
+            ],
+            "temperature": 0.2
+        }))?;
+
+        assert!(!request.stream);
+        assert_eq!(request.last_user_message().as_deref(), Some("second"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_user_message_requires_text() -> anyhow::Result<()> {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "messages": [{"role": "system", "content": "hi"}]
+        }))?;
+        assert!(request.last_user_message().is_none());
+        Ok(())
+    }
+}
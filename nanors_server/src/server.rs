@@ -0,0 +1,451 @@
+//! HTTP routes and session mapping.
+
+use axum::{
+    Json, Router,
+    extract::{State, rejection::JsonRejection},
+    http::{HeaderMap, StatusCode, header},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use futures_util::{StreamExt, stream};
+use nanors_core::{AgentFactory, LLMProvider, MemoryScope, SessionStorage};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::{convert::Infallible, sync::Arc};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::openai::{self, ChatCompletionRequest};
+
+/// Request header naming one of the caller's conversations. The key is
+/// echoed back in the same header; send it again to continue the
+/// conversation.
+pub const SESSION_HEADER: &str = "x-session-id";
+
+/// Conversation key of a named `user` that sends no session header.
+const DEFAULT_SESSION_KEY: &str = "default";
+
+/// Map a caller's conversation key to a session id.
+///
+/// The id is derived from the `user` field as well as the key, so a caller
+/// only ever reaches sessions of its own `user`: not another user's, and
+/// not ones created by other frontends, even when it knows their UUIDs.
+#[must_use]
+pub fn session_id_for(user: Option<&str>, key: &str) -> Uuid {
+    let user = user.unwrap_or_default();
+    let digest = Sha256::digest(format!("nanors:openai:{}:{user}:{key}", user.len()).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
+/// OpenAI-compatible API server.
+pub struct ApiServer<P, S>
+where
+    P: Send + Sync,
+    S: Send + Sync,
+{
+    factory: AgentFactory<P, S>,
+    default_model: String,
+    api_key: Option<String>,
+}
+
+impl<P, S> ApiServer<P, S>
+where
+    P: LLMProvider + Clone + Send + Sync + 'static,
+    S: SessionStorage + Send + Sync + 'static,
+{
+    pub fn new(factory: AgentFactory<P, S>, default_model: impl Into<String>) -> Self {
+        Self {
+            factory,
+            default_model: default_model.into(),
+            api_key: None,
+        }
+    }
+
+    /// Require `Authorization: Bearer <key>` on every request.
+    #[must_use]
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key.filter(|k| !k.is_empty());
+        self
+    }
+
+    /// Build the axum router.
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/v1/chat/completions", post(chat_completions::<P, S>))
+            .route("/v1/models", get(list_models::<P, S>))
+            .with_state(Arc::new(self))
+    }
+
+    /// Listen on `addr` until the process is stopped.
+    ///
+    /// Without an API key anyone who can reach the server can act as any
+    /// `user`, so only loopback addresses are allowed then.
+    pub async fn serve(self, addr: &str) -> anyhow::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        if self.api_key.is_none() && !listener.local_addr()?.ip().is_loopback() {
+            anyhow::bail!(
+                "Refusing to listen on {addr} without an API key; set server.api_key or listen on a loopback address"
+            );
+        }
+        info!("OpenAI-compatible API listening on http://{addr}/v1");
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(expected) = &self.api_key else {
+            return true;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            == Some(expected.as_str())
+    }
+}
+
+fn unauthorized() -> Response {
+    error_response(
+        StatusCode::UNAUTHORIZED,
+        "Invalid API key",
+        "invalid_request_error",
+    )
+}
+
+fn error_response(status: StatusCode, message: &str, error_type: &str) -> Response {
+    (status, Json(openai::error_body(message, error_type))).into_response()
+}
+
+/// Conversation key and session of a request: the key from the header,
+/// else the user's default conversation, else a fresh one.
+fn resolve_session(headers: &HeaderMap, user: Option<&str>) -> (String, Uuid) {
+    let key = headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(String::from)
+        .or_else(|| user.map(|_| DEFAULT_SESSION_KEY.to_string()))
+        .unwrap_or_else(|| Uuid::now_v7().to_string());
+    let session_id = session_id_for(user, &key);
+    (key, session_id)
+}
+
+/// Scope of the memories anonymous requests share, apart from the global
+/// scope of the CLI.
+const ANONYMOUS_AGENT: &str = "api";
+
+/// Memory scope for a request: the `user` field's own memories, or the
+/// API's anonymous scope.
+fn memory_scope(user: Option<&str>) -> MemoryScope {
+    user.filter(|user| !user.is_empty()).map_or_else(
+        || MemoryScope::Agent(ANONYMOUS_AGENT.to_string()),
+        |user| MemoryScope::user("api", user),
+    )
+}
+
+async fn list_models<P, S>(
+    State(state): State<Arc<ApiServer<P, S>>>,
+    headers: HeaderMap,
+) -> Response
+where
+    P: LLMProvider + Clone + Send + Sync + 'static,
+    S: SessionStorage + Send + Sync + 'static,
+{
+    if !state.is_authorized(&headers) {
+        return unauthorized();
+    }
+    Json(openai::model_list(&[state.default_model.as_str()])).into_response()
+}
+
+async fn chat_completions<P, S>(
+    State(state): State<Arc<ApiServer<P, S>>>,
+    headers: HeaderMap,
+    body: Result<Json<ChatCompletionRequest>, JsonRejection>,
+) -> Response
+where
+    P: LLMProvider + Clone + Send + Sync + 'static,
+    S: SessionStorage + Send + Sync + 'static,
+{
+    if !state.is_authorized(&headers) {
+        return unauthorized();
+    }
+    let request = match body {
+        Ok(Json(request)) => request,
+        Err(e) => {
+            return error_response(e.status(), &e.body_text(), "invalid_request_error");
+        }
+    };
+    let Some(content) = request.last_user_message() else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "No user message in request",
+            "invalid_request_error",
+        );
+    };
+
+    // Session and memory scope both belong to the `user` field
+    let user = request
+        .user
+        .as_deref()
+        .map(str::trim)
+        .filter(|user| !user.is_empty());
+    let (session_key, session_id) = resolve_session(&headers, user);
+    let scope = memory_scope(user);
+    let model = request
+        .model
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| state.default_model.clone());
+    let completion_id = format!("chatcmpl-{}", Uuid::now_v7().simple());
+    let created = chrono::Utc::now().timestamp();
+    let session_header = [(SESSION_HEADER, session_key)];
+
+    info!(
+        "API request for session {session_id} (stream: {})",
+        request.stream
+    );
+
+    if !request.stream {
+        let agent = (state.factory)(&model).with_memory_scope(scope);
+        return match agent.process_message(&session_id, &content).await {
+            Ok(text) => (
+                session_header,
+                Json(openai::completion(&completion_id, &model, created, &text)),
+            )
+                .into_response(),
+            Err(e) => {
+                warn!("Agent error for session {session_id}: {e}");
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    &format!("Agent error: {e}"),
+                    "server_error",
+                )
+            }
+        };
+    }
+
+    // Streaming is emulated: the agent loop produces the whole reply at
+    // once, so the stream is a single content delta sent after the turn;
+    // keep-alives cover the time spent in tool calls.
+    let events = stream::once(async move {
+        let agent = (state.factory)(&model).with_memory_scope(scope);
+        let chunks = match agent.process_message(&session_id, &content).await {
+            Ok(text) => vec![
+                openai::chunk(
+                    &completion_id,
+                    &model,
+                    created,
+                    json!({"role": "assistant", "content": ""}),
+                    None,
+                ),
+                openai::chunk(
+                    &completion_id,
+                    &model,
+                    created,
+                    json!({"content": text}),
+                    None,
+                ),
+                openai::chunk(&completion_id, &model, created, json!({}), Some("stop")),
+            ],
+            Err(e) => {
+                warn!("Agent error for session {session_id}: {e}");
+                vec![openai::error_body(
+                    &format!("Agent error: {e}"),
+                    "server_error",
+                )]
+            }
+        };
+        chunks
+            .iter()
+            .map(Value::to_string)
+            .chain(std::iter::once("[DONE]".to_string()))
+            .map(|data| Ok::<_, Infallible>(Event::default().data(data)))
+            .collect::<Vec<_>>()
+    })
+    .flat_map(stream::iter);
+
+    (
+        session_header,
+        Sse::new(events).keep_alive(KeepAlive::default()),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use nanors_core::testing::{InMemorySessions, repeat_factory};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn test_router(api_key: Option<&str>) -> (Router, Arc<InMemorySessions>) {
+        let sessions = Arc::new(InMemorySessions::default());
+        let factory = repeat_factory(Arc::clone(&sessions), Duration::ZERO);
+        let router = ApiServer::new(factory, "glm-test")
+            .with_api_key(api_key.map(String::from))
+            .router();
+        (router, sessions)
+    }
+
+    async fn post_chat(
+        router: &Router,
+        session: Option<&str>,
+        body: Value,
+    ) -> anyhow::Result<(Response, String)> {
+        let mut request =
+            Request::post("/v1/chat/completions").header(header::CONTENT_TYPE, "application/json");
+        if let Some(session) = session {
+            request = request.header(SESSION_HEADER, session);
+        }
+        let response = router
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string()))?)
+            .await?;
+        let (parts, body) = response.into_parts();
+        let bytes = to_bytes(body, usize::MAX).await?;
+        Ok((
+            Response::from_parts(parts, Body::empty()),
+            String::from_utf8(bytes.to_vec())?,
+        ))
+    }
+
+    /// Messages stored in the session of `user`'s conversation `key`.
+    async fn history_len(sessions: &InMemorySessions, user: Option<&str>, key: &str) -> usize {
+        sessions
+            .get(&session_id_for(user, key))
+            .await
+            .ok()
+            .flatten()
+            .map_or(0, |s| s.messages.len())
+    }
+
+    #[test]
+    fn test_session_id_for_is_stable_per_user() {
+        assert_eq!(
+            session_id_for(Some("alice"), "k"),
+            session_id_for(Some("alice"), "k")
+        );
+        assert_ne!(
+            session_id_for(Some("alice"), "k"),
+            session_id_for(Some("bob"), "k")
+        );
+        assert_ne!(
+            session_id_for(Some("a:b"), "c"),
+            session_id_for(Some("a"), "b:c")
+        );
+
+        // UUID keys are hashed too, so other sessions can't be addressed
+        let id = Uuid::now_v7();
+        assert_ne!(session_id_for(None, &id.to_string()), id);
+    }
+
+    #[test]
+    fn test_memory_scope_follows_user_field() {
+        assert_eq!(memory_scope(Some("alice")).to_string(), "user:api:alice");
+        assert_eq!(memory_scope(Some("")).to_string(), "agent:api");
+        assert_eq!(memory_scope(None).to_string(), "agent:api");
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_keeps_session_per_user() -> anyhow::Result<()> {
+        let (router, sessions) = test_router(None);
+        let body = json!({
+            "model": "glm-test",
+            "user": "alice",
+            "messages": [{"role": "user", "content": "hello"}]
+        });
+
+        let (response, text) = post_chat(&router, None, body.clone()).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[SESSION_HEADER], DEFAULT_SESSION_KEY);
+        let first: Value = serde_json::from_str(&text)?;
+        assert_eq!(first["object"], "chat.completion");
+        assert_eq!(first["choices"][0]["message"]["content"], "hello hello");
+
+        // The second turn lands in the same session
+        post_chat(&router, None, body).await?;
+        assert_eq!(
+            history_len(&sessions, Some("alice"), DEFAULT_SESSION_KEY).await,
+            4
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_header_is_scoped_to_user() -> anyhow::Result<()> {
+        let (router, sessions) = test_router(None);
+        let message =
+            |user: &str| json!({"user": user, "messages": [{"role": "user", "content": "hi"}]});
+
+        let (response, _) = post_chat(&router, Some("work"), message("alice")).await?;
+        assert_eq!(response.headers()[SESSION_HEADER], "work");
+        post_chat(&router, Some("work"), message("alice")).await?;
+        // Bob naming the same conversation gets his own session
+        post_chat(&router, Some("work"), message("bob")).await?;
+
+        assert_eq!(history_len(&sessions, Some("alice"), "work").await, 4);
+        assert_eq!(history_len(&sessions, Some("bob"), "work").await, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_streams_sse() -> anyhow::Result<()> {
+        let (router, _) = test_router(None);
+        let (response, text) = post_chat(
+            &router,
+            None,
+            json!({"stream": true, "messages": [{"role": "user", "content": "hi"}]}),
+        )
+        .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let data: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .collect();
+        assert_eq!(data.last(), Some(&"[DONE]"));
+        let content: Value = serde_json::from_str(data[1])?;
+        assert_eq!(content["object"], "chat.completion.chunk");
+        assert_eq!(content["model"], "glm-test");
+        assert_eq!(content["choices"][0]["delta"]["content"], "hi hi");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_and_models() -> anyhow::Result<()> {
+        let (router, _) = test_router(Some("secret"));
+
+        let (response, _) = post_chat(
+            &router,
+            None,
+            json!({"messages": [{"role": "user", "content": "hi"}]}),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let request = Request::get("/v1/models")
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .body(Body::empty())?;
+        let response = router.oneshot(request).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let models: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(models["data"][0]["id"], "glm-test");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_serve_requires_api_key_off_loopback() {
+        let sessions = Arc::new(InMemorySessions::default());
+        let server = ApiServer::new(repeat_factory(sessions, Duration::ZERO), "glm-test");
+        let error = server.serve("0.0.0.0:0").await;
+        assert!(error.is_err_and(|e| e.to_string().contains("API key")));
+    }
+}
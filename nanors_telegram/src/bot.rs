@@ -1,161 +1,161 @@
-use crate::{Error, Result};
-use nanors_config::Config;
-use nanors_core::{AgentConfig, AgentLoop, SessionStorage};
-use nanors_memory::MemoryManager;
+use crate::channel::{CHANNEL_NAME, TelegramChannel};
+use crate::group::BotIdentity;
+use crate::webhook::{self, WebhookOptions};
+use crate::{Command, Error, Result};
+use nanors_core::channel::{ChatGateway, ChatSessionStore, InboundMessage};
+use nanors_core::{AgentFactory, MemoryScope, SpeechToText};
+use nanors_memory::DynMemoryManager;
 use nanors_providers::ZhipuProvider;
-use nanors_tools::StaticToolRegistry;
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::{path::PathBuf, time::Duration};
 use teloxide::prelude::*;
+use teloxide::types::User;
 use tokio::time::sleep;
-use tracing::{info, warn};
-use uuid::Uuid;
-
-/// Session data for each chat
-#[derive(Clone)]
-struct SessionData {
-    session_id: Uuid,
-}
-
-/// Build `AgentConfig` from bot config.
-fn build_agent_config(config: &Config) -> AgentConfig {
-    AgentConfig {
-        model: config.agents.defaults.model.clone(),
-        max_tokens: config.agents.defaults.max_tokens,
-        temperature: config.agents.defaults.temperature,
-    }
+use tracing::{error, info, warn};
+
+/// Tag agent input with its sender's name and id.
+pub fn with_user(msg: InboundMessage, from: Option<&User>) -> InboundMessage {
+    let Some(user) = from else {
+        return msg.with_sender("unknown");
+    };
+    msg.with_sender(user.username.as_deref().unwrap_or("unknown"))
+        .with_sender_id(user.id.to_string())
 }
 
 /// Telegram Bot with AI integration
+#[derive(Clone)]
 pub struct TelegramBot {
     /// Teloxide bot instance
     pub bot: Bot,
-    /// Zhipu AI provider
-    provider: ZhipuProvider,
     /// Memory manager for session and long-term storage
-    pub memory_manager: Arc<MemoryManager>,
-    /// Configuration
-    pub config: Config,
-    /// Session mapping: `chat_id` -> session data
-    sessions: Arc<tokio::sync::Mutex<HashMap<i64, SessionData>>>,
-    /// Allowed chat IDs
-    allowed_chats: Vec<i64>,
-    /// Working directory for tools
-    working_dir: String,
+    pub memory_manager: Arc<DynMemoryManager>,
+    /// Embeddings for `/remember` and `/forget`
+    pub(crate) provider: ZhipuProvider,
+    /// Session mapping, allowlist and agent runs
+    pub(crate) gateway: Arc<ChatGateway<ZhipuProvider, Arc<DynMemoryManager>>>,
+    /// Directory uploaded documents are saved under
+    pub(crate) working_dir: PathBuf,
+    /// Voice note transcription (voice notes are refused without it)
+    pub(crate) speech_to_text: Option<Arc<dyn SpeechToText>>,
+    /// The bot's own account, set once connected
+    pub(crate) identity: Arc<OnceLock<BotIdentity>>,
+    /// Groups in listen mode, mirrored from the database
+    pub(crate) listening: Arc<Mutex<HashSet<i64>>>,
+    /// Receive updates by webhook instead of long polling
+    pub(crate) webhook: Option<WebhookOptions>,
 }
 
 impl TelegramBot {
     /// Create a new Telegram bot
+    ///
+    /// `factory` builds the agent for each message; `model` is the model it
+    /// is asked for; `provider` embeds text for memory commands. At most
+    /// `max_concurrent_turns` agent runs happen at once across all chats.
     pub fn new(
         token: String,
+        factory: AgentFactory<ZhipuProvider, Arc<DynMemoryManager>>,
+        model: String,
         provider: ZhipuProvider,
-        memory_manager: Arc<MemoryManager>,
-        config: Config,
+        memory_manager: Arc<DynMemoryManager>,
         allowed_chats: &[String],
-        working_dir: String,
+        max_concurrent_turns: usize,
     ) -> Result<Self> {
-        // Parse allowed chat IDs
+        // Keep only well-formed chat IDs
         let allowed_chats = allowed_chats
             .iter()
-            .filter_map(|s| s.parse::<i64>().ok())
+            .filter(|s| s.parse::<i64>().is_ok())
+            .cloned()
             .collect();
 
         let bot = Bot::new(token);
+        let channel = Arc::new(TelegramChannel::new(bot.clone()));
+        // Chat → session mappings live in the database and survive restarts
+        let session_store: Arc<dyn ChatSessionStore> = memory_manager.clone();
+        let gateway = ChatGateway::new(channel, factory, model)
+            .with_session_store(session_store)
+            .with_allowed_chats(allowed_chats)
+            .with_max_concurrent_turns(max_concurrent_turns);
 
         Ok(Self {
             bot,
-            provider,
             memory_manager,
-            config,
-            sessions: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
-            allowed_chats,
-            working_dir,
+            provider,
+            gateway: Arc::new(gateway),
+            working_dir: PathBuf::from("."),
+            speech_to_text: None,
+            identity: Arc::new(OnceLock::new()),
+            listening: Arc::new(Mutex::new(HashSet::new())),
+            webhook: None,
         })
     }
 
-    /// Check if a chat is allowed
+    /// Save uploaded documents under `working_dir/uploads/<chat_id>`.
     #[must_use]
-    pub fn is_allowed(&self, chat_id: i64) -> bool {
-        self.allowed_chats.is_empty() || self.allowed_chats.contains(&chat_id)
+    pub fn with_working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = working_dir.into();
+        self
     }
 
-    /// Get or create a session ID for a chat
-    async fn get_or_create_session_id(&self, chat_id: i64) -> Result<Uuid> {
-        // Check authorization
-        if !self.is_allowed(chat_id) {
-            return Err(Error::Unauthorized(chat_id));
-        }
-
-        // Return existing session if available
-        {
-            let sessions = self.sessions.lock().await;
-            if let Some(data) = sessions.get(&chat_id) {
-                return Ok(data.session_id);
-            }
-        }
-
-        // Create new session
-        let session_id = Uuid::now_v7();
-
-        // Get or create session from storage
-        let storage: Arc<dyn SessionStorage> = self.memory_manager.clone();
-        storage
-            .get_or_create(&session_id)
-            .await
-            .map_err(|e| Error::Memory(anyhow::anyhow!("Failed to create session: {e}")))?;
-
-        let data = SessionData { session_id };
-
-        {
-            let mut sessions = self.sessions.lock().await;
-            sessions.insert(chat_id, data.clone());
-        }
+    /// Transcribe voice notes with `speech_to_text`.
+    #[must_use]
+    pub fn with_speech_to_text(mut self, speech_to_text: Arc<dyn SpeechToText>) -> Self {
+        self.speech_to_text = Some(speech_to_text);
+        self
+    }
 
-        Ok(session_id)
+    /// Check if a chat is allowed
+    #[must_use]
+    pub fn is_allowed(&self, chat_id: i64) -> bool {
+        self.gateway.is_allowed(&chat_id.to_string())
     }
 
     /// Reset session for a chat
+    ///
+    /// The old session is archived and keeps its history.
     pub async fn reset_session(&self, chat_id: i64) -> Result<()> {
-        let session_id = {
-            let mut sessions = self.sessions.lock().await;
-            sessions.remove(&chat_id).map(|d| d.session_id)
-        };
-
-        if let Some(id) = session_id {
-            self.memory_manager
-                .clear_session(&id)
-                .await
-                .map_err(Error::Memory)?;
-        }
-
+        self.gateway
+            .reset(&chat_id.to_string())
+            .await
+            .map_err(Error::Memory)?;
         Ok(())
     }
 
-    /// Process a message and get response
-    pub async fn process_message(&self, chat_id: i64, text: String) -> Result<String> {
-        let session_id = self.get_or_create_session_id(chat_id).await?;
-
-        // Build agent config
-        let agent_config = build_agent_config(&self.config);
+    /// Memory scope of a sender: their own, or the chat's when unknown
+    /// (e.g. channel posts). Matches the scope the gateway gives their turns.
+    #[must_use]
+    pub fn memory_scope(chat_id: i64, from: Option<&User>) -> MemoryScope {
+        from.map_or_else(
+            || MemoryScope::chat(CHANNEL_NAME, chat_id),
+            |user| MemoryScope::user(CHANNEL_NAME, user.id),
+        )
+    }
 
-        // Register tools using default tool registry
-        let tool_registry = StaticToolRegistry::with_default_tools(&self.working_dir);
+    /// Handle a text message: run the agent and send the reply
+    pub fn handle_text(&self, chat_id: i64, from: Option<&User>, text: &str) -> Result<()> {
+        let msg = with_user(InboundMessage::new(chat_id.to_string(), text), from);
+        self.handle_inbound(chat_id, msg)
+    }
 
-        // Use AgentLoop with tool support
-        let agent_loop = AgentLoop::new(
-            self.provider.clone(),
-            self.memory_manager.clone(),
-            agent_config,
-        )
-        .with_memory(self.memory_manager.clone())
-        .with_tools(tool_registry);
+    /// Queue prepared agent input for the chat
+    ///
+    /// Turns run one at a time per chat and the reply is sent when the
+    /// turn finishes.
+    pub fn handle_inbound(&self, chat_id: i64, msg: InboundMessage) -> Result<()> {
+        if !self.is_allowed(chat_id) {
+            return Err(Error::Unauthorized(chat_id));
+        }
 
-        // Process message with tool calling support
-        let response = agent_loop
-            .process_message(&session_id, &text)
-            .await
-            .map_err(|e| Error::Provider(anyhow::anyhow!("{e}")))?;
+        self.gateway.submit(msg);
+        Ok(())
+    }
 
-        Ok(response)
+    /// Cancel the chat's running turn and drop its queued messages
+    ///
+    /// Returns whether anything was cancelled.
+    #[must_use]
+    pub fn cancel(&self, chat_id: i64) -> bool {
+        self.gateway.cancel(&chat_id.to_string())
     }
 
     /// Test connection to Telegram API with exponential backoff retry.
@@ -169,6 +169,10 @@ impl TelegramBot {
         loop {
             match self.bot.get_me().await {
                 Ok(bot_user) => {
+                    let _ = self.identity.set(BotIdentity {
+                        id: bot_user.user.id,
+                        username: bot_user.user.username.clone().unwrap_or_default(),
+                    });
                     info!(
                         "Connected to Telegram API: @{} (id: {})",
                         bot_user
@@ -203,45 +207,76 @@ impl TelegramBot {
         }
     }
 
+    /// Receive updates through a webhook instead of long polling
+    #[must_use]
+    pub fn with_webhook(mut self, webhook: WebhookOptions) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
     /// Run the bot
+    ///
+    /// Long-polls by default; with [`Self::with_webhook`] it registers the
+    /// webhook and serves it until Ctrl+C, then deletes the webhook.
     pub async fn run(self) -> Result<()> {
         use teloxide::dispatching::{Dispatcher, UpdateFilterExt};
         use teloxide::dptree;
+        use teloxide::error_handlers::LoggingErrorHandler;
         use teloxide::types::Update;
+        use teloxide::update_listeners::webhooks::axum_to_router;
 
         // Test connection with exponential backoff retry before starting dispatcher
         self.test_connection().await?;
+        self.load_listening().await?;
+
+        if let Err(e) = self.bot.set_my_commands(Command::bot_commands()).await {
+            warn!("Failed to register bot commands: {e}");
+        }
 
         let bot = self.bot.clone();
 
-        let schema = dptree::entry().branch(Update::filter_message().endpoint({
-            let bot_clone = self.clone();
-            move |_bot: Bot, msg: teloxide::types::Message| {
-                let bot_clone = bot_clone.clone();
-                async move { crate::handler::handle_message(bot_clone, msg).await }
-            }
-        }));
+        let schema = dptree::entry()
+            .branch(Update::filter_message().endpoint({
+                let bot_clone = self.clone();
+                move |_bot: Bot, msg: teloxide::types::Message| {
+                    let bot_clone = bot_clone.clone();
+                    async move { crate::handler::handle_message(bot_clone, msg).await }
+                }
+            }))
+            .branch(Update::filter_callback_query().endpoint({
+                let bot_clone = self.clone();
+                move |_bot: Bot, query: teloxide::types::CallbackQuery| {
+                    let bot_clone = bot_clone.clone();
+                    async move { crate::handler::handle_callback(bot_clone, query).await }
+                }
+            }));
 
-        Dispatcher::builder(bot, schema)
+        let mut dispatcher = Dispatcher::builder(bot.clone(), schema)
             .enable_ctrlc_handler()
-            .build()
-            .dispatch()
+            .build();
+
+        let Some(options) = self.webhook else {
+            dispatcher.dispatch().await;
+            return Ok(());
+        };
+
+        let listener = tokio::net::TcpListener::bind(options.listen).await?;
+        let (updates, stop, router) = axum_to_router(bot, options.to_teloxide()).await?;
+        info!("Webhook registered at {}", options.url);
+
+        tokio::spawn(async move {
+            if let Err(e) = webhook::serve(listener, router, options.tls.as_ref(), stop).await {
+                error!("Webhook server failed: {e}");
+            }
+        });
+
+        dispatcher
+            .dispatch_with_listener(
+                updates,
+                LoggingErrorHandler::with_custom_text("Webhook listener error"),
+            )
             .await;
 
         Ok(())
     }
 }
-
-impl Clone for TelegramBot {
-    fn clone(&self) -> Self {
-        Self {
-            bot: self.bot.clone(),
-            provider: self.provider.clone(),
-            memory_manager: Arc::clone(&self.memory_manager),
-            config: self.config.clone(),
-            sessions: Arc::clone(&self.sessions),
-            allowed_chats: self.allowed_chats.clone(),
-            working_dir: self.working_dir.clone(),
-        }
-    }
-}
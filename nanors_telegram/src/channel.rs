@@ -0,0 +1,109 @@
+//! Telegram implementation of the core [`Channel`] trait.
+
+use async_trait::async_trait;
+use nanors_core::channel::Channel;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, InputFile, ParseMode};
+use tracing::warn;
+
+use crate::format::{markdown_to_html, split_markdown};
+
+/// Channel name, also the prefix of Telegram memory scopes.
+pub const CHANNEL_NAME: &str = "telegram";
+
+/// Telegram's limit on message length.
+const TELEGRAM_MAX_MESSAGE_LEN: usize = 4096;
+
+/// Markdown budget per message; HTML tags and entities need the headroom.
+const MARKDOWN_CHUNK_LEN: usize = 3500;
+
+/// Replies needing more messages than this are sent as a document instead.
+const MAX_REPLY_MESSAGES: usize = 4;
+
+/// Sends replies through the Telegram Bot API.
+#[derive(Clone)]
+pub struct TelegramChannel {
+    bot: Bot,
+}
+
+impl TelegramChannel {
+    #[must_use]
+    pub const fn new(bot: Bot) -> Self {
+        Self { bot }
+    }
+
+    fn chat_id(chat_id: &str) -> anyhow::Result<ChatId> {
+        chat_id
+            .parse::<i64>()
+            .map(ChatId)
+            .map_err(|_| anyhow::anyhow!("Invalid Telegram chat id: {chat_id}"))
+    }
+
+    /// Send one chunk as HTML, falling back to plain text if Telegram
+    /// rejects the markup.
+    async fn send_chunk(&self, chat_id: ChatId, markdown: &str) -> anyhow::Result<()> {
+        let html = markdown_to_html(markdown);
+        if html.chars().count() <= TELEGRAM_MAX_MESSAGE_LEN {
+            match self
+                .bot
+                .send_message(chat_id, html)
+                .parse_mode(ParseMode::Html)
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(e) => warn!("HTML reply rejected, sending plain text: {e}"),
+            }
+        }
+        self.bot.send_message(chat_id, markdown).await?;
+        Ok(())
+    }
+
+    /// Send the raw Markdown reply as a `reply.md` attachment.
+    async fn send_document(&self, chat_id: ChatId, markdown: &str) -> anyhow::Result<()> {
+        let file = InputFile::memory(markdown.as_bytes().to_vec()).file_name("reply.md");
+        self.bot.send_document(chat_id, file).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Channel for TelegramChannel {
+    fn name(&self) -> &'static str {
+        CHANNEL_NAME
+    }
+
+    fn max_message_len(&self) -> usize {
+        TELEGRAM_MAX_MESSAGE_LEN
+    }
+
+    async fn send_text(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        self.bot.send_message(Self::chat_id(chat_id)?, text).await?;
+        Ok(())
+    }
+
+    async fn send_typing(&self, chat_id: &str) -> anyhow::Result<()> {
+        self.bot
+            .send_chat_action(Self::chat_id(chat_id)?, ChatAction::Typing)
+            .await?;
+        Ok(())
+    }
+
+    /// Render the reply as Telegram HTML, split at paragraph and code-fence
+    /// boundaries. Very long replies, or ones Telegram refuses even as plain
+    /// text, are sent as a `.md` document.
+    async fn send_reply(&self, chat_id: &str, text: &str) -> anyhow::Result<()> {
+        let chat_id = Self::chat_id(chat_id)?;
+        let chunks = split_markdown(text, MARKDOWN_CHUNK_LEN);
+        if chunks.len() > MAX_REPLY_MESSAGES {
+            return self.send_document(chat_id, text).await;
+        }
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            if let Err(e) = self.send_chunk(chat_id, chunk).await {
+                warn!("Reply chunk failed, sending the rest as a document: {e}");
+                return self.send_document(chat_id, &chunks[i..].join("\n\n")).await;
+            }
+        }
+        Ok(())
+    }
+}
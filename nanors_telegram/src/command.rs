@@ -1,28 +1,51 @@
 use teloxide::types::BotCommand;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Command {
     Start,
     Reset,
+    Cancel,
     Help,
+    /// List memories, newest first
+    Memory,
+    /// Delete a memory by id or best match for a query
+    Forget(String),
+    /// Store a fact in long-term memory
+    Remember(String),
+    /// Show or switch the chat's model
+    Model(String),
+    /// Show or toggle tool calling (`on` / `off`)
+    Tools(String),
+    /// List the chat's sessions
+    Sessions,
+    /// Export the current session as Markdown
+    Export,
+    /// Toggle group listen mode (`on` / `off`, admins only)
+    Listen(String),
 }
 
 impl Command {
     fn all() -> Vec<BotCommand> {
-        vec![
-            BotCommand {
-                command: "start".to_string(),
-                description: "开始使用机器人".to_string(),
-            },
-            BotCommand {
-                command: "reset".to_string(),
-                description: "重置对话历史".to_string(),
-            },
-            BotCommand {
-                command: "help".to_string(),
-                description: "显示帮助信息".to_string(),
-            },
+        [
+            ("start", "开始使用机器人"),
+            ("reset", "重置对话历史"),
+            ("cancel", "取消正在进行的任务"),
+            ("memory", "查看记忆"),
+            ("forget", "删除记忆：/forget <id|关键词>"),
+            ("remember", "记住一条信息：/remember <内容>"),
+            ("model", "查看或切换模型：/model <名称>"),
+            ("tools", "开关工具调用：/tools on|off"),
+            ("sessions", "查看会话列表"),
+            ("export", "导出当前会话"),
+            ("listen", "群组旁听模式：/listen on|off（管理员）"),
+            ("help", "显示帮助信息"),
         ]
+        .into_iter()
+        .map(|(command, description)| BotCommand {
+            command: command.to_string(),
+            description: description.to_string(),
+        })
+        .collect()
     }
 
     #[must_use]
@@ -30,21 +53,44 @@ impl Command {
         Self::all()
     }
 
+    /// Parse a command; `bot_name` filters out commands addressed to other
+    /// bots (e.g. `/start@other_bot` in a group). An empty name accepts all.
     #[must_use]
-    pub fn parse_from_text(text: &str, _bot_name: &str) -> Option<Self> {
-        let text = text.trim().to_lowercase();
+    pub fn parse_from_text(text: &str, bot_name: &str) -> Option<Self> {
+        let text = text.trim();
+        let (command, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let args = args.trim().to_string();
 
         // Remove bot mention if present (e.g., "/start@my_bot")
-        let text = text.split('@').next().unwrap_or(&text).to_string();
+        let (command, target) = command.split_once('@').unwrap_or((command, ""));
+        if !target.is_empty() && !bot_name.is_empty() && !target.eq_ignore_ascii_case(bot_name) {
+            return None;
+        }
+        let command = command.to_lowercase();
 
-        match text.as_str() {
+        match command.as_str() {
             "/start" => Some(Self::Start),
             "/reset" => Some(Self::Reset),
+            "/cancel" => Some(Self::Cancel),
             "/help" => Some(Self::Help),
+            "/memory" => Some(Self::Memory),
+            "/forget" => Some(Self::Forget(args)),
+            "/remember" => Some(Self::Remember(args)),
+            "/model" => Some(Self::Model(args)),
+            "/tools" => Some(Self::Tools(args)),
+            "/sessions" => Some(Self::Sessions),
+            "/export" => Some(Self::Export),
+            "/listen" => Some(Self::Listen(args)),
             _ => None,
         }
     }
 
+    /// Whether chats outside the allowlist may use the command.
+    #[must_use]
+    pub const fn is_public(&self) -> bool {
+        matches!(self, Self::Start | Self::Help)
+    }
+
     #[must_use]
     pub const fn help_text() -> &'static str {
         r"
@@ -53,9 +99,18 @@ impl Command {
 命令列表:
 /start - 开始使用机器人
 /reset - 重置对话历史
+/cancel - 取消正在进行的任务
+/memory - 查看记忆（分页，仅私聊）
+/forget <id|关键词> - 删除一条记忆（仅私聊）
+/remember <内容> - 记住一条信息
+/model [名称] - 查看或切换模型
+/tools on|off - 开关工具调用
+/sessions - 查看会话列表
+/export - 导出当前会话
+/listen on|off - 群组旁听模式（管理员）
 /help  - 显示此帮助信息
 
-直接发送消息即可开始对话！
+直接发送消息即可开始对话！在群组中请 @ 我或回复我的消息。
 "
     }
 
@@ -73,3 +128,53 @@ impl Command {
 "
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands_with_arguments() {
+        assert_eq!(Command::parse_from_text("/START", ""), Some(Command::Start));
+        assert_eq!(
+            Command::parse_from_text("/remember@nano_bot  I like Rust ", ""),
+            Some(Command::Remember("I like Rust".to_string()))
+        );
+        assert_eq!(
+            Command::parse_from_text("/model", ""),
+            Some(Command::Model(String::new()))
+        );
+        assert_eq!(Command::parse_from_text("hello /start", ""), None);
+        assert_eq!(Command::parse_from_text("/unknown", ""), None);
+    }
+
+    #[test]
+    fn test_parse_ignores_commands_for_other_bots() {
+        assert_eq!(
+            Command::parse_from_text("/help@NanoBot", "nanobot"),
+            Some(Command::Help)
+        );
+        assert_eq!(Command::parse_from_text("/help@other_bot", "nanobot"), None);
+        assert_eq!(
+            Command::parse_from_text("/help", "nanobot"),
+            Some(Command::Help)
+        );
+    }
+
+    #[test]
+    fn test_only_start_and_help_are_public() {
+        assert!(Command::Start.is_public());
+        assert!(Command::Help.is_public());
+        assert!(!Command::Remember("x".to_string()).is_public());
+        assert!(!Command::Memory.is_public());
+        assert!(!Command::Reset.is_public());
+    }
+
+    #[test]
+    fn test_bot_commands_match_parser() {
+        for command in Command::bot_commands() {
+            let text = format!("/{}", command.command);
+            assert!(Command::parse_from_text(&text, "").is_some(), "{text}");
+        }
+    }
+}
@@ -7,6 +7,13 @@ pub enum Error {
     #[error("Telegram API error: {0}")]
     Telegram(#[from] teloxide::RequestError),
 
+    #[error("Telegram download error: {0}")]
+    Download(#[from] teloxide::DownloadError),
+
+    /// A message the bot cannot handle; the text is shown to the user
+    #[error("Unsupported message: {0}")]
+    Unsupported(String),
+
     #[error("AI provider error: {0}")]
     Provider(anyhow::Error),
 
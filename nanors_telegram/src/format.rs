@@ -0,0 +1,360 @@
+//! Markdown rendering and splitting for Telegram replies.
+//!
+//! Models answer in Markdown, which Telegram does not render as-is. Replies
+//! are split into message-sized chunks at paragraph and code-fence
+//! boundaries first, then each chunk is rendered to Telegram's HTML subset,
+//! so no tag ever spans two messages.
+
+use nanors_core::channel::chunk_message;
+
+const FENCE: &str = "```";
+
+/// A top-level Markdown block.
+enum Block<'a> {
+    /// Paragraph text (may contain single line breaks)
+    Text(String),
+    /// Fenced code block: info string and body lines
+    Code { lang: &'a str, lines: Vec<&'a str> },
+}
+
+impl Block<'_> {
+    fn to_markdown(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Code { lang, lines } => fence(lang, lines),
+        }
+    }
+}
+
+fn fence(lang: &str, lines: &[&str]) -> String {
+    format!("{FENCE}{lang}\n{}\n{FENCE}", lines.join("\n"))
+}
+
+fn parse_blocks(text: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut lines = text.lines();
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Text(paragraph.join("\n")));
+            paragraph.clear();
+        }
+    };
+
+    while let Some(line) = lines.next() {
+        if let Some(info) = line.trim_start().strip_prefix(FENCE) {
+            flush(&mut paragraph, &mut blocks);
+            // An unclosed fence runs to the end of the text
+            let body = lines
+                .by_ref()
+                .take_while(|l| !l.trim_start().starts_with(FENCE))
+                .collect();
+            blocks.push(Block::Code {
+                lang: info.trim(),
+                lines: body,
+            });
+        } else if line.trim().is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+
+    blocks
+}
+
+/// Split a code block that is too long on its own into re-fenced parts.
+fn split_code(lang: &str, lines: &[&str], max_chars: usize) -> Vec<String> {
+    // Room left for the opening and closing fence lines
+    let overhead = FENCE.len() * 2 + lang.chars().count() + 2;
+    let budget = max_chars.saturating_sub(overhead).max(1);
+
+    let mut parts = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut len = 0;
+    for &line in lines {
+        let line_len = line.chars().count() + 1;
+        if !current.is_empty() && len + line_len > budget {
+            parts.push(fence(lang, &current));
+            current.clear();
+            len = 0;
+        }
+        if line_len > budget {
+            // A single overlong line is cut without regard for words
+            for piece in chunk_message(line, budget) {
+                parts.push(fence(lang, &[piece.as_str()]));
+            }
+        } else {
+            current.push(line);
+            len += line_len;
+        }
+    }
+    if !current.is_empty() {
+        parts.push(fence(lang, &current));
+    }
+    parts
+}
+
+/// Split Markdown into chunks of at most `max_chars` characters.
+///
+/// Chunks break between paragraphs and around code blocks; a code block
+/// that does not fit in one message is split by lines and every part is
+/// fenced again.
+#[must_use]
+pub fn split_markdown(text: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    for block in parse_blocks(text) {
+        let markdown = block.to_markdown();
+        if markdown.chars().count() <= max_chars {
+            pieces.push(markdown);
+            continue;
+        }
+        match block {
+            Block::Text(text) => pieces.extend(chunk_message(&text, max_chars)),
+            Block::Code { lang, lines } => pieces.extend(split_code(lang, &lines, max_chars)),
+        }
+    }
+
+    // Pack pieces back together, separated by blank lines
+    let mut chunks: Vec<String> = Vec::new();
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(last) if last.chars().count() + 2 + piece.chars().count() <= max_chars => {
+                last.push_str("\n\n");
+                last.push_str(&piece);
+            }
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+}
+
+/// Escape text for Telegram HTML.
+#[must_use]
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render Markdown to the HTML subset accepted by Telegram.
+///
+/// Supports fenced and inline code, bold, italic, strikethrough, links,
+/// headings (rendered bold) and bullet lists. Everything else is escaped
+/// and passed through as text.
+#[must_use]
+pub fn markdown_to_html(text: &str) -> String {
+    let mut out = Vec::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        if let Some(info) = line.trim_start().strip_prefix(FENCE) {
+            let body: Vec<&str> = lines
+                .by_ref()
+                .take_while(|l| !l.trim_start().starts_with(FENCE))
+                .collect();
+            let code = escape_html(&body.join("\n"));
+            let lang = info.trim();
+            if lang.is_empty() {
+                out.push(format!("<pre>{code}</pre>"));
+            } else {
+                out.push(format!(
+                    "<pre><code class=\"language-{}\">{code}</code></pre>",
+                    escape_html(lang)
+                ));
+            }
+        } else {
+            out.push(render_line(line));
+        }
+    }
+
+    out.join("\n")
+}
+
+fn render_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) {
+        if let Some(title) = trimmed[hashes..].strip_prefix(' ') {
+            return format!("<b>{}</b>", render_inline(title.trim()));
+        }
+    }
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(bullet) {
+            let indent = &line[..line.len() - trimmed.len()];
+            return format!("{indent}• {}", render_inline(item));
+        }
+    }
+
+    render_inline(line)
+}
+
+/// Find a closing `marker` after `start` that encloses non-empty text.
+fn find_closing(text: &str, start: usize, marker: &str) -> Option<usize> {
+    let end = start + text[start..].find(marker)?;
+    (end > start).then_some(end)
+}
+
+fn render_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+
+        if let Some(after) = rest.strip_prefix('`') {
+            if let Some(end) = find_closing(after, 0, "`") {
+                out.push_str("<code>");
+                out.push_str(&escape_html(&after[..end]));
+                out.push_str("</code>");
+                i += end + 2;
+                continue;
+            }
+        }
+
+        if let Some((marker, tag)) = [("**", "b"), ("__", "b"), ("~~", "s")]
+            .into_iter()
+            .find(|(marker, _)| rest.starts_with(marker))
+        {
+            if let Some(end) = find_closing(rest, marker.len(), marker) {
+                let inner = render_inline(&rest[marker.len()..end]);
+                push_tag(&mut out, tag, &inner);
+                i += end + marker.len();
+                continue;
+            }
+        }
+
+        if let Some(marker) = ["*", "_"].into_iter().find(|m| rest.starts_with(m)) {
+            // `snake_case` and `2*3` are not emphasis
+            let after_word = text[..i]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric);
+            if !after_word && !rest[1..].starts_with(' ') {
+                if let Some(end) = find_closing(rest, 1, marker) {
+                    let inner = render_inline(&rest[1..end]);
+                    push_tag(&mut out, "i", &inner);
+                    i += end + 1;
+                    continue;
+                }
+            }
+        }
+
+        if let Some(link) = parse_link(rest) {
+            out.push_str("<a href=\"");
+            out.push_str(&escape_html(link.url));
+            out.push_str("\">");
+            out.push_str(&render_inline(link.label));
+            out.push_str("</a>");
+            i += link.len;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap_or_default();
+        out.push_str(&escape_html(c.encode_utf8(&mut [0; 4])));
+        i += c.len_utf8();
+    }
+
+    out
+}
+
+fn push_tag(out: &mut String, tag: &str, inner: &str) {
+    out.push('<');
+    out.push_str(tag);
+    out.push('>');
+    out.push_str(inner);
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+
+struct Link<'a> {
+    label: &'a str,
+    url: &'a str,
+    len: usize,
+}
+
+/// Parse `[label](url)` at the start of `text`.
+fn parse_link(text: &str) -> Option<Link<'_>> {
+    let rest = text.strip_prefix('[')?;
+    let label_end = rest.find("](")?;
+    let url_start = label_end + 2;
+    let url_end = url_start + rest[url_start..].find(')')?;
+    let url = &rest[url_start..url_end];
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(Link {
+        label: &rest[..label_end],
+        url,
+        len: url_end + 2,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_html_escapes_and_styles() {
+        assert_eq!(
+            markdown_to_html("**a < b** and `x && y`"),
+            "<b>a &lt; b</b> and <code>x &amp;&amp; y</code>"
+        );
+        assert_eq!(
+            markdown_to_html("# Title\n- *one*\n- [docs](https://x.io/a?b=1&c=2)"),
+            "<b>Title</b>\n• <i>one</i>\n• <a href=\"https://x.io/a?b=1&amp;c=2\">docs</a>"
+        );
+    }
+
+    #[test]
+    fn test_markdown_to_html_leaves_identifiers_alone() {
+        assert_eq!(
+            markdown_to_html("use snake_case_name and 2*3*4"),
+            "use snake_case_name and 2*3*4"
+        );
+        assert_eq!(markdown_to_html("unclosed **bold"), "unclosed **bold");
+    }
+
+    #[test]
+    fn test_markdown_to_html_code_block() {
+        let html = markdown_to_html("Run:\n```rust\nfn main() { a<b }\n```\nDone");
+        assert_eq!(
+            html,
+            "Run:\n<pre><code class=\"language-rust\">fn main() { a&lt;b }</code></pre>\nDone"
+        );
+    }
+
+    #[test]
+    fn test_split_markdown_keeps_code_fences_balanced() {
+        let code: Vec<String> = (0..30).map(|i| format!("let x{i} = {i};")).collect();
+        let text = format!("intro\n\n```rust\n{}\n```\n\noutro", code.join("\n"));
+
+        let chunks = split_markdown(&text, 120);
+        assert!(chunks.len() > 2);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 120, "chunk too long: {chunk}");
+            assert_eq!(chunk.matches(FENCE).count() % 2, 0, "unbalanced: {chunk}");
+        }
+        assert!(chunks[0].starts_with("intro"));
+        assert!(chunks.iter().any(|c| c.ends_with("outro")));
+    }
+
+    #[test]
+    fn test_split_markdown_packs_short_paragraphs() {
+        assert_eq!(split_markdown("a\n\nb\n\n\nc", 100), vec!["a\n\nb\n\nc"]);
+        assert_eq!(split_markdown("aaaa\n\nbbbb", 6), vec!["aaaa", "bbbb"]);
+    }
+}
@@ -0,0 +1,256 @@
+//! Group chat behaviour: mention/reply triggering and listen mode.
+
+use nanors_core::channel::InboundMessage;
+use nanors_core::{LLMProvider, MemoryItem, MemoryItemRepo, MemoryType};
+use serde_json::json;
+use std::collections::HashSet;
+use teloxide::prelude::*;
+use teloxide::types::{Chat, User};
+use tracing::{debug, info};
+
+use crate::channel::CHANNEL_NAME;
+use crate::{Error, Result, TelegramBot};
+
+/// The bot's own account, learned from `getMe` at startup.
+#[derive(Debug, Clone)]
+pub struct BotIdentity {
+    pub id: UserId,
+    pub username: String,
+}
+
+/// Whether a chat is a group or supergroup.
+#[must_use]
+pub fn is_group(chat: &Chat) -> bool {
+    chat.is_group() || chat.is_supergroup()
+}
+
+/// Remove `@username` mentions of the bot from `text`.
+///
+/// Returns `None` when the bot is not mentioned.
+#[must_use]
+pub fn strip_mention(text: &str, username: &str) -> Option<String> {
+    if username.is_empty() {
+        return None;
+    }
+    // Usernames are ASCII, so ASCII lowercasing keeps byte offsets intact
+    let mention = format!("@{}", username.to_ascii_lowercase());
+    let lower = text.to_ascii_lowercase();
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut found = false;
+    for (pos, _) in lower.match_indices(&mention) {
+        let end = pos + mention.len();
+        // `@nanobot_dev` is a different account than `@nanobot`
+        let continues = lower[end..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+        if continues {
+            continue;
+        }
+        out.push_str(&text[last..pos]);
+        last = end;
+        found = true;
+    }
+    if !found {
+        return None;
+    }
+    out.push_str(&text[last..]);
+
+    Some(out.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Display name of a message sender.
+#[must_use]
+pub fn sender_name(user: Option<&User>) -> String {
+    user.map_or_else(|| "unknown".to_string(), User::full_name)
+}
+
+/// A group message as the agent sees it: prefixed with the sender.
+#[must_use]
+pub fn group_turn(sender: &str, text: &str) -> String {
+    format!("{sender}: {text}")
+}
+
+impl TelegramBot {
+    /// The bot's account, once connected.
+    pub(crate) fn identity(&self) -> Option<&BotIdentity> {
+        self.identity.get()
+    }
+
+    /// Whether a group message is a reply to one of the bot's messages.
+    pub(crate) fn is_reply_to_bot(&self, msg: &Message) -> bool {
+        let Some(identity) = self.identity() else {
+            return false;
+        };
+        msg.reply_to_message()
+            .and_then(|reply| reply.from.as_ref())
+            .is_some_and(|user| user.id == identity.id)
+    }
+
+    /// Text the bot should answer in a group, or `None` to stay quiet.
+    ///
+    /// The bot answers when it is mentioned or replied to; the mention is
+    /// removed and the sender's name is prefixed.
+    pub(crate) fn group_text(&self, msg: &Message, text: &str) -> Option<String> {
+        let username = self.identity().map_or("", |i| i.username.as_str());
+        let text = match strip_mention(text, username) {
+            Some(stripped) => stripped,
+            None if self.is_reply_to_bot(msg) => text.to_string(),
+            None => return None,
+        };
+        Some(group_turn(&sender_name(msg.from.as_ref()), &text))
+    }
+
+    /// Prefix the sender to a group attachment and drop the mention from its
+    /// caption.
+    pub(crate) fn group_media(&self, msg: &Message, mut inbound: InboundMessage) -> InboundMessage {
+        let username = self.identity().map_or("", |i| i.username.as_str());
+        if let Some(caption) = msg.caption().map(str::trim)
+            && let Some(stripped) = strip_mention(caption, username)
+        {
+            inbound.text = inbound.text.replace(caption, &stripped);
+        }
+        inbound.text = group_turn(&sender_name(msg.from.as_ref()), inbound.text.trim());
+        inbound
+    }
+
+    /// Whether listen mode is on for a group.
+    #[must_use]
+    pub fn is_listening(&self, chat_id: i64) -> bool {
+        self.listening
+            .lock()
+            .is_ok_and(|listening| listening.contains(&chat_id))
+    }
+
+    /// Turn listen mode on or off for a group; the setting is stored and
+    /// survives restarts.
+    pub async fn set_listening(&self, chat_id: i64, enabled: bool) -> Result<()> {
+        self.memory_manager
+            .set_listening(CHANNEL_NAME, &chat_id.to_string(), enabled)
+            .await
+            .map_err(Error::Memory)?;
+        if let Ok(mut listening) = self.listening.lock() {
+            if enabled {
+                listening.insert(chat_id);
+            } else {
+                listening.remove(&chat_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the groups in listen mode from the database.
+    pub(crate) async fn load_listening(&self) -> Result<()> {
+        let chats = self
+            .memory_manager
+            .listening_chats(CHANNEL_NAME)
+            .await
+            .map_err(Error::Memory)?;
+        let chats: HashSet<i64> = chats.iter().filter_map(|id| id.parse().ok()).collect();
+        info!("Listen mode is on in {} groups", chats.len());
+        if let Ok(mut listening) = self.listening.lock() {
+            *listening = chats;
+        }
+        Ok(())
+    }
+
+    /// Whether a user is an administrator of a chat.
+    pub async fn is_admin(&self, chat_id: ChatId, user: Option<&User>) -> Result<bool> {
+        let Some(user) = user else {
+            return Ok(false);
+        };
+        let member = self.bot.get_chat_member(chat_id, user.id).await?;
+        Ok(member.is_privileged())
+    }
+
+    /// Handle `/listen on|off`: group-only and restricted to admins.
+    pub(crate) async fn toggle_listen(&self, msg: &Message, arg: &str) -> Result<String> {
+        let chat_id = msg.chat.id.0;
+        if !is_group(&msg.chat) {
+            return Ok("旁听模式仅在群组中可用".to_string());
+        }
+        if !self.is_admin(msg.chat.id, msg.from.as_ref()).await? {
+            return Ok("只有群组管理员可以切换旁听模式".to_string());
+        }
+
+        let reply = match arg.trim().to_lowercase().as_str() {
+            "on" => {
+                self.set_listening(chat_id, true).await?;
+                "旁听模式已开启：未 @ 我的消息会被记住，但不会回复".to_string()
+            }
+            "off" => {
+                self.set_listening(chat_id, false).await?;
+                "旁听模式已关闭".to_string()
+            }
+            _ => {
+                let state = if self.is_listening(chat_id) {
+                    "开启"
+                } else {
+                    "关闭"
+                };
+                format!("旁听模式当前{state}。用法：/listen on|off")
+            }
+        };
+        Ok(reply)
+    }
+
+    /// Store an unaddressed group message in its sender's memory.
+    pub async fn listen(&self, msg: &Message, text: &str) -> Result<()> {
+        let sender = sender_name(msg.from.as_ref());
+        let summary = group_turn(&sender, text);
+        let embedding = self
+            .provider
+            .embed(&summary)
+            .await
+            .map_err(Error::Provider)?;
+
+        let mut item = MemoryItem::new(
+            MemoryType::Episodic,
+            &summary,
+            Some(embedding),
+            chrono::Utc::now(),
+        );
+        item.extra = Some(json!({
+            "channel": CHANNEL_NAME,
+            "chat_id": msg.chat.id.0,
+        }));
+
+        // Stored as the sender's memory, never the group's
+        let scope = Self::memory_scope(msg.chat.id.0, msg.from.as_ref());
+        let id = self
+            .memory_manager
+            .semantic_upsert(&scope, &item)
+            .await
+            .map_err(Error::Memory)?;
+        debug!("Listen mode stored memory {id} from chat {}", msg.chat.id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_mention() {
+        assert_eq!(
+            strip_mention("@NanoBot what's up?", "nanobot"),
+            Some("what's up?".to_string())
+        );
+        assert_eq!(
+            strip_mention("hey @nanobot, 你好 @NANOBOT", "NanoBot"),
+            Some("hey , 你好".to_string())
+        );
+        assert_eq!(strip_mention("hello everyone", "nanobot"), None);
+        assert_eq!(strip_mention("ask @nanobot_dev", "nanobot"), None);
+        assert_eq!(strip_mention("@nanobot hi", ""), None);
+    }
+
+    #[test]
+    fn test_group_turn_prefixes_sender() {
+        assert_eq!(group_turn("Alice", "hi"), "Alice: hi");
+        assert_eq!(sender_name(None), "unknown");
+    }
+}
@@ -1,6 +1,11 @@
+use crate::bot::with_user;
+use crate::group::is_group;
+use crate::manage::{FORGET_CALLBACK, parse_forget_callback, parse_memory_callback};
 use crate::{Command, Error, Result, TelegramBot};
-use teloxide::{requests::Requester, types::Message};
-use tracing::info;
+use teloxide::payloads::{EditMessageTextSetters, SendMessageSetters};
+use teloxide::requests::Requester;
+use teloxide::types::{CallbackQuery, InlineKeyboardMarkup, InputFile, Message};
+use tracing::{info, warn};
 
 /// Handle bot commands
 pub async fn handle_command(bot: TelegramBot, msg: Message, cmd: Command) -> Result<()> {
@@ -11,6 +16,19 @@ pub async fn handle_command(bot: TelegramBot, msg: Message, cmd: Command) -> Res
         .and_then(|u| u.username.as_deref())
         .unwrap_or("unknown");
 
+    // Everything but /start and /help uses the model, memory or settings
+    if !cmd.is_public() && !bot.is_allowed(chat_id) {
+        return Err(Error::Unauthorized(chat_id));
+    }
+    // Memory listings and deletion prompts show the sender's own memories,
+    // which may come from private chats
+    if matches!(cmd, Command::Memory | Command::Forget(_)) && is_group(&msg.chat) {
+        bot.bot
+            .send_message(msg.chat.id, "请在与我的私聊中查看或删除记忆")
+            .await?;
+        return Ok(());
+    }
+
     match cmd {
         Command::Start => {
             info!("[@{username}] Command: /start");
@@ -23,6 +41,64 @@ pub async fn handle_command(bot: TelegramBot, msg: Message, cmd: Command) -> Res
             bot.reset_session(chat_id).await?;
             bot.bot.send_message(msg.chat.id, "对话历史已重置").await?;
         }
+        Command::Cancel => {
+            info!("[@{username}] Command: /cancel");
+            let reply = if bot.cancel(chat_id) {
+                "已取消当前任务"
+            } else {
+                "当前没有正在进行的任务"
+            };
+            bot.bot.send_message(msg.chat.id, reply).await?;
+        }
+        Command::Memory => {
+            info!("[@{username}] Command: /memory");
+            let (text, keyboard) = bot.memory_page(chat_id, msg.from.as_ref(), 0).await?;
+            send_with_keyboard(&bot, &msg, text, keyboard).await?;
+        }
+        Command::Forget(target) => {
+            info!("[@{username}] Command: /forget {target}");
+            let scope = TelegramBot::memory_scope(chat_id, msg.from.as_ref());
+            let owner = msg.from.as_ref().map_or(0, |u| u.id.0);
+            let (text, keyboard) = bot.forget(&scope, owner, &target).await?;
+            send_with_keyboard(&bot, &msg, text, keyboard).await?;
+        }
+        Command::Remember(fact) => {
+            info!("[@{username}] Command: /remember");
+            let scope = TelegramBot::memory_scope(chat_id, msg.from.as_ref());
+            let reply = bot.remember(&scope, &fact).await?;
+            bot.bot.send_message(msg.chat.id, reply).await?;
+        }
+        Command::Model(model) => {
+            info!("[@{username}] Command: /model {model}");
+            let reply = bot.switch_model(chat_id, &model);
+            bot.bot.send_message(msg.chat.id, reply).await?;
+        }
+        Command::Tools(arg) => {
+            info!("[@{username}] Command: /tools {arg}");
+            let reply = bot.toggle_tools(chat_id, &arg);
+            bot.bot.send_message(msg.chat.id, reply).await?;
+        }
+        Command::Sessions => {
+            info!("[@{username}] Command: /sessions");
+            let reply = bot.list_sessions(chat_id).await?;
+            bot.bot.send_message(msg.chat.id, reply).await?;
+        }
+        Command::Export => {
+            info!("[@{username}] Command: /export");
+            let Some((file_name, markdown)) = bot.export_session(chat_id).await? else {
+                bot.bot
+                    .send_message(msg.chat.id, "当前会话还没有消息")
+                    .await?;
+                return Ok(());
+            };
+            let file = InputFile::memory(markdown.into_bytes()).file_name(file_name);
+            bot.bot.send_document(msg.chat.id, file).await?;
+        }
+        Command::Listen(arg) => {
+            info!("[@{username}] Command: /listen {arg}");
+            let reply = bot.toggle_listen(&msg, &arg).await?;
+            bot.bot.send_message(msg.chat.id, reply).await?;
+        }
         Command::Help => {
             info!("[@{username}] Command: /help");
             bot.bot
@@ -34,35 +110,147 @@ pub async fn handle_command(bot: TelegramBot, msg: Message, cmd: Command) -> Res
     Ok(())
 }
 
-/// Handle any message (commands or regular text)
+/// Reply to `msg` with `text` and optional inline buttons.
+async fn send_with_keyboard(
+    bot: &TelegramBot,
+    msg: &Message,
+    text: String,
+    keyboard: Option<InlineKeyboardMarkup>,
+) -> Result<()> {
+    let request = bot.bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => request.reply_markup(keyboard).await?,
+        None => request.await?,
+    };
+    Ok(())
+}
+
+/// Handle inline keyboard presses (`/memory` pagination, `/forget`
+/// confirmation)
+pub async fn handle_callback(bot: TelegramBot, query: CallbackQuery) -> Result<()> {
+    bot.bot.answer_callback_query(query.id.clone()).await?;
+
+    let Some(msg) = query.regular_message() else {
+        return Ok(());
+    };
+    if !bot.is_allowed(msg.chat.id.0) {
+        return Err(Error::Unauthorized(msg.chat.id.0));
+    }
+    let data = query.data.as_deref().unwrap_or_default();
+    if data.starts_with(FORGET_CALLBACK) {
+        return handle_forget_callback(&bot, &query, msg, data).await;
+    }
+    let Some((owner, page)) = parse_memory_callback(data) else {
+        return Ok(());
+    };
+    // Only the user who asked can page through their memories
+    if owner != query.from.id.0 {
+        return Ok(());
+    }
+
+    let (text, keyboard) = bot
+        .memory_page(msg.chat.id.0, Some(&query.from), page)
+        .await?;
+    let request = bot.bot.edit_message_text(msg.chat.id, msg.id, text);
+    match keyboard {
+        Some(keyboard) => request.reply_markup(keyboard).await?,
+        None => request.await?,
+    };
+    Ok(())
+}
+
+/// Delete the memory a `/forget` prompt offered, or dismiss the prompt.
+async fn handle_forget_callback(
+    bot: &TelegramBot,
+    query: &CallbackQuery,
+    msg: &Message,
+    data: &str,
+) -> Result<()> {
+    let Some((owner, target)) = parse_forget_callback(data) else {
+        return Ok(());
+    };
+    // Only the user who asked can confirm deleting their memory
+    if owner != query.from.id.0 {
+        return Ok(());
+    }
+
+    let text = match target {
+        Some(id) => {
+            let scope = TelegramBot::memory_scope(msg.chat.id.0, Some(&query.from));
+            info!(
+                "[@{}] Confirmed /forget {id}",
+                query.from.username.as_deref().unwrap_or("unknown")
+            );
+            bot.delete_memory(&scope, &id).await?
+        }
+        None => "已取消删除".to_string(),
+    };
+    bot.bot.edit_message_text(msg.chat.id, msg.id, text).await?;
+    Ok(())
+}
+
+/// Handle any message (commands, text, documents, photos, voice notes)
+///
+/// In groups the bot only answers commands, mentions and replies to its own
+/// messages; other messages are ignored, or remembered in listen mode.
 pub async fn handle_message(bot: TelegramBot, msg: Message) -> Result<()> {
     let chat_id = msg.chat.id.0;
-    let text = msg.text().ok_or(Error::Config("No text content".into()))?;
     let username = msg
         .from
         .as_ref()
         .and_then(|u| u.username.as_deref())
         .unwrap_or("unknown");
+    let in_group = is_group(&msg.chat);
+
+    let Some(text) = msg.text() else {
+        // Check before downloading anything
+        if !bot.is_allowed(chat_id) {
+            return Err(Error::Unauthorized(chat_id));
+        }
+        if in_group
+            && bot
+                .group_text(&msg, msg.caption().unwrap_or_default())
+                .is_none()
+        {
+            return Ok(());
+        }
+
+        return match bot.media_message(&msg).await {
+            Ok(inbound) => {
+                let inbound = if in_group {
+                    bot.group_media(&msg, inbound)
+                } else {
+                    inbound
+                };
+                bot.handle_inbound(chat_id, with_user(inbound, msg.from.as_ref()))
+            }
+            Err(Error::Unsupported(reason)) => {
+                info!("[@{username}] Unsupported message: {reason}");
+                bot.bot.send_message(msg.chat.id, reason).await?;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        };
+    };
 
     // Check if this is a command
-    if let Some(cmd) = Command::parse_from_text(text, "") {
+    let bot_name = bot.identity().map_or("", |i| i.username.as_str());
+    if let Some(cmd) = Command::parse_from_text(text, bot_name) {
         return handle_command(bot, msg, cmd).await;
     }
 
-    info!("[@{username}] Message: {text}");
-
-    // Show typing indicator
-    bot.bot
-        .send_chat_action(msg.chat.id, teloxide::types::ChatAction::Typing)
-        .await?;
-
-    // Process message and get response
-    let response = bot.process_message(chat_id, text.to_string()).await?;
-
-    info!("[@{username}] Response: {response}");
-
-    // Send response to Telegram
-    bot.bot.send_message(msg.chat.id, response).await?;
+    if !in_group {
+        // Queued per chat; the gateway runs the agent and sends the reply
+        return bot.handle_text(chat_id, msg.from.as_ref(), text);
+    }
 
+    if let Some(turn) = bot.group_text(&msg, text) {
+        return bot.handle_text(chat_id, msg.from.as_ref(), &turn);
+    }
+    if bot.is_allowed(chat_id) && bot.is_listening(chat_id) {
+        if let Err(e) = bot.listen(&msg, text).await {
+            warn!("Failed to store listen-mode message from chat {chat_id}: {e}");
+        }
+    }
     Ok(())
 }
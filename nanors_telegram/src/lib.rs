@@ -18,10 +18,18 @@
 )]
 
 mod bot;
+mod channel;
 mod command;
 mod error;
+mod format;
+mod group;
 mod handler;
+mod manage;
+mod media;
+mod webhook;
 
 pub use bot::TelegramBot;
+pub use channel::TelegramChannel;
 pub use command::Command;
 pub use error::{Error, Result};
+pub use webhook::{TlsFiles, WebhookOptions};
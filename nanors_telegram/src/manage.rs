@@ -0,0 +1,503 @@
+//! Memory and session management commands.
+
+use nanors_core::{
+    ChatMessage, ContentBlock, LLMProvider, MemoryItem, MemoryItemRepo, MemoryScope, MemoryType,
+    MessageContent, Role, Session, SessionStorage,
+};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, User};
+use uuid::Uuid;
+
+use crate::{Error, Result, TelegramBot};
+
+/// Memories shown per `/memory` page.
+const MEMORY_PAGE_SIZE: usize = 5;
+
+/// Callback data prefix of the `/memory` page buttons
+/// (`memory:<owner user id>:<page>`).
+pub const MEMORY_PAGE_CALLBACK: &str = "memory:";
+
+/// Callback data prefix of the `/forget` confirmation buttons
+/// (`forget:<owner user id>:<memory id>`, or `forget:<owner user id>:cancel`).
+pub const FORGET_CALLBACK: &str = "forget:";
+
+/// Callback data of the `/forget` cancel button after the owner id.
+const FORGET_CANCEL: &str = "cancel";
+
+/// Longest memory summary shown in a listing.
+const SUMMARY_PREVIEW_CHARS: usize = 200;
+
+/// Sessions shown by `/sessions`.
+const SESSION_LIST_LIMIT: usize = 10;
+
+/// Similarity a `/forget` query needs to offer its best match for deletion.
+const FORGET_MIN_SIMILARITY: f64 = 0.75;
+
+fn preview(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max_chars).collect::<String>())
+    }
+}
+
+/// Parse `/memory` button data into the owner's user id and the page.
+#[must_use]
+pub fn parse_memory_callback(data: &str) -> Option<(u64, usize)> {
+    let (owner, page) = data.strip_prefix(MEMORY_PAGE_CALLBACK)?.split_once(':')?;
+    Some((owner.parse().ok()?, page.parse().ok()?))
+}
+
+/// Parse `/forget` button data into the owner's user id and the memory to
+/// delete, `None` when the owner cancelled.
+#[must_use]
+pub fn parse_forget_callback(data: &str) -> Option<(u64, Option<Uuid>)> {
+    let (owner, target) = data.strip_prefix(FORGET_CALLBACK)?.split_once(':')?;
+    let owner = owner.parse().ok()?;
+    if target == FORGET_CANCEL {
+        return Some((owner, None));
+    }
+    Some((owner, Some(target.parse().ok()?)))
+}
+
+/// Ask `owner` to confirm deleting `item`.
+#[must_use]
+pub fn render_forget_prompt(item: &MemoryItem, owner: u64) -> (String, InlineKeyboardMarkup) {
+    let text = format!(
+        "确认删除这条记忆？\n\n{}\n  {}",
+        preview(&item.summary, SUMMARY_PREVIEW_CHARS),
+        item.id
+    );
+    let keyboard = InlineKeyboardMarkup::new([[
+        InlineKeyboardButton::callback("删除", format!("{FORGET_CALLBACK}{owner}:{}", item.id)),
+        InlineKeyboardButton::callback("取消", format!("{FORGET_CALLBACK}{owner}:{FORGET_CANCEL}")),
+    ]]);
+    (text, keyboard)
+}
+
+/// Render one page of memories (newest first) and its navigation buttons.
+///
+/// Only `owner` can page through the listing.
+#[must_use]
+pub fn render_memory_page(
+    items: &[MemoryItem],
+    page: usize,
+    owner: u64,
+) -> (String, Option<InlineKeyboardMarkup>) {
+    if items.is_empty() {
+        return ("还没有任何记忆".to_string(), None);
+    }
+
+    let pages = items.len().div_ceil(MEMORY_PAGE_SIZE);
+    let page = page.min(pages - 1);
+    let start = page * MEMORY_PAGE_SIZE;
+
+    let header = format!(
+        "🧠 记忆（第 {}/{pages} 页，共 {} 条）\n",
+        page + 1,
+        items.len()
+    );
+    let entries: Vec<String> = items
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(MEMORY_PAGE_SIZE)
+        .map(|(i, item)| {
+            format!(
+                "{}. [{}] {}\n   {} · {}\n",
+                i + 1,
+                item.memory_type,
+                preview(&item.summary, SUMMARY_PREVIEW_CHARS),
+                item.happened_at.format("%Y-%m-%d %H:%M"),
+                item.id
+            )
+        })
+        .collect();
+    let text = format!("{header}\n{}", entries.join("\n"));
+
+    let mut buttons = Vec::new();
+    if page > 0 {
+        buttons.push(InlineKeyboardButton::callback(
+            "◀ 上一页",
+            format!("{MEMORY_PAGE_CALLBACK}{owner}:{}", page - 1),
+        ));
+    }
+    if page + 1 < pages {
+        buttons.push(InlineKeyboardButton::callback(
+            "下一页 ▶",
+            format!("{MEMORY_PAGE_CALLBACK}{owner}:{}", page + 1),
+        ));
+    }
+    let keyboard = (!buttons.is_empty()).then(|| InlineKeyboardMarkup::new([buttons]));
+
+    (text, keyboard)
+}
+
+const fn role_label(role: &Role) -> &'static str {
+    match role {
+        Role::User => "用户",
+        Role::Assistant => "助手",
+        Role::System => "系统",
+        Role::Tool => "工具",
+    }
+}
+
+fn message_text(message: &ChatMessage) -> String {
+    match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .map(|block| match block {
+                ContentBlock::Text { text } => text.clone(),
+                ContentBlock::ToolResult { content, .. } => content.clone(),
+                ContentBlock::ToolUse { name, .. } => format!("[调用工具 {name}]"),
+                ContentBlock::Image { .. } => "[图片]".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Render a session's history as a Markdown document.
+#[must_use]
+pub fn render_session_markdown(session: &Session) -> String {
+    let header = format!(
+        "# 会话 {}\n\n创建于 {}\n",
+        session.id,
+        session.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    let messages: Vec<String> = session
+        .messages
+        .iter()
+        .map(|message| {
+            format!(
+                "\n## {}\n\n{}\n",
+                role_label(&message.role),
+                message_text(message)
+            )
+        })
+        .collect();
+    header + &messages.concat()
+}
+
+impl TelegramBot {
+    /// Text and buttons of a `/memory` page of `user`'s memories.
+    pub async fn memory_page(
+        &self,
+        chat_id: i64,
+        user: Option<&User>,
+        page: usize,
+    ) -> Result<(String, Option<InlineKeyboardMarkup>)> {
+        let scope = Self::memory_scope(chat_id, user);
+        let mut items = self
+            .memory_manager
+            .list(&scope)
+            .await
+            .map_err(Error::Memory)?;
+        items.sort_by_key(|item| std::cmp::Reverse(item.happened_at));
+        Ok(render_memory_page(&items, page, user.map_or(0, |u| u.id.0)))
+    }
+
+    /// Delete one of the scope's memories by id, or offer its best match
+    /// for a query for deletion; only `owner` can confirm.
+    pub async fn forget(
+        &self,
+        scope: &MemoryScope,
+        owner: u64,
+        target: &str,
+    ) -> Result<(String, Option<InlineKeyboardMarkup>)> {
+        if target.is_empty() {
+            return Ok(("用法：/forget <记忆 id 或关键词>".to_string(), None));
+        }
+
+        if let Ok(id) = target.parse::<Uuid>() {
+            return Ok((self.delete_memory(scope, &id).await?, None));
+        }
+
+        let embedding = self.provider.embed(target).await.map_err(Error::Provider)?;
+        let matches = self
+            .memory_manager
+            .search_by_embedding(scope, &embedding, target, 3)
+            .await
+            .map_err(Error::Memory)?;
+        let Some(best) = matches.first() else {
+            return Ok(("没有找到相关记忆".to_string(), None));
+        };
+
+        let exact = best
+            .item
+            .summary
+            .to_lowercase()
+            .contains(&target.to_lowercase());
+        if exact || best.similarity >= FORGET_MIN_SIMILARITY {
+            let (text, keyboard) = render_forget_prompt(&best.item, owner);
+            return Ok((text, Some(keyboard)));
+        }
+
+        // Too uncertain to offer one; let the user pick by id
+        let candidates: Vec<String> = matches
+            .iter()
+            .map(|candidate| {
+                format!(
+                    "\n• {}\n  {}\n",
+                    preview(&candidate.item.summary, SUMMARY_PREVIEW_CHARS),
+                    candidate.item.id
+                )
+            })
+            .collect();
+        Ok((
+            format!(
+                "没有足够匹配的记忆，可用 /forget <id> 删除以下之一：\n{}",
+                candidates.concat()
+            ),
+            None,
+        ))
+    }
+
+    /// Delete one of the scope's memories by id.
+    pub async fn delete_memory(&self, scope: &MemoryScope, id: &Uuid) -> Result<String> {
+        let memory = &self.memory_manager;
+        let Some(item) = memory.find_by_id(scope, id).await.map_err(Error::Memory)? else {
+            return Ok(format!("未找到记忆 {id}"));
+        };
+        memory.delete(scope, id).await.map_err(Error::Memory)?;
+        Ok(format!(
+            "已删除记忆：{}",
+            preview(&item.summary, SUMMARY_PREVIEW_CHARS)
+        ))
+    }
+
+    /// Store a fact in the scope's long-term memory.
+    pub async fn remember(&self, scope: &MemoryScope, fact: &str) -> Result<String> {
+        if fact.is_empty() {
+            return Ok("用法：/remember <要记住的内容>".to_string());
+        }
+
+        let embedding = self.provider.embed(fact).await.map_err(Error::Provider)?;
+        let item = MemoryItem::new(
+            MemoryType::Semantic,
+            fact,
+            Some(embedding),
+            chrono::Utc::now(),
+        );
+        let id = self
+            .memory_manager
+            .semantic_upsert(scope, &item)
+            .await
+            .map_err(Error::Memory)?;
+        Ok(format!("已记住（{id}）"))
+    }
+
+    /// Show or switch the chat's model; `default` clears the override.
+    #[must_use]
+    pub fn switch_model(&self, chat_id: i64, model: &str) -> String {
+        let chat_id = chat_id.to_string();
+        match model {
+            "" => {
+                let settings = self.gateway.settings(&chat_id);
+                format!(
+                    "当前模型：{}",
+                    settings
+                        .model
+                        .as_deref()
+                        .unwrap_or_else(|| self.gateway.model())
+                )
+            }
+            "default" => {
+                self.gateway.update_settings(&chat_id, |s| s.model = None);
+                format!("已恢复默认模型：{}", self.gateway.model())
+            }
+            model => {
+                self.gateway
+                    .update_settings(&chat_id, |s| s.model = Some(model.to_string()));
+                format!("已切换到模型：{model}")
+            }
+        }
+    }
+
+    /// Show or toggle tool calling for the chat.
+    #[must_use]
+    pub fn toggle_tools(&self, chat_id: i64, arg: &str) -> String {
+        let chat_id = chat_id.to_string();
+        let enabled = match arg.to_lowercase().as_str() {
+            "on" => true,
+            "off" => false,
+            _ => {
+                let state = if self.gateway.settings(&chat_id).tools {
+                    "开启"
+                } else {
+                    "关闭"
+                };
+                return format!("工具调用当前为{state}。用法：/tools on|off");
+            }
+        };
+
+        self.gateway
+            .update_settings(&chat_id, |s| s.tools = enabled);
+        if enabled {
+            "已开启工具调用".to_string()
+        } else {
+            "已关闭工具调用".to_string()
+        }
+    }
+
+    /// The chat's sessions, newest first.
+    pub async fn list_sessions(&self, chat_id: i64) -> Result<String> {
+        let sessions = self
+            .gateway
+            .sessions(&chat_id.to_string())
+            .await
+            .map_err(Error::Memory)?;
+        if sessions.is_empty() {
+            return Ok("还没有会话".to_string());
+        }
+
+        let mut entries = Vec::new();
+        for info in sessions.iter().take(SESSION_LIST_LIMIT) {
+            let messages = self
+                .memory_manager
+                .get(&info.session_id)
+                .await
+                .map_err(Error::Memory)?
+                .map_or(0, |session| session.messages.len());
+            entries.push(format!(
+                "{} {} · {} 条消息\n  {}\n",
+                if info.archived { "·" } else { "▶" },
+                info.created_at.format("%Y-%m-%d %H:%M"),
+                messages,
+                info.session_id
+            ));
+        }
+        Ok(format!(
+            "💬 会话（共 {} 个）\n\n{}",
+            sessions.len(),
+            entries.join("\n")
+        ))
+    }
+
+    /// The chat's current session as a Markdown file name and body, `None`
+    /// when it has no messages yet.
+    pub async fn export_session(&self, chat_id: i64) -> Result<Option<(String, String)>> {
+        let sessions = self
+            .gateway
+            .sessions(&chat_id.to_string())
+            .await
+            .map_err(Error::Memory)?;
+        let Some(active) = sessions.iter().find(|info| !info.archived) else {
+            return Ok(None);
+        };
+        let session = self
+            .memory_manager
+            .get(&active.session_id)
+            .await
+            .map_err(Error::Memory)?;
+        Ok(session.map(|session| {
+            (
+                format!("session-{}.md", session.id),
+                render_session_markdown(&session),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memories(count: usize) -> Vec<MemoryItem> {
+        (0..count)
+            .map(|i| {
+                MemoryItem::new(
+                    MemoryType::Semantic,
+                    &format!("fact {i}"),
+                    None,
+                    chrono::Utc::now(),
+                )
+            })
+            .collect()
+    }
+
+    fn callbacks(keyboard: Option<&InlineKeyboardMarkup>) -> Vec<String> {
+        keyboard
+            .into_iter()
+            .flat_map(|k| k.inline_keyboard.iter().flatten())
+            .filter_map(|button| match &button.kind {
+                teloxide::types::InlineKeyboardButtonKind::CallbackData(data) => Some(data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_memory_page_navigation() {
+        let items = memories(12);
+
+        let (text, keyboard) = render_memory_page(&items, 0, 7);
+        assert!(text.contains("第 1/3 页，共 12 条"));
+        assert!(text.contains("1. [semantic] fact 0"));
+        assert!(!text.contains("fact 5"));
+        assert_eq!(callbacks(keyboard.as_ref()), vec!["memory:7:1"]);
+
+        let (text, keyboard) = render_memory_page(&items, 1, 7);
+        assert!(text.contains("6. [semantic] fact 5"));
+        assert_eq!(
+            callbacks(keyboard.as_ref()),
+            vec!["memory:7:0", "memory:7:2"]
+        );
+
+        // Out-of-range pages clamp to the last one
+        let (text, keyboard) = render_memory_page(&items, 9, 7);
+        assert!(text.contains("第 3/3 页"));
+        assert_eq!(callbacks(keyboard.as_ref()), vec!["memory:7:1"]);
+
+        assert_eq!(render_memory_page(&[], 0, 7).1, None);
+        assert_eq!(parse_memory_callback("memory:7:2"), Some((7, 2)));
+        assert_eq!(parse_memory_callback("memory:2"), None);
+    }
+
+    #[test]
+    fn test_forget_prompt_buttons() {
+        let item = &memories(1)[0];
+        let (text, keyboard) = render_forget_prompt(item, 7);
+        assert!(text.contains("fact 0"));
+
+        let data = callbacks(Some(&keyboard));
+        assert_eq!(
+            data,
+            vec![
+                format!("forget:7:{}", item.id),
+                "forget:7:cancel".to_string()
+            ]
+        );
+        assert!(data.iter().all(|d| d.len() <= 64));
+        assert_eq!(parse_forget_callback(&data[0]), Some((7, Some(item.id))));
+        assert_eq!(parse_forget_callback(&data[1]), Some((7, None)));
+        assert_eq!(parse_forget_callback("forget:7:nope"), None);
+        assert_eq!(parse_forget_callback("memory:7:1"), None);
+    }
+
+    #[test]
+    fn test_render_session_markdown() {
+        let now = chrono::Utc::now();
+        let session = Session {
+            id: Uuid::nil(),
+            messages: vec![
+                ChatMessage {
+                    role: Role::User,
+                    content: MessageContent::Text("hi".to_string()),
+                },
+                ChatMessage {
+                    role: Role::Assistant,
+                    content: MessageContent::Blocks(vec![ContentBlock::Text {
+                        text: "hello".to_string(),
+                    }]),
+                },
+            ],
+            created_at: now,
+            updated_at: now,
+        };
+
+        let markdown = render_session_markdown(&session);
+        assert!(markdown.starts_with(&format!("# 会话 {}", Uuid::nil())));
+        assert!(markdown.ends_with("\n## 用户\n\nhi\n\n## 助手\n\nhello\n"));
+    }
+}
@@ -0,0 +1,145 @@
+//! Turning documents, photos and voice notes into agent input.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use nanors_core::channel::InboundMessage;
+use std::path::{Path, PathBuf};
+use teloxide::net::Download;
+use teloxide::prelude::*;
+use teloxide::types::FileMeta;
+use tracing::info;
+
+use crate::{Error, Result, TelegramBot};
+
+/// The Bot API refuses to serve files larger than 20 MB.
+const MAX_DOWNLOAD_SIZE: u32 = 20 * 1024 * 1024;
+
+/// Directory a chat's uploaded documents are saved to.
+#[must_use]
+pub fn upload_dir(working_dir: &Path, chat_id: i64) -> PathBuf {
+    working_dir.join("uploads").join(chat_id.to_string())
+}
+
+/// Keep only the final path component so uploads cannot escape their
+/// directory.
+fn safe_file_name(name: Option<&str>, fallback: &str) -> String {
+    name.and_then(|n| Path::new(n).file_name())
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.starts_with('.'))
+        .map_or_else(|| fallback.to_string(), str::to_string)
+}
+
+/// Agent input for a message carrying an attachment.
+fn with_caption(marker: &str, caption: Option<&str>) -> String {
+    caption
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map_or_else(|| marker.to_string(), |c| format!("{marker}\n{c}"))
+}
+
+impl TelegramBot {
+    async fn download(&self, file: &FileMeta) -> Result<Vec<u8>> {
+        if file.size > MAX_DOWNLOAD_SIZE {
+            return Err(Error::Unsupported(format!(
+                "文件过大（{} MB），Telegram 机器人最多只能下载 20 MB",
+                file.size / 1024 / 1024
+            )));
+        }
+
+        let file = self.bot.get_file(file.id.clone()).await?;
+        let mut data = Vec::new();
+        self.bot.download_file(&file.path, &mut data).await?;
+        Ok(data)
+    }
+
+    /// Build agent input from a document, photo or voice message.
+    ///
+    /// Documents are saved under the chat's upload directory and their path
+    /// is given to the agent; photos are attached as images; voice notes are
+    /// transcribed.
+    pub(crate) async fn media_message(&self, msg: &Message) -> Result<InboundMessage> {
+        let chat_id = msg.chat.id.0;
+        let caption = msg.caption();
+
+        let text = if let Some(doc) = msg.document() {
+            let name = safe_file_name(doc.file_name.as_deref(), &doc.file.unique_id.0);
+            let dir = upload_dir(&self.working_dir, chat_id);
+            let path = dir.join(&name);
+
+            let data = self.download(&doc.file).await?;
+            tokio::fs::create_dir_all(&dir).await?;
+            tokio::fs::write(&path, data).await?;
+            info!("Saved upload from chat {chat_id} to {}", path.display());
+
+            with_caption(
+                &format!("[用户上传了文件，已保存到 {}]", path.display()),
+                caption,
+            )
+        } else if let Some(photo) = msg.photo().and_then(|sizes| {
+            sizes
+                .iter()
+                .max_by_key(|p| u64::from(p.width) * u64::from(p.height))
+        }) {
+            let data = self.download(&photo.file).await?;
+            return Ok(
+                InboundMessage::new(chat_id.to_string(), with_caption("[图片]", caption))
+                    .with_image("image/jpeg", STANDARD.encode(data)),
+            );
+        } else if let Some(voice) = msg.voice() {
+            let Some(speech) = &self.speech_to_text else {
+                return Err(Error::Unsupported(
+                    "暂不支持语音消息：未配置语音识别".into(),
+                ));
+            };
+            let mime_type = voice
+                .mime_type
+                .as_ref()
+                .map_or_else(|| "audio/ogg".to_string(), ToString::to_string);
+
+            let data = self.download(&voice.file).await?;
+            let transcript = speech
+                .transcribe(data, &mime_type)
+                .await
+                .map_err(Error::Provider)?;
+            info!("Transcribed voice note from chat {chat_id}: {transcript}");
+
+            format!("[语音] {transcript}")
+        } else {
+            return Err(Error::Config("No text content".into()));
+        };
+
+        Ok(InboundMessage::new(chat_id.to_string(), text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_file_name_strips_directories() {
+        assert_eq!(safe_file_name(Some("report.pdf"), "x"), "report.pdf");
+        assert_eq!(safe_file_name(Some("../../etc/passwd"), "x"), "passwd");
+        assert_eq!(safe_file_name(Some(".."), "x"), "x");
+        assert_eq!(safe_file_name(Some(".bashrc"), "x"), "x");
+        assert_eq!(safe_file_name(None, "fallback"), "fallback");
+    }
+
+    #[test]
+    fn test_with_caption() {
+        assert_eq!(with_caption("[图片]", None), "[图片]");
+        assert_eq!(with_caption("[图片]", Some("  ")), "[图片]");
+        assert_eq!(
+            with_caption("[图片]", Some("这是什么？")),
+            "[图片]\n这是什么？"
+        );
+    }
+
+    #[test]
+    fn test_upload_dir_is_per_chat() {
+        assert_eq!(
+            upload_dir(Path::new("/work"), -100),
+            Path::new("/work/uploads/-100")
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Webhook delivery: Telegram posts updates to a local HTTP(S) listener.
+
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use teloxide::types::InputFile;
+use teloxide::update_listeners::webhooks;
+use tracing::info;
+use url::Url;
+
+use crate::{Error, Result};
+
+/// Certificate and private key (PEM) for serving HTTPS directly.
+#[derive(Debug, Clone)]
+pub struct TlsFiles {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// Where Telegram delivers updates and how the local listener accepts them.
+#[derive(Debug, Clone)]
+pub struct WebhookOptions {
+    /// Public URL registered with Telegram
+    pub url: Url,
+    /// Address the listener binds to
+    pub listen: SocketAddr,
+    /// Path updates are accepted on (defaults to the URL's path)
+    pub path: Option<String>,
+    /// Expected `X-Telegram-Bot-Api-Secret-Token` (random when unset)
+    pub secret_token: Option<String>,
+    /// Serve HTTPS instead of plain HTTP (e.g. behind a reverse proxy)
+    pub tls: Option<TlsFiles>,
+    /// Upload the certificate to Telegram so a self-signed one is trusted
+    pub self_signed: bool,
+}
+
+impl WebhookOptions {
+    /// Options for `url`, listening on `listen`.
+    pub fn new(url: &str, listen: &str) -> Result<Self> {
+        let url = Url::parse(url).map_err(|e| Error::Config(format!("webhook url {url}: {e}")))?;
+        let listen = listen
+            .parse()
+            .map_err(|e| Error::Config(format!("webhook listen address {listen}: {e}")))?;
+        Ok(Self {
+            url,
+            listen,
+            path: None,
+            secret_token: None,
+            tls: None,
+            self_signed: false,
+        })
+    }
+
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_secret_token(mut self, secret_token: impl Into<String>) -> Self {
+        self.secret_token = Some(secret_token.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_tls(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        self.tls = Some(TlsFiles {
+            cert: cert.into(),
+            key: key.into(),
+        });
+        self
+    }
+
+    #[must_use]
+    pub const fn self_signed(mut self, self_signed: bool) -> Self {
+        self.self_signed = self_signed;
+        self
+    }
+
+    /// The teloxide options these translate to.
+    pub(crate) fn to_teloxide(&self) -> webhooks::Options {
+        let mut options = webhooks::Options::new(self.listen, self.url.clone());
+        if let Some(path) = &self.path {
+            options = options.path(path.clone());
+        }
+        if let Some(secret_token) = &self.secret_token {
+            options = options.secret_token(secret_token.clone());
+        }
+        if let Some(tls) = self.tls.as_ref().filter(|_| self.self_signed) {
+            options = options.certificate(InputFile::file(&tls.cert));
+        }
+        options
+    }
+}
+
+/// Serve the webhook router until `shutdown` resolves.
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    router: Router,
+    tls: Option<&TlsFiles>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let addr = listener.local_addr()?;
+
+    let Some(tls) = tls else {
+        info!("Webhook listening on http://{addr}");
+        return Ok(axum::serve(listener, router)
+            .with_graceful_shutdown(shutdown)
+            .await?);
+    };
+
+    // Several rustls backends are linked in, so pick one explicitly
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+    let config = RustlsConfig::from_pem_file(&tls.cert, &tls.key).await?;
+    let handle = axum_server::Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            shutdown.await;
+            handle.graceful_shutdown(None);
+        }
+    });
+
+    info!("Webhook listening on https://{addr}");
+    axum_server::from_tcp_rustls(listener.into_std()?, config)
+        .handle(handle)
+        .serve(router.into_make_service())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use std::time::Duration;
+    use teloxide::update_listeners::AsUpdateStream;
+
+    /// An update as recorded from the Bot API.
+    const RECORDED_UPDATE: &str = r#"{
+        "update_id": 10000,
+        "message": {
+            "message_id": 1365,
+            "date": 1441645532,
+            "chat": {"id": 1111111, "type": "private", "first_name": "Alice"},
+            "from": {"id": 1111111, "is_bot": false, "first_name": "Alice"},
+            "text": "你好"
+        }
+    }"#;
+
+    #[test]
+    fn test_options_default_path_from_url() -> Result<()> {
+        let options = WebhookOptions::new("https://bot.example.com/tg/hook", "127.0.0.1:8443")?;
+        assert_eq!(options.to_teloxide().path, "/tg/hook");
+        assert_eq!(options.with_path("/hook").to_teloxide().path, "/hook");
+        assert!(WebhookOptions::new("not a url", "127.0.0.1:8443").is_err());
+        assert!(WebhookOptions::new("https://bot.example.com", "localhost").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listener_accepts_recorded_update_with_secret() -> anyhow::Result<()> {
+        let options = WebhookOptions::new("https://bot.example.com/hook", "127.0.0.1:0")?
+            .with_secret_token("s3cret");
+        let (mut updates, stop, router) = webhooks::axum_no_setup(options.to_teloxide());
+
+        let listener = tokio::net::TcpListener::bind(options.listen).await?;
+        let url = format!("http://{}/hook", listener.local_addr()?);
+        tokio::spawn(serve(listener, router, None, stop));
+
+        let client = reqwest::Client::new();
+        let post = |secret: &'static str| {
+            client
+                .post(&url)
+                .header("X-Telegram-Bot-Api-Secret-Token", secret)
+                .header("Content-Type", "application/json")
+                .body(RECORDED_UPDATE)
+                .send()
+        };
+
+        assert_eq!(post("wrong").await?.status(), 401);
+        assert_eq!(post("s3cret").await?.status(), 200);
+
+        let stream = updates.as_stream();
+        tokio::pin!(stream);
+        let update = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("listener closed"))?
+            .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+        assert_eq!(update.id.0, 10000);
+        let teloxide::types::UpdateKind::Message(msg) = update.kind else {
+            anyhow::bail!("expected a message update");
+        };
+        assert_eq!(msg.text(), Some("你好"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listener_serves_https() -> anyhow::Result<()> {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let dir = std::env::temp_dir().join(format!("nanors_webhook_{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("cert.pem"), cert.cert.pem())?;
+        std::fs::write(dir.join("key.pem"), cert.signing_key.serialize_pem())?;
+
+        let options = WebhookOptions::new("https://localhost/hook", "127.0.0.1:0")?
+            .with_secret_token("s3cret")
+            .with_tls(dir.join("cert.pem"), dir.join("key.pem"));
+        let (_updates, stop, router) = webhooks::axum_no_setup(options.to_teloxide());
+
+        let listener = tokio::net::TcpListener::bind(options.listen).await?;
+        let url = format!("https://localhost:{}/hook", listener.local_addr()?.port());
+        let tls = options.tls.clone();
+        tokio::spawn(async move { serve(listener, router, tls.as_ref(), stop).await });
+
+        let client = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(cert.cert.pem().as_bytes())?)
+            .build()?;
+        let response = client
+            .post(&url)
+            .header("X-Telegram-Bot-Api-Secret-Token", "s3cret")
+            .body(RECORDED_UPDATE)
+            .send()
+            .await?;
+        assert_eq!(response.status(), 200);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
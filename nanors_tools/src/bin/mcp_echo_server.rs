@@ -0,0 +1,65 @@
+//! Minimal MCP stdio server used to exercise the MCP client.
+//!
+//! Exposes a single `echo` tool that returns its `text` argument.
+
+use std::io::{BufRead, Write};
+
+use nanors_tools::mcp::protocol;
+use serde_json::{Value, json};
+
+fn handle(method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": protocol::PROTOCOL_VERSION,
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": "echo", "version": "0.1.0"},
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({
+            "tools": [{
+                "name": "echo",
+                "description": "Echo the input text",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"text": {"type": "string"}},
+                    "required": ["text"],
+                },
+            }],
+        })),
+        "tools/call" if params["name"] == "echo" => {
+            let text = params["arguments"]["text"].as_str().unwrap_or_default();
+            Ok(json!({"content": [{"type": "text", "text": text}], "isError": false}))
+        }
+        "tools/call" => Err((protocol::INVALID_PARAMS, "unknown tool".to_string())),
+        _ => Err((
+            protocol::METHOD_NOT_FOUND,
+            format!("unknown method: {method}"),
+        )),
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let Ok(msg) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        // Notifications carry no id and get no reply
+        let Some(id) = msg.get("id") else {
+            continue;
+        };
+        let method = msg["method"].as_str().unwrap_or_default();
+
+        let reply = match handle(method, &msg["params"]) {
+            Ok(result) => protocol::response(id, result),
+            Err((code, message)) => protocol::error_response(id, code, &message),
+        };
+        writeln!(stdout, "{reply}")?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
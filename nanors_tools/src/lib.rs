@@ -3,6 +3,7 @@ pub mod bash;
 pub mod command_runner;
 pub mod glob;
 pub mod grep;
+pub mod mcp;
 pub mod path_guard;
 pub mod read_file;
 pub mod web_fetch;
@@ -12,9 +13,11 @@ pub use apply_patch::ApplyPatchTool;
 pub use bash::BashTool;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
+pub use mcp::{McpManager, McpServer, McpTransport};
 pub use read_file::ReadFileTool;
 pub use web_fetch::{WebFetchConfig, WebFetchTool};
 
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
@@ -90,9 +93,13 @@ impl ToolResult {
     }
 }
 
-/// Tool trait (internal use only)
+/// Tool trait
+///
+/// Built-in tools implement this trait and are wrapped in [`StaticTool`]
+/// variants. Downstream crates implement it for their own tools and register
+/// them at runtime through [`StaticToolRegistry::register_dynamic`].
 #[async_trait]
-trait Tool: Send + Sync {
+pub trait Tool: Send + Sync {
     fn name(&self) -> &str;
     fn definition(&self) -> ToolDefinition;
     async fn execute(&self, input: serde_json::Value) -> ToolResult;
@@ -101,11 +108,8 @@ trait Tool: Send + Sync {
 /// Static dispatch tool enum
 ///
 /// Uses static dispatch instead of dynamic dispatch (trait objects)
-/// for zero-cost abstraction and better performance.
-/// Static dispatch tool enum
-///
-/// Uses static dispatch instead of dynamic dispatch (trait objects)
-/// for zero-cost abstraction and better performance.
+/// for zero-cost abstraction and better performance. Tools that are not
+/// known at compile time are held in the `Dynamic` variant.
 pub enum StaticTool {
     Bash(BashTool),
     ReadFile(ReadFileTool),
@@ -113,8 +117,11 @@ pub enum StaticTool {
     Glob(GlobTool),
     Grep(GrepTool),
     WebFetch(WebFetchTool),
+    /// Tool registered at runtime (dynamic dispatch)
+    Dynamic(Box<dyn Tool>),
 }
 
+#[allow(clippy::must_use_candidate)]
 impl StaticTool {
     /// Fast name matching (compile-time optimization)
     pub fn name_str(&self) -> &str {
@@ -125,6 +132,7 @@ impl StaticTool {
             Self::Glob(_) => "glob",
             Self::Grep(_) => "grep",
             Self::WebFetch(_) => "web_fetch",
+            Self::Dynamic(t) => t.name(),
         }
     }
 
@@ -137,6 +145,7 @@ impl StaticTool {
             Self::Glob(t) => t.definition(),
             Self::Grep(t) => t.definition(),
             Self::WebFetch(t) => t.definition(),
+            Self::Dynamic(t) => t.definition(),
         }
     }
 
@@ -149,6 +158,7 @@ impl StaticTool {
             Self::Glob(t) => t.execute(input).await,
             Self::Grep(t) => t.execute(input).await,
             Self::WebFetch(t) => t.execute(input).await,
+            Self::Dynamic(t) => t.execute(input).await,
         }
     }
 }
@@ -157,15 +167,22 @@ impl StaticTool {
 ///
 /// Uses static dispatch via enum matching instead of dynamic dispatch
 /// through trait objects. This eliminates vtable lookup overhead and
-/// enables compiler optimizations like inlining.
+/// enables compiler optimizations like inlining. Runtime tools registered
+/// via [`Self::register_dynamic`] are the only entries dispatched through
+/// a vtable.
 pub struct StaticToolRegistry {
     tools: Vec<StaticTool>,
+    /// Names of registered tools that are hidden from the model
+    disabled: HashSet<String>,
 }
 
 impl StaticToolRegistry {
     #[must_use]
     pub fn new() -> Self {
-        Self { tools: Vec::new() }
+        Self {
+            tools: Vec::new(),
+            disabled: HashSet::new(),
+        }
     }
 
     /// Create registry with all default tools
@@ -186,17 +203,102 @@ impl StaticToolRegistry {
                         .expect("Failed to create WebFetchTool"),
                 ),
             ],
+            disabled: HashSet::new(),
+        }
+    }
+
+    /// Register a tool.
+    ///
+    /// # Errors
+    /// Returns an error if a tool with the same name is already registered.
+    pub fn register(&mut self, tool: StaticTool) -> anyhow::Result<()> {
+        let name = tool.name_str();
+        if self.contains(name) {
+            anyhow::bail!("Tool name collision: '{name}' is already registered");
+        }
+        self.tools.push(tool);
+        Ok(())
+    }
+
+    /// Register a tool implemented outside this crate.
+    ///
+    /// # Errors
+    /// Returns an error if a tool with the same name is already registered.
+    pub fn register_dynamic(&mut self, tool: Box<dyn Tool>) -> anyhow::Result<()> {
+        self.register(StaticTool::Dynamic(tool))
+    }
+
+    /// Check whether a tool with the given name is registered.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.iter().any(|t| t.name_str() == name)
+    }
+
+    /// Check whether a tool is registered and enabled.
+    #[must_use]
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.contains(name) && !self.disabled.contains(name)
+    }
+
+    /// Enable or disable a registered tool.
+    ///
+    /// Returns `false` if no tool with the given name is registered.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if !self.contains(name) {
+            return false;
+        }
+        if enabled {
+            self.disabled.remove(name);
+        } else {
+            self.disabled.insert(name.to_string());
+        }
+        true
+    }
+
+    /// Apply an allow/deny list to the registered tools.
+    ///
+    /// An empty `enabled` list keeps every tool enabled; otherwise only the
+    /// listed tools stay enabled. Tools in `disabled` are always disabled.
+    /// Unknown names are logged and ignored.
+    pub fn apply_filter(&mut self, enabled: &[String], disabled: &[String]) {
+        for name in enabled.iter().chain(disabled) {
+            if !self.contains(name) {
+                tracing::warn!("Tool filter references unknown tool: {name}");
+            }
         }
+
+        let names: Vec<String> = self.names().into_iter().map(String::from).collect();
+        for name in names {
+            let allowed = enabled.is_empty() || enabled.contains(&name);
+            let denied = disabled.contains(&name);
+            self.set_enabled(&name, allowed && !denied);
+        }
+    }
+
+    /// Names of all registered tools, enabled or not.
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(StaticTool::name_str).collect()
     }
 
+    /// Definitions of all enabled tools.
     #[must_use]
     pub fn definitions(&self) -> Vec<ToolDefinition> {
-        self.tools.iter().map(|t| t.definition()).collect()
+        self.tools
+            .iter()
+            .filter(|t| !self.disabled.contains(t.name_str()))
+            .map(StaticTool::definition)
+            .collect()
     }
 
     pub async fn execute(&self, name: &str, input: serde_json::Value) -> ToolResult {
         let started = Instant::now();
 
+        if self.disabled.contains(name) {
+            return ToolResult::error(format!("Tool is disabled: {name}"))
+                .with_error_type("tool_disabled");
+        }
+
         let result = match self.tools.iter().find(|t| t.name_str() == name) {
             Some(tool) => tool.execute(input).await,
             None => {
@@ -396,6 +498,7 @@ mod static_dispatch_tests {
     }
 
     #[tokio::test]
+    #[allow(clippy::manual_assert)]
     async fn static_dispatch_read_file() {
         let registry = StaticToolRegistry::with_default_tools(".");
         let result = registry
@@ -465,3 +568,104 @@ mod static_dispatch_tests {
         assert!(names.contains(&"web_fetch"));
     }
 }
+
+/// Dynamic registration tests
+#[cfg(test)]
+mod dynamic_registration_tests {
+    use super::*;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "echo".into(),
+                description: "Echo the input text".into(),
+                input_schema: schema_object(json!({"text": {"type": "string"}}), &["text"]),
+            }
+        }
+
+        async fn execute(&self, input: serde_json::Value) -> ToolResult {
+            ToolResult::success(input["text"].as_str().unwrap_or_default())
+        }
+    }
+
+    struct FakeBashTool;
+
+    #[async_trait]
+    impl Tool for FakeBashTool {
+        fn name(&self) -> &'static str {
+            "bash"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "bash".into(),
+                description: "Shadows the builtin".into(),
+                input_schema: schema_object(json!({}), &[]),
+            }
+        }
+
+        async fn execute(&self, _input: serde_json::Value) -> ToolResult {
+            ToolResult::success("")
+        }
+    }
+
+    #[tokio::test]
+    async fn dynamic_tool_executes_alongside_static() -> anyhow::Result<()> {
+        let mut registry = StaticToolRegistry::with_default_tools(".");
+        registry.register_dynamic(Box::new(EchoTool))?;
+
+        assert_eq!(registry.definitions().len(), 7);
+        let result = registry.execute("echo", json!({"text": "hi"})).await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "hi");
+        Ok(())
+    }
+
+    #[test]
+    fn name_collision_is_rejected() {
+        let mut registry = StaticToolRegistry::with_default_tools(".");
+        assert!(registry.register_dynamic(Box::new(FakeBashTool)).is_err());
+        assert!(
+            registry
+                .register(StaticTool::Glob(GlobTool::new(".")))
+                .is_err()
+        );
+        assert_eq!(registry.names().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn disabled_tool_is_hidden_and_rejected() {
+        let mut registry = StaticToolRegistry::with_default_tools(".");
+        assert!(registry.set_enabled("bash", false));
+        assert!(!registry.set_enabled("missing", false));
+
+        assert!(!registry.is_enabled("bash"));
+        assert!(registry.definitions().iter().all(|d| d.name != "bash"));
+        let result = registry
+            .execute("bash", json!({"command": "echo hi"}))
+            .await;
+        assert!(result.is_error);
+        assert_eq!(result.error_type.as_deref(), Some("tool_disabled"));
+    }
+
+    #[test]
+    fn apply_filter_allow_and_deny() {
+        let mut registry = StaticToolRegistry::with_default_tools(".");
+        registry.apply_filter(
+            &["bash".to_string(), "grep".to_string()],
+            &["grep".to_string()],
+        );
+        let names: Vec<_> = registry.definitions().into_iter().map(|d| d.name).collect();
+        assert_eq!(names, vec!["bash".to_string()]);
+
+        registry.apply_filter(&[], &[]);
+        assert_eq!(registry.definitions().len(), 6);
+    }
+}
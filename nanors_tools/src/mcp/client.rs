@@ -0,0 +1,346 @@
+//! MCP client with stdio and streamable HTTP transports.
+
+use anyhow::{Context, Result};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use super::protocol;
+use super::{McpServer, McpTransport};
+use crate::{ToolDefinition, ToolResult};
+
+/// Header carrying the session id for streamable HTTP servers.
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Newline-delimited JSON-RPC over a spawned child process.
+struct StdioTransport {
+    /// Kept alive so the child is killed when the transport is dropped
+    _child: Child,
+    io: Mutex<StdioPipes>,
+}
+
+struct StdioPipes {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl StdioTransport {
+    fn spawn(
+        command: &str,
+        args: &[String],
+        env: &std::collections::HashMap<String, String>,
+        cwd: Option<&str>,
+    ) -> Result<Self> {
+        let mut cmd = tokio::process::Command::new(command);
+        cmd.args(args)
+            .envs(env)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = cwd {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to spawn MCP server: {command}"))?;
+        let stdin = child.stdin.take().context("MCP server stdin unavailable")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("MCP server stdout unavailable")?;
+
+        // Forward server stderr to tracing instead of the terminal
+        if let Some(stderr) = child.stderr.take() {
+            let command = command.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("[mcp:{command}] {line}");
+                }
+            });
+        }
+
+        Ok(Self {
+            _child: child,
+            io: Mutex::new(StdioPipes {
+                stdin,
+                stdout: BufReader::new(stdout),
+            }),
+        })
+    }
+
+    async fn write_message(stdin: &mut ChildStdin, msg: &Value) -> Result<()> {
+        let mut line = serde_json::to_string(msg)?;
+        line.push('\n');
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    async fn request(&self, msg: &Value, id: u64) -> Result<Value> {
+        let mut io = self.io.lock().await;
+        Self::write_message(&mut io.stdin, msg).await?;
+
+        loop {
+            let mut line = String::new();
+            if io.stdout.read_line(&mut line).await? == 0 {
+                anyhow::bail!("MCP server closed the connection");
+            }
+            let Ok(incoming) = serde_json::from_str::<Value>(line.trim()) else {
+                debug!("Ignoring non-JSON line from MCP server: {}", line.trim());
+                continue;
+            };
+
+            if protocol::is_response_to(&incoming, id) {
+                return Ok(incoming);
+            }
+
+            // Answer server-initiated pings; ignore other requests and notifications
+            if incoming.get("method").and_then(Value::as_str) == Some("ping") {
+                if let Some(ping_id) = incoming.get("id") {
+                    let pong = protocol::response(ping_id, json!({}));
+                    Self::write_message(&mut io.stdin, &pong).await?;
+                }
+            }
+        }
+    }
+
+    async fn notify(&self, msg: &Value) -> Result<()> {
+        let mut io = self.io.lock().await;
+        Self::write_message(&mut io.stdin, msg).await
+    }
+}
+
+/// JSON-RPC over MCP streamable HTTP.
+struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: std::collections::HashMap<String, String>,
+    session_id: Mutex<Option<String>>,
+}
+
+impl HttpTransport {
+    fn new(url: &str, headers: &std::collections::HashMap<String, String>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            headers: headers.clone(),
+            session_id: Mutex::new(None),
+        })
+    }
+
+    async fn post(&self, msg: &Value) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(msg);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if let Some(session_id) = self.session_id.lock().await.as_deref() {
+            request = request.header(SESSION_HEADER, session_id);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("MCP HTTP error: {}", response.status());
+        }
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock().await = Some(session_id.to_string());
+        }
+
+        Ok(response)
+    }
+
+    async fn request(&self, msg: &Value, id: u64) -> Result<Value> {
+        let response = self.post(msg).await?;
+        let is_sse = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        let body = response.text().await?;
+
+        if is_sse {
+            protocol::parse_sse_messages(&body)
+                .into_iter()
+                .find(|m| protocol::is_response_to(m, id))
+                .context("MCP server sent no response in event stream")
+        } else {
+            let incoming: Value =
+                serde_json::from_str(&body).context("Invalid JSON from MCP server")?;
+            // Servers may batch; pick our response out of an array
+            match incoming {
+                Value::Array(batch) => batch
+                    .into_iter()
+                    .find(|m| protocol::is_response_to(m, id))
+                    .context("MCP server sent no matching response"),
+                single => Ok(single),
+            }
+        }
+    }
+
+    async fn notify(&self, msg: &Value) -> Result<()> {
+        self.post(msg).await?;
+        Ok(())
+    }
+}
+
+/// Transport variants (static dispatch)
+enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+}
+
+impl Transport {
+    async fn request(&self, msg: &Value, id: u64) -> Result<Value> {
+        match self {
+            Self::Stdio(t) => t.request(msg, id).await,
+            Self::Http(t) => t.request(msg, id).await,
+        }
+    }
+
+    async fn notify(&self, msg: &Value) -> Result<()> {
+        match self {
+            Self::Stdio(t) => t.notify(msg).await,
+            Self::Http(t) => t.notify(msg).await,
+        }
+    }
+}
+
+/// Connection to a single MCP server.
+pub struct McpClient {
+    name: String,
+    transport: Transport,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+impl McpClient {
+    /// Connect to an MCP server and perform the initialize handshake.
+    pub async fn connect(config: &McpServer) -> Result<Self> {
+        let transport = match &config.transport {
+            McpTransport::Stdio {
+                command,
+                args,
+                env,
+                cwd,
+            } => Transport::Stdio(StdioTransport::spawn(command, args, env, cwd.as_deref())?),
+            McpTransport::Http { url, headers } => {
+                Transport::Http(HttpTransport::new(url, headers)?)
+            }
+        };
+
+        let client = Self {
+            name: config.name.clone(),
+            transport,
+            next_id: AtomicU64::new(1),
+            timeout: config.timeout,
+        };
+
+        let init = client
+            .request(
+                "initialize",
+                Some(json!({
+                    "protocolVersion": protocol::PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "nanors",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                })),
+            )
+            .await
+            .with_context(|| format!("MCP initialize failed for '{}'", config.name))?;
+
+        let server_name = init["serverInfo"]["name"].as_str().unwrap_or("unknown");
+        info!(
+            "Connected to MCP server '{}' ({}, protocol {})",
+            config.name,
+            server_name,
+            init["protocolVersion"].as_str().unwrap_or("unknown")
+        );
+
+        client
+            .transport
+            .notify(&protocol::notification("notifications/initialized", None))
+            .await?;
+
+        Ok(client)
+    }
+
+    /// Server name from configuration.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Send a request and wait for its result.
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let msg = protocol::request(id, method, params);
+        let response = tokio::time::timeout(self.timeout, self.transport.request(&msg, id))
+            .await
+            .with_context(|| format!("MCP request '{method}' timed out"))??;
+        protocol::into_result(response)
+    }
+
+    /// List the tools exposed by the server (follows pagination cursors).
+    ///
+    /// Returned definitions carry the remote tool names.
+    pub async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = cursor.as_ref().map(|c| json!({"cursor": c}));
+            let result = self.request("tools/list", params).await?;
+
+            for tool in result["tools"].as_array().into_iter().flatten() {
+                let Some(name) = tool.get("name").and_then(Value::as_str) else {
+                    warn!("MCP server '{}' listed a tool without a name", self.name);
+                    continue;
+                };
+                tools.push(protocol::tool_definition_from_mcp(tool, name));
+            }
+
+            match result.get("nextCursor").and_then(Value::as_str) {
+                Some(next) if !next.is_empty() => cursor = Some(next.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(tools)
+    }
+
+    /// Call a remote tool.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> ToolResult {
+        match self
+            .request(
+                "tools/call",
+                Some(json!({"name": name, "arguments": arguments})),
+            )
+            .await
+        {
+            Ok(result) => protocol::tool_result_from_mcp(&result),
+            Err(e) => ToolResult::error(format!("MCP server '{}': {e}", self.name))
+                .with_error_type("mcp_error"),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Model Context Protocol (MCP) support.
+//!
+//! Connects to external MCP tool servers over stdio (spawned child processes)
+//! or streamable HTTP, and exposes their tools through [`StaticToolRegistry`].
+//! The [`server`] module does the reverse and serves a registry over stdio.
+
+#![allow(clippy::missing_errors_doc)]
+
+mod client;
+pub mod protocol;
+pub mod server;
+
+pub use client::McpClient;
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::{StaticToolRegistry, Tool, ToolDefinition, ToolResult};
+
+/// An MCP server to connect to.
+#[derive(Debug, Clone)]
+pub struct McpServer {
+    /// Server name used in logs and error messages
+    pub name: String,
+
+    /// How to reach the server
+    pub transport: McpTransport,
+
+    /// Prefix added to remote tool names (`{prefix}_{tool}`) to avoid collisions
+    pub tool_prefix: Option<String>,
+
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+impl McpServer {
+    /// Name the model sees for a remote tool.
+    #[must_use]
+    pub fn exposed_tool_name(&self, remote_name: &str) -> String {
+        self.tool_prefix.as_deref().map_or_else(
+            || remote_name.to_string(),
+            |prefix| format!("{prefix}_{remote_name}"),
+        )
+    }
+}
+
+/// How to reach an MCP server.
+#[derive(Debug, Clone)]
+pub enum McpTransport {
+    /// Spawn a child process and speak JSON-RPC over its stdin/stdout
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        cwd: Option<String>,
+    },
+    /// POST JSON-RPC to a streamable HTTP endpoint
+    Http {
+        url: String,
+        headers: HashMap<String, String>,
+    },
+}
+
+/// A remote MCP tool, registered as a dynamic tool.
+pub struct McpTool {
+    client: Arc<McpClient>,
+    remote_name: String,
+    definition: ToolDefinition,
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.definition.clone()
+    }
+
+    async fn execute(&self, mut input: serde_json::Value) -> ToolResult {
+        // Internal auth context is not meant for external servers
+        if let Some(obj) = input.as_object_mut() {
+            obj.remove(crate::AUTH_CONTEXT_KEY);
+        }
+        self.client.call_tool(&self.remote_name, input).await
+    }
+}
+
+/// Connected server with its tool list, fetched once at connect time.
+struct ConnectedServer {
+    client: Arc<McpClient>,
+    /// (remote name, definition with exposed name)
+    tools: Vec<(String, ToolDefinition)>,
+}
+
+/// Set of connected MCP servers.
+///
+/// Connect once at startup, then call [`Self::register_into`] for every
+/// registry that should expose the remote tools.
+#[derive(Default)]
+pub struct McpManager {
+    servers: Vec<ConnectedServer>,
+}
+
+impl McpManager {
+    /// Connect to every server in `configs`.
+    ///
+    /// Servers that fail to connect or list tools are logged and skipped.
+    pub async fn connect_all(configs: &[McpServer]) -> Self {
+        let mut servers = Vec::new();
+
+        for config in configs {
+            match Self::connect_server(config).await {
+                Ok(server) => {
+                    info!(
+                        "MCP server '{}' provides {} tools",
+                        config.name,
+                        server.tools.len()
+                    );
+                    servers.push(server);
+                }
+                Err(e) => warn!("Skipping MCP server '{}': {e:#}", config.name),
+            }
+        }
+
+        Self { servers }
+    }
+
+    async fn connect_server(config: &McpServer) -> anyhow::Result<ConnectedServer> {
+        let client = Arc::new(McpClient::connect(config).await?);
+        let tools = client
+            .list_tools()
+            .await?
+            .into_iter()
+            .map(|mut def| {
+                let remote_name = std::mem::take(&mut def.name);
+                def.name = config.exposed_tool_name(&remote_name);
+                (remote_name, def)
+            })
+            .collect();
+        Ok(ConnectedServer { client, tools })
+    }
+
+    /// Number of remote tools across all connected servers.
+    #[must_use]
+    pub fn tool_count(&self) -> usize {
+        self.servers.iter().map(|s| s.tools.len()).sum()
+    }
+
+    /// Register all remote tools into a registry.
+    ///
+    /// Tools whose names collide with already registered tools are skipped.
+    /// Returns the number of tools registered.
+    pub fn register_into(&self, registry: &mut StaticToolRegistry) -> usize {
+        let mut registered = 0;
+
+        for server in &self.servers {
+            for (remote_name, definition) in &server.tools {
+                let tool = McpTool {
+                    client: Arc::clone(&server.client),
+                    remote_name: remote_name.clone(),
+                    definition: definition.clone(),
+                };
+                match registry.register_dynamic(Box::new(tool)) {
+                    Ok(()) => registered += 1,
+                    Err(e) => warn!("MCP server '{}': {e}", server.client.name()),
+                }
+            }
+        }
+
+        registered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposed_tool_name_applies_prefix() {
+        let mut server = McpServer {
+            name: "wiki".to_string(),
+            transport: McpTransport::Http {
+                url: "http://localhost:8080/mcp".to_string(),
+                headers: HashMap::new(),
+            },
+            tool_prefix: Some("wiki".to_string()),
+            timeout: Duration::from_secs(60),
+        };
+        assert_eq!(server.exposed_tool_name("search"), "wiki_search");
+        server.tool_prefix = None;
+        assert_eq!(server.exposed_tool_name("search"), "search");
+    }
+}
@@ -0,0 +1,268 @@
+//! JSON-RPC 2.0 message helpers for the Model Context Protocol.
+
+use serde_json::{Value, json};
+
+use crate::{ToolDefinition, ToolResult};
+
+/// MCP protocol revision spoken by nanors.
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// JSON-RPC error code: method not found
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code: invalid params
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// JSON-RPC error code: parse error
+pub const PARSE_ERROR: i64 = -32700;
+
+/// Build a JSON-RPC request.
+#[must_use]
+pub fn request(id: u64, method: &str, params: Option<Value>) -> Value {
+    let mut msg = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+    });
+    if let Some(params) = params {
+        msg["params"] = params;
+    }
+    msg
+}
+
+/// Build a JSON-RPC notification (a request without an id).
+#[must_use]
+pub fn notification(method: &str, params: Option<Value>) -> Value {
+    let mut msg = json!({
+        "jsonrpc": "2.0",
+        "method": method,
+    });
+    if let Some(params) = params {
+        msg["params"] = params;
+    }
+    msg
+}
+
+/// Build a successful JSON-RPC response.
+#[must_use]
+pub fn response(id: &Value, result: Value) -> Value {
+    let mut msg = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+    });
+    msg["result"] = result;
+    msg
+}
+
+/// Build a JSON-RPC error response.
+#[must_use]
+pub fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+/// Check whether a message is the response to the request with the given id.
+#[must_use]
+pub fn is_response_to(msg: &Value, id: u64) -> bool {
+    msg.get("id").and_then(Value::as_u64) == Some(id)
+        && (msg.get("result").is_some() || msg.get("error").is_some())
+}
+
+/// Extract the result of a JSON-RPC response, turning errors into `Err`.
+pub fn into_result(mut msg: Value) -> anyhow::Result<Value> {
+    if let Some(error) = msg.get("error") {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        anyhow::bail!("MCP error {code}: {message}");
+    }
+    Ok(msg.get_mut("result").map_or(Value::Null, Value::take))
+}
+
+/// Convert an MCP tool description into a [`ToolDefinition`].
+///
+/// `exposed_name` is the name the model sees, which may carry a server prefix.
+#[must_use]
+pub fn tool_definition_from_mcp(tool: &Value, exposed_name: &str) -> ToolDefinition {
+    let description = tool
+        .get("description")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut input_schema = tool
+        .get("inputSchema")
+        .filter(|s| s.is_object())
+        .cloned()
+        .unwrap_or_else(|| json!({"type": "object"}));
+    if input_schema.get("type").is_none() {
+        input_schema["type"] = json!("object");
+    }
+    if input_schema.get("properties").is_none() {
+        input_schema["properties"] = json!({});
+    }
+
+    ToolDefinition {
+        name: exposed_name.to_string(),
+        description,
+        input_schema,
+    }
+}
+
+/// Convert a [`ToolDefinition`] into an MCP tool description.
+#[must_use]
+pub fn tool_definition_to_mcp(definition: &ToolDefinition) -> Value {
+    json!({
+        "name": definition.name,
+        "description": definition.description,
+        "inputSchema": definition.input_schema,
+    })
+}
+
+/// Convert a `tools/call` result into a [`ToolResult`].
+#[must_use]
+pub fn tool_result_from_mcp(result: &Value) -> ToolResult {
+    let is_error = result
+        .get("isError")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut parts = Vec::new();
+    if let Some(content) = result.get("content").and_then(Value::as_array) {
+        for block in content {
+            match block.get("type").and_then(Value::as_str) {
+                Some("text") => {
+                    if let Some(text) = block.get("text").and_then(Value::as_str) {
+                        parts.push(text.to_string());
+                    }
+                }
+                Some("resource") => {
+                    let resource = &block["resource"];
+                    if let Some(text) = resource.get("text").and_then(Value::as_str) {
+                        parts.push(text.to_string());
+                    } else if let Some(uri) = resource.get("uri").and_then(Value::as_str) {
+                        parts.push(format!("[resource: {uri}]"));
+                    }
+                }
+                Some(other) => {
+                    let mime = block
+                        .get("mimeType")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown");
+                    parts.push(format!("[{other}: {mime}]"));
+                }
+                None => {}
+            }
+        }
+    }
+    if parts.is_empty() {
+        if let Some(structured) = result.get("structuredContent") {
+            parts.push(structured.to_string());
+        }
+    }
+
+    let content = parts.join("\n");
+    if is_error {
+        ToolResult::error(content).with_error_type("mcp_tool_error")
+    } else {
+        ToolResult::success(content)
+    }
+}
+
+/// Convert a [`ToolResult`] into a `tools/call` result.
+#[must_use]
+pub fn tool_result_to_mcp(result: &ToolResult) -> Value {
+    json!({
+        "content": [{"type": "text", "text": result.content}],
+        "isError": result.is_error,
+    })
+}
+
+/// Parse the `data:` payloads of a `text/event-stream` body as JSON messages.
+#[must_use]
+pub fn parse_sse_messages(body: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    let mut data = String::new();
+
+    for line in body.lines().chain(std::iter::once("")) {
+        if line.is_empty() {
+            if !data.is_empty() {
+                if let Ok(msg) = serde_json::from_str(&data) {
+                    messages.push(msg);
+                }
+                data.clear();
+            }
+        } else if let Some(payload) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(payload.strip_prefix(' ').unwrap_or(payload));
+        }
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_definition_from_mcp_fills_schema() {
+        let tool = json!({"name": "lookup", "description": "Look things up"});
+        let def = tool_definition_from_mcp(&tool, "internal_lookup");
+        assert_eq!(def.name, "internal_lookup");
+        assert_eq!(def.description, "Look things up");
+        assert_eq!(def.input_schema["type"], "object");
+        assert!(def.input_schema["properties"].is_object());
+    }
+
+    #[test]
+    fn test_tool_result_from_mcp_joins_content() {
+        let result = json!({
+            "content": [
+                {"type": "text", "text": "line one"},
+                {"type": "image", "data": "...", "mimeType": "image/png"},
+                {"type": "resource", "resource": {"uri": "file:///a", "text": "body"}}
+            ]
+        });
+        let r = tool_result_from_mcp(&result);
+        assert!(!r.is_error);
+        assert_eq!(r.content, "line one\n[image: image/png]\nbody");
+    }
+
+    #[test]
+    fn test_tool_result_from_mcp_error_flag() {
+        let r = tool_result_from_mcp(&json!({
+            "content": [{"type": "text", "text": "boom"}],
+            "isError": true
+        }));
+        assert!(r.is_error);
+        assert_eq!(r.error_type.as_deref(), Some("mcp_tool_error"));
+    }
+
+    #[test]
+    fn test_parse_sse_messages() {
+        let body = "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n\n\
+                    data: {\"jsonrpc\":\"2.0\",\n\
+                    data: \"method\":\"notifications/progress\"}\n\n";
+        let msgs = parse_sse_messages(body);
+        assert_eq!(msgs.len(), 2);
+        assert!(is_response_to(&msgs[0], 1));
+        assert_eq!(msgs[1]["method"], "notifications/progress");
+    }
+
+    #[test]
+    fn test_into_result_error() {
+        let msg = error_response(&json!(3), METHOD_NOT_FOUND, "nope");
+        assert!(into_result(msg).is_err());
+    }
+}
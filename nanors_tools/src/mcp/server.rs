@@ -0,0 +1,187 @@
+//! MCP server that exposes a tool registry over newline-delimited JSON-RPC.
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, info};
+
+use super::protocol;
+use crate::StaticToolRegistry;
+
+/// Serve the enabled tools of `registry` until `reader` reaches EOF.
+///
+/// Requests are handled one at a time in arrival order.
+pub async fn serve<R, W>(
+    registry: &StaticToolRegistry,
+    server_name: &str,
+    reader: R,
+    mut writer: W,
+) -> anyhow::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str::<Value>(&line) {
+            Ok(msg) => handle_message(registry, server_name, &msg).await,
+            Err(e) => Some(protocol::error_response(
+                &Value::Null,
+                protocol::PARSE_ERROR,
+                &format!("Parse error: {e}"),
+            )),
+        };
+
+        if let Some(reply) = reply {
+            let mut out = serde_json::to_string(&reply)?;
+            out.push('\n');
+            writer.write_all(out.as_bytes()).await?;
+            writer.flush().await?;
+        }
+    }
+
+    info!("MCP client disconnected");
+    Ok(())
+}
+
+/// Serve the registry over the process stdin/stdout.
+pub async fn serve_stdio(registry: &StaticToolRegistry, server_name: &str) -> anyhow::Result<()> {
+    let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    serve(registry, server_name, stdin, tokio::io::stdout()).await
+}
+
+/// Handle one message; returns `None` for notifications.
+async fn handle_message(
+    registry: &StaticToolRegistry,
+    server_name: &str,
+    msg: &Value,
+) -> Option<Value> {
+    let method = msg
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let Some(id) = msg.get("id") else {
+        debug!("MCP notification: {method}");
+        return None;
+    };
+    let params = msg.get("params").cloned().unwrap_or(Value::Null);
+
+    let reply = match method {
+        "initialize" => protocol::response(
+            id,
+            json!({
+                "protocolVersion": protocol::PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {
+                    "name": server_name,
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }),
+        ),
+        "ping" => protocol::response(id, json!({})),
+        "tools/list" => {
+            let tools: Vec<Value> = registry
+                .definitions()
+                .iter()
+                .map(protocol::tool_definition_to_mcp)
+                .collect();
+            protocol::response(id, json!({"tools": tools}))
+        }
+        "tools/call" => {
+            let Some(name) = params.get("name").and_then(Value::as_str) else {
+                return Some(protocol::error_response(
+                    id,
+                    protocol::INVALID_PARAMS,
+                    "Missing tool name",
+                ));
+            };
+            let arguments = params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            info!("MCP tool call: {name}");
+            let result = registry.execute(name, arguments).await;
+            protocol::response(id, protocol::tool_result_to_mcp(&result))
+        }
+        _ => protocol::error_response(
+            id,
+            protocol::METHOD_NOT_FOUND,
+            &format!("Method not found: {method}"),
+        ),
+    };
+
+    Some(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::BufReader;
+
+    async fn roundtrip(requests: &[Value]) -> anyhow::Result<Vec<Value>> {
+        let registry = StaticToolRegistry::with_default_tools(".");
+        let input = requests
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut output = Vec::new();
+
+        serve(
+            &registry,
+            "test",
+            BufReader::new(input.as_bytes()),
+            &mut output,
+        )
+        .await?;
+
+        String::from_utf8(output)?
+            .lines()
+            .map(|l| serde_json::from_str(l).map_err(Into::into))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_serve_initialize_list_and_call() -> anyhow::Result<()> {
+        let replies = roundtrip(&[
+            protocol::request(1, "initialize", Some(json!({}))),
+            protocol::notification("notifications/initialized", None),
+            protocol::request(2, "tools/list", None),
+            protocol::request(
+                3,
+                "tools/call",
+                Some(json!({"name": "bash", "arguments": {"command": "echo served"}})),
+            ),
+        ])
+        .await?;
+
+        // The notification gets no reply
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["result"]["serverInfo"]["name"], "test");
+        assert_eq!(
+            replies[1]["result"]["tools"].as_array().map(Vec::len),
+            Some(6)
+        );
+        let result = protocol::tool_result_from_mcp(&replies[2]["result"]);
+        assert!(!result.is_error);
+        assert!(result.content.contains("served"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_serve_reports_protocol_errors() -> anyhow::Result<()> {
+        let replies = roundtrip(&[
+            protocol::request(1, "resources/list", None),
+            protocol::request(2, "tools/call", Some(json!({}))),
+        ])
+        .await?;
+
+        assert_eq!(replies[0]["error"]["code"], protocol::METHOD_NOT_FOUND);
+        assert_eq!(replies[1]["error"]["code"], protocol::INVALID_PARAMS);
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! Integration tests for the MCP client over stdio.
+//!
+//! These tests spawn the bundled `mcp_echo_server` binary and route tool
+//! calls to it through `StaticToolRegistry`.
+
+use nanors_tools::StaticToolRegistry;
+use nanors_tools::mcp::{McpClient, McpManager, McpServer, McpTransport};
+use serde_json::json;
+use std::time::Duration;
+
+fn echo_server_config(tool_prefix: Option<&str>) -> McpServer {
+    McpServer {
+        name: "echo".to_string(),
+        transport: McpTransport::Stdio {
+            command: env!("CARGO_BIN_EXE_mcp_echo_server").to_string(),
+            args: Vec::new(),
+            env: std::collections::HashMap::new(),
+            cwd: None,
+        },
+        tool_prefix: tool_prefix.map(String::from),
+        timeout: Duration::from_secs(10),
+    }
+}
+
+#[tokio::test]
+async fn test_client_lists_and_calls_tools() -> anyhow::Result<()> {
+    let client = McpClient::connect(&echo_server_config(None)).await?;
+
+    let tools = client.list_tools().await?;
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "echo");
+    assert_eq!(tools[0].input_schema["required"], json!(["text"]));
+
+    let result = client.call_tool("echo", json!({"text": "hello"})).await;
+    assert!(!result.is_error);
+    assert_eq!(result.content, "hello");
+
+    let missing = client.call_tool("nope", json!({})).await;
+    assert!(missing.is_error);
+    assert_eq!(missing.error_type.as_deref(), Some("mcp_error"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_registry_routes_to_mcp_server() {
+    let manager = McpManager::connect_all(&[echo_server_config(Some("remote"))]).await;
+    assert_eq!(manager.tool_count(), 1);
+
+    let mut registry = StaticToolRegistry::with_default_tools(".");
+    assert_eq!(manager.register_into(&mut registry), 1);
+    assert!(
+        registry
+            .definitions()
+            .iter()
+            .any(|d| d.name == "remote_echo")
+    );
+
+    let result = registry
+        .execute("remote_echo", json!({"text": "via registry"}))
+        .await;
+    assert!(!result.is_error);
+    assert_eq!(result.content, "via registry");
+    assert!(result.duration_ms.is_some());
+}
+
+#[tokio::test]
+async fn test_unreachable_server_is_skipped() {
+    let mut config = echo_server_config(None);
+    config.transport = McpTransport::Stdio {
+        command: "/nonexistent/mcp-server".to_string(),
+        args: Vec::new(),
+        env: std::collections::HashMap::new(),
+        cwd: None,
+    };
+    let manager = McpManager::connect_all(&[config]).await;
+    assert_eq!(manager.tool_count(), 0);
+}